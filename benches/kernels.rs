@@ -0,0 +1,27 @@
+// kernels.rs: criterion entry point for the rugmat::bench harness
+//! `cargo bench --features bench` times every default case with criterion.
+//! With `RUGMAT_BENCH_REPORT=report.json` (or `.csv`) set, the cases are
+//! also timed once more directly and written to that file.
+use criterion::{Criterion, criterion_group, criterion_main};
+use rugmat::bench::{BenchConfig, criterion_benches, run};
+use rugmat::solvers::HistoryFormat;
+
+fn kernels(c: &mut Criterion) {
+    let config = BenchConfig::default();
+    criterion_benches(c, &config);
+
+    if let Ok(path) = std::env::var("RUGMAT_BENCH_REPORT") {
+        let format = if path.ends_with(".csv") {
+            HistoryFormat::Csv
+        } else {
+            HistoryFormat::Json
+        };
+        run(&config)
+            .export(&path, format)
+            .expect("Failed to write the benchmark report");
+        println!("Wrote benchmark report to {}", path);
+    }
+}
+
+criterion_group!(benches, kernels);
+criterion_main!(benches);
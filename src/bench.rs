@@ -0,0 +1,306 @@
+// bench.rs: timing harness for the parallel kernels across precisions and thread counts
+//! Standardized problems for matmul, CG, LSQR and QR, timed at several
+//! precisions and rayon thread counts.
+//!
+//! [`criterion_benches`] registers the cases with criterion for regression
+//! tracking (`cargo bench --features bench`), and [`run`] times the same
+//! cases directly and returns a [`BenchReport`] that can be written as CSV
+//! or JSON for sizing hardware. Setting `RUGMAT_BENCH_REPORT=path.json`
+//! when running the bundled bench target writes such a report as well.
+use crate::RugMat;
+use crate::solvers::{self, HistoryFormat, SolverOptions};
+use criterion::{BenchmarkId, Criterion};
+use rug::Float;
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant};
+
+/// The kernels the harness times
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    /// Product of two dense n×n matrices
+    Matmul,
+    /// CG on the n×n 1-D Laplacian tridiag(-1, 2, -1), n iterations
+    Cg,
+    /// LSQR on a 2n×n cosine fit, n iterations
+    Lsqr,
+    /// Householder QR of the same 2n×n cosine fit
+    Qr,
+}
+
+impl Kernel {
+    pub const ALL: [Kernel; 4] = [Kernel::Matmul, Kernel::Cg, Kernel::Lsqr, Kernel::Qr];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kernel::Matmul => "matmul",
+            Kernel::Cg => "cg",
+            Kernel::Lsqr => "lsqr",
+            Kernel::Qr => "qr",
+        }
+    }
+}
+
+/// Which cases to run; every combination of the lists is timed
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub kernels: Vec<Kernel>,
+    pub sizes: Vec<usize>,
+    pub precisions: Vec<u32>,
+    pub threads: Vec<usize>,
+    /// Timed repetitions per case in [`run`]
+    pub samples: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        let mut threads = vec![1, rayon::current_num_threads()];
+        threads.dedup();
+        BenchConfig {
+            kernels: Kernel::ALL.to_vec(),
+            sizes: vec![32, 64],
+            precisions: vec![64, 256, 1024],
+            threads,
+            samples: 5,
+        }
+    }
+}
+
+/// Deterministic inputs for one kernel, size and precision
+enum Case {
+    Matmul(RugMat, RugMat),
+    Solve(Kernel, RugMat, Vec<Float>),
+    Qr(RugMat),
+}
+
+/// Dense n×n matrix with entries 1/(i + 2j + 1) + [i = j]
+fn dense(n: usize, precision: u32) -> RugMat {
+    let mut a = RugMat::identity(n, precision);
+    for j in 0..n {
+        for i in 0..n {
+            a[(i, j)] += Float::with_val(precision, i + 2 * j + 1).recip();
+        }
+    }
+    a
+}
+
+fn laplacian(n: usize, precision: u32) -> RugMat {
+    let mut a = RugMat::new(n, n, precision);
+    for i in 0..n {
+        a[(i, i)] = Float::with_val(precision, 2);
+        if i + 1 < n {
+            a[(i, i + 1)] = Float::with_val(precision, -1);
+            a[(i + 1, i)] = Float::with_val(precision, -1);
+        }
+    }
+    a
+}
+
+/// 2n×n least-squares fit of exp(-θ) by cos(jθ), j < n, at the midpoints
+/// θᵢ = π(i + ½)/2n, which keeps the columns well conditioned
+fn cosine_fit(n: usize, precision: u32) -> (RugMat, Vec<Float>) {
+    let m = 2 * n;
+    let pi = Float::with_val(precision, rug::float::Constant::Pi);
+    let mut a = RugMat::new(m, n, precision);
+    let mut b = Vec::with_capacity(m);
+    for i in 0..m {
+        let theta = (Float::with_val(precision, 2 * i + 1) * &pi) / (2 * m) as u32;
+        for j in 0..n {
+            a[(i, j)] = (theta.clone() * j as u32).cos();
+        }
+        b.push((-theta).exp());
+    }
+    (a, b)
+}
+
+impl Case {
+    fn new(kernel: Kernel, n: usize, precision: u32) -> Self {
+        match kernel {
+            Kernel::Matmul => Case::Matmul(dense(n, precision), dense(n, precision).transpose()),
+            Kernel::Cg => {
+                let a = laplacian(n, precision);
+                let b = a.matmul_vec(&vec![Float::with_val(precision, 1); n]);
+                Case::Solve(kernel, a, b)
+            }
+            Kernel::Lsqr => {
+                let (a, b) = cosine_fit(n, precision);
+                Case::Solve(kernel, a, b)
+            }
+            Kernel::Qr => Case::Qr(cosine_fit(n, precision).0),
+        }
+    }
+
+    fn run(&self) {
+        match self {
+            Case::Matmul(a, b) => {
+                black_box(a.matmul(b));
+            }
+            Case::Solve(kernel, a, b) => {
+                // A zero tolerance keeps the work fixed at n iterations
+                let precision = b[0].prec();
+                let options = SolverOptions::new(a.ncols()).rel_tol(Float::with_val(precision, 0));
+                let outcome = if *kernel == Kernel::Cg {
+                    solvers::conjugate_gradient_with_info(a, b, &options)
+                } else {
+                    solvers::lsqr_with_info(a, b, &options)
+                };
+                black_box(outcome);
+            }
+            Case::Qr(a) => {
+                black_box(a.qr());
+            }
+        }
+    }
+}
+
+fn pool(threads: usize) -> rayon::ThreadPool {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Failed to build the benchmark thread pool")
+}
+
+/// Timing of one case over [`BenchConfig::samples`] repetitions
+#[derive(Debug, Clone)]
+pub struct BenchRecord {
+    pub kernel: Kernel,
+    pub size: usize,
+    pub precision: u32,
+    pub threads: usize,
+    pub median: Duration,
+    pub min: Duration,
+}
+
+/// All timings of a [`run`], with the crate version they were taken with
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub records: Vec<BenchRecord>,
+    pub crate_version: String,
+}
+
+impl BenchReport {
+    /// Write one row per case: kernel, size, precision, threads and the
+    /// median and minimum times in seconds
+    pub fn export(&self, path: &str, format: HistoryFormat) -> std::io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        match format {
+            HistoryFormat::Csv => {
+                writeln!(w, "kernel,size,precision,threads,median_s,min_s")?;
+                for rec in &self.records {
+                    writeln!(
+                        w,
+                        "{},{},{},{},{},{}",
+                        rec.kernel.name(),
+                        rec.size,
+                        rec.precision,
+                        rec.threads,
+                        rec.median.as_secs_f64(),
+                        rec.min.as_secs_f64()
+                    )?;
+                }
+            }
+            HistoryFormat::Json => {
+                writeln!(w, "{{")?;
+                writeln!(w, "  \"crate_version\": \"{}\",", self.crate_version)?;
+                writeln!(w, "  \"records\": [")?;
+                for (k, rec) in self.records.iter().enumerate() {
+                    let sep = if k + 1 < self.records.len() { "," } else { "" };
+                    writeln!(
+                        w,
+                        "    {{\"kernel\": \"{}\", \"size\": {}, \"precision\": {}, \"threads\": {}, \"median_s\": {}, \"min_s\": {}}}{}",
+                        rec.kernel.name(),
+                        rec.size,
+                        rec.precision,
+                        rec.threads,
+                        rec.median.as_secs_f64(),
+                        rec.min.as_secs_f64(),
+                        sep
+                    )?;
+                }
+                writeln!(w, "  ]")?;
+                writeln!(w, "}}")?;
+            }
+        }
+        w.flush()
+    }
+}
+
+/// Time every configured case directly, without criterion's statistics
+pub fn run(config: &BenchConfig) -> BenchReport {
+    assert!(config.samples >= 1, "Need at least one sample per case");
+    let mut records = Vec::new();
+    for &threads in &config.threads {
+        let pool = pool(threads);
+        for &kernel in &config.kernels {
+            for &precision in &config.precisions {
+                for &size in &config.sizes {
+                    let case = Case::new(kernel, size, precision);
+                    let mut times: Vec<Duration> = (0..config.samples)
+                        .map(|_| {
+                            let start = Instant::now();
+                            pool.install(|| case.run());
+                            start.elapsed()
+                        })
+                        .collect();
+                    times.sort();
+                    records.push(BenchRecord {
+                        kernel,
+                        size,
+                        precision,
+                        threads,
+                        median: times[times.len() / 2],
+                        min: times[0],
+                    });
+                }
+            }
+        }
+    }
+    BenchReport {
+        records,
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// Register every configured case with criterion, one group per kernel and
+/// ids of the form `<precision>bit/<threads>t/<size>`
+pub fn criterion_benches(c: &mut Criterion, config: &BenchConfig) {
+    for &kernel in &config.kernels {
+        let mut group = c.benchmark_group(kernel.name());
+        for &threads in &config.threads {
+            let pool = pool(threads);
+            for &precision in &config.precisions {
+                for &size in &config.sizes {
+                    let case = Case::new(kernel, size, precision);
+                    let id = BenchmarkId::new(format!("{}bit/{}t", precision, threads), size);
+                    group
+                        .bench_function(id, |bencher| bencher.iter(|| pool.install(|| case.run())));
+                }
+            }
+        }
+        group.finish();
+    }
+}
+
+#[test]
+fn test_run_covers_every_case_and_exports() {
+    let config = BenchConfig {
+        kernels: Kernel::ALL.to_vec(),
+        sizes: vec![4],
+        precisions: vec![64, 128],
+        threads: vec![1, 2],
+        samples: 2,
+    };
+    let report = run(&config);
+    assert_eq!(report.records.len(), 4 * 2 * 2);
+    assert!(report.records.iter().all(|r| r.min <= r.median));
+
+    let path = std::env::temp_dir().join(format!("rugmat_bench_test_{}.json", std::process::id()));
+    report
+        .export(path.to_str().unwrap(), HistoryFormat::Json)
+        .unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text.matches("\"kernel\"").count(), report.records.len());
+    assert!(text.contains("\"kernel\": \"lsqr\", \"size\": 4, \"precision\": 128"));
+    std::fs::remove_file(path).unwrap();
+}
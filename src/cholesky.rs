@@ -0,0 +1,312 @@
+// cholesky.rs: Cholesky factorization of symmetric positive definite matrices
+use crate::RugMat;
+use crate::svd::working_tol;
+use rayon::prelude::*;
+use rug::Float;
+use rug::ops::CompleteRound;
+use std::fmt;
+
+/// Cholesky factorization A = L Lᵗ with L lower triangular
+#[derive(Debug, Clone)]
+pub struct Cholesky {
+    pub l: RugMat,
+}
+
+/// Returned by [`RugMat::cholesky`] when a pivot is not positive
+#[derive(Debug, Clone)]
+pub struct NotPositiveDefinite {
+    /// Column at which the factorization broke down
+    pub column: usize,
+    /// The offending pivot a_jj - Σ l_jk²
+    pub pivot: Float,
+}
+
+impl fmt::Display for NotPositiveDefinite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "matrix is not positive definite: pivot {:.6e} at column {}",
+            self.pivot, self.column
+        )
+    }
+}
+
+impl std::error::Error for NotPositiveDefinite {}
+
+/// Pivoted Cholesky factorization Pᵗ A P ≈ L Lᵗ of a symmetric positive
+/// semidefinite matrix, where column j of P is e_{perm[j]}.
+///
+/// Only the first `rank` columns of L are nonzero. When `rank` < n the
+/// factorization stopped because the remaining diagonal was negligible
+/// or negative, i.e. A is not numerically positive definite.
+#[derive(Debug, Clone)]
+pub struct PivotedCholesky {
+    pub l: RugMat,
+    pub perm: Vec<usize>,
+    pub rank: usize,
+}
+
+impl RugMat {
+    /// Cholesky factorization reading only the lower triangle of self.
+    ///
+    /// Left-looking: column j of L is computed in parallel over its rows
+    /// from the already finished columns.
+    pub fn cholesky(&self) -> Result<Cholesky, NotPositiveDefinite> {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let mut l = RugMat::new(n, n, precision);
+
+        for j in 0..n {
+            let (done, rest) = l.as_mut_slice().split_at_mut(j * n);
+            let col = &mut rest[..n];
+
+            let mut pivot = self[(j, j)].clone();
+            for k in 0..j {
+                let ljk = &done[k * n + j];
+                pivot -= (ljk * ljk).complete(precision);
+            }
+            if pivot <= 0 {
+                return Err(NotPositiveDefinite { column: j, pivot });
+            }
+            let ljj = pivot.sqrt();
+
+            col[j + 1..]
+                .par_iter_mut()
+                .enumerate()
+                .for_each(|(offset, lij)| {
+                    let i = j + 1 + offset;
+                    let mut sum = self[(i, j)].clone();
+                    for k in 0..j {
+                        sum -= (&done[k * n + i] * &done[k * n + j]).complete(precision);
+                    }
+                    *lij = sum / &ljj;
+                });
+            col[j] = ljj;
+        }
+
+        Ok(Cholesky { l })
+    }
+}
+
+impl RugMat {
+    /// Cholesky factorization with diagonal pivoting (as in LAPACK `xPSTRF`).
+    ///
+    /// Each step takes the largest remaining diagonal entry as pivot and
+    /// stops once it is at most n·ε·max aᵢᵢ, with ε the working precision,
+    /// so semidefinite and slightly indefinite matrices still return the
+    /// factor of their well-determined part together with its rank.
+    pub fn cholesky_pivoted(&self) -> PivotedCholesky {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let mut a = self.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+
+        let max_diag =
+            (0..n)
+                .map(|i| a[(i, i)].clone())
+                .fold(
+                    Float::with_val(precision, 0),
+                    |m, d| if d > m { d } else { m },
+                );
+        let threshold = max_diag * working_tol(precision) * n as u32;
+
+        let mut rank = n;
+        for j in 0..n {
+            let mut p = j;
+            for i in (j + 1)..n {
+                if a[(i, i)] > a[(p, p)] {
+                    p = i;
+                }
+            }
+            if a[(p, p)] <= threshold {
+                rank = j;
+                break;
+            }
+            if p != j {
+                a.as_mut_slice()
+                    .par_chunks_mut(n)
+                    .for_each(|col| col.swap(j, p));
+                let (left, right) = a.as_mut_slice().split_at_mut(p * n);
+                left[j * n..(j + 1) * n].swap_with_slice(&mut right[..n]);
+                perm.swap(j, p);
+            }
+
+            let (head, trailing) = a.as_mut_slice().split_at_mut((j + 1) * n);
+            let col = &mut head[j * n..];
+            col[j].sqrt_mut();
+            let (pivot, below) = col[j..].split_at_mut(1);
+            for l in below.iter_mut() {
+                *l /= &pivot[0];
+            }
+            let multipliers = &col[j + 1..];
+            trailing
+                .par_chunks_mut(n)
+                .enumerate()
+                .for_each(|(offset, target)| {
+                    let lk = &multipliers[offset];
+                    for (x, li) in target[j + 1..].iter_mut().zip(multipliers) {
+                        *x -= (li * lk).complete(precision);
+                    }
+                });
+        }
+
+        let mut l = RugMat::new(n, n, precision);
+        for j in 0..rank {
+            for i in j..n {
+                l[(i, j)] = a[(i, j)].clone();
+            }
+        }
+        PivotedCholesky { l, perm, rank }
+    }
+}
+
+impl Cholesky {
+    /// Solve L y = b by forward substitution
+    pub fn solve_lower(&self, b: &[Float]) -> Vec<Float> {
+        let n = self.l.nrows();
+        assert_eq!(b.len(), n);
+        let mut y = b.to_vec();
+        for j in 0..n {
+            y[j] /= &self.l[(j, j)];
+            let (done, rest) = y.split_at_mut(j + 1);
+            for (i, yi) in rest.iter_mut().enumerate() {
+                *yi -= (&self.l[(j + 1 + i, j)] * &done[j]).complete(yi.prec());
+            }
+        }
+        y
+    }
+
+    /// Solve A x = b by forward substitution with L, then backward with Lᵗ
+    pub fn solve(&self, b: &[Float]) -> Vec<Float> {
+        self.solve_upper(&self.solve_lower(b))
+    }
+
+    /// Solve Lᵗ x = y by backward substitution
+    pub fn solve_upper(&self, y: &[Float]) -> Vec<Float> {
+        let n = self.l.nrows();
+        assert_eq!(y.len(), n);
+        let mut x = y.to_vec();
+        for i in (0..n).rev() {
+            for k in (i + 1)..n {
+                let update = (&self.l[(k, i)] * &x[k]).complete(x[i].prec());
+                x[i] -= update;
+            }
+            x[i] /= &self.l[(i, i)];
+        }
+        x
+    }
+
+    /// det(A) = Π lᵢᵢ²
+    pub fn det(&self) -> Float {
+        let mut det = Float::with_val(self.l.as_slice()[0].prec(), 1);
+        for i in 0..self.l.nrows() {
+            det *= &self.l[(i, i)];
+        }
+        det.square()
+    }
+
+    /// A⁻¹, solving for the columns of the identity in parallel
+    pub fn inverse(&self) -> RugMat {
+        let n = self.l.nrows();
+        let precision = self.l.as_slice()[0].prec();
+        let mut inv = RugMat::new(n, n, precision);
+        inv.as_mut_slice()
+            .par_chunks_mut(n)
+            .enumerate()
+            .for_each(|(j, col)| {
+                let mut e = vec![Float::with_val(precision, 0); n];
+                e[j] = Float::with_val(precision, 1);
+                col.clone_from_slice(&self.solve(&e));
+            });
+        inv
+    }
+}
+
+#[test]
+fn test_cholesky_solve_det_inverse() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(
+        [[4, 12, -16], [12, 37, -43], [-16, -43, 98]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let chol = a.cholesky().unwrap();
+    // Textbook example: L = [[2, 0, 0], [6, 1, 0], [-8, 5, 3]]
+    assert!(chol.l.is_lower_triangular());
+    assert_eq!(chol.l.as_slice(), [2, 6, -8, 0, 1, 5, 0, 0, 3]);
+    assert_eq!(chol.det(), 36);
+
+    let b = a.matmul_vec(&[
+        Float::with_val(precision, 1),
+        Float::with_val(precision, -1),
+        Float::with_val(precision, 2),
+    ]);
+    let x = chol.solve(&b);
+    for (xi, e) in x.iter().zip([1i32, -1, 2]) {
+        assert!((xi.clone() - e).abs() < 1e-30);
+    }
+    let diff = a
+        .matmul(&chol.inverse())
+        .compare(&RugMat::identity(3, precision));
+    assert!(diff.max_abs < 1e-30);
+}
+
+#[test]
+fn test_cholesky_rejects_indefinite() {
+    let precision = 64;
+    let a = RugMat::diagonal_from_f64(&[1.0, -2.0], precision);
+    let err = a.cholesky().unwrap_err();
+    assert_eq!(err.column, 1);
+    assert_eq!(err.pivot, -2);
+}
+
+#[test]
+fn test_cholesky_pivoted_full_and_deficient() {
+    let precision = 128;
+    let mat = |rows: &[[i32; 3]]| {
+        RugMat::from_vecvec(
+            rows.iter()
+                .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+                .collect(),
+        )
+    };
+    let permuted = |a: &RugMat, perm: &[usize]| {
+        let mut b = a.clone();
+        for j in 0..a.ncols() {
+            for i in 0..a.nrows() {
+                b[(i, j)] = a[(perm[i], perm[j])].clone();
+            }
+        }
+        b
+    };
+
+    let a = mat(&[[4, 12, -16], [12, 37, -43], [-16, -43, 98]]);
+    let pc = a.cholesky_pivoted();
+    assert_eq!(pc.rank, 3);
+    // Largest diagonal entry first
+    assert_eq!(pc.perm[0], 2);
+    let llt = pc.l.matmul(&pc.l.transpose());
+    assert!(llt.compare(&permuted(&a, &pc.perm)).max_abs < 1e-30);
+
+    // B Bᵗ with B = [[1, 0], [1, 1], [2, 1]] has rank 2
+    let b = mat(&[[2, 1, 3], [1, 2, 3], [3, 3, 6]]);
+    let pc = b.cholesky_pivoted();
+    assert_eq!(pc.rank, 2);
+    let llt = pc.l.matmul(&pc.l.transpose());
+    assert!(llt.compare(&permuted(&b, &pc.perm)).max_abs < 1e-30);
+
+    // Indefinite: stops before the negative direction
+    let c = RugMat::diagonal_from_f64(&[1.0, -2.0, 3.0], precision);
+    assert_eq!(c.cholesky_pivoted().rank, 2);
+}
@@ -0,0 +1,79 @@
+// compare.rs: entrywise comparison of two matrices, e.g. against a reference
+use crate::RugMat;
+use rayon::prelude::*;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Summary of the entrywise differences between two matrices
+#[derive(Debug, Clone)]
+pub struct DiffReport {
+    /// max |a_ij - b_ij|
+    pub max_abs: Float,
+    /// max |a_ij - b_ij| / max(|a_ij|, |b_ij|), with 0/0 taken as 0
+    pub max_rel: Float,
+    /// ‖A - B‖_F
+    pub frobenius: Float,
+    /// (row, col) of the entry with the largest absolute difference
+    pub worst: (usize, usize),
+}
+
+impl RugMat {
+    /// Compare against other (typically a reference) in one pass
+    pub fn compare(&self, other: &RugMat) -> DiffReport {
+        assert_eq!(
+            (self.nrows(), self.ncols()),
+            (other.nrows(), other.ncols()),
+            "Shape mismatch in compare"
+        );
+        let precision = self.as_slice()[0].prec().max(other.as_slice()[0].prec());
+        let zero = || Float::with_val(precision, 0);
+
+        let (max_abs, worst, max_rel, sum_sq) = self
+            .as_slice()
+            .par_iter()
+            .zip(other.as_slice().par_iter())
+            .enumerate()
+            .map(|(k, (a, b))| {
+                let diff = (a - b).complete(precision).abs();
+                let scale = Float::with_val(precision, a.abs_ref()).max(&b.clone().abs());
+                let rel = if scale.is_zero() {
+                    zero()
+                } else {
+                    (&diff / &scale).complete(precision)
+                };
+                let sq = (&diff * &diff).complete(precision);
+                (diff, k, rel, sq)
+            })
+            .reduce(
+                || (zero(), 0, zero(), zero()),
+                |a, b| {
+                    let (abs, k) = if b.0 > a.0 { (b.0, b.1) } else { (a.0, a.1) };
+                    let rel = if b.2 > a.2 { b.2 } else { a.2 };
+                    (abs, k, rel, a.3 + b.3)
+                },
+            );
+
+        DiffReport {
+            max_abs,
+            max_rel,
+            frobenius: sum_sq.sqrt(),
+            worst: (worst % self.nrows(), worst / self.nrows()),
+        }
+    }
+}
+
+#[test]
+fn test_compare_against_reference() {
+    let precision = 128;
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0, 4.0], precision);
+    let mut b = a.clone();
+    b[(2, 1)] = Float::with_val(precision, 3);
+    b[(0, 0)] = Float::with_val(precision, 1.5);
+
+    let report = a.compare(&b);
+    assert_eq!(report.max_abs, 3);
+    assert_eq!(report.worst, (2, 1));
+    // 0 vs 3 is a full relative miss
+    assert_eq!(report.max_rel, 1);
+    assert_eq!(report.frobenius, Float::with_val(precision, 9.25).sqrt());
+}
@@ -0,0 +1,202 @@
+// compatibility.rs: golden RugMat files that every release must keep loading
+//! Small reference files of each on-disk format version, embedded in the
+//! library, with the exact matrices and metadata they hold.
+//!
+//! The files were written once by the release that introduced their version
+//! and are never regenerated: a change to `float_serializer` or the
+//! container that no longer reads them breaks archives in the field.
+//! A new format version adds its own golden files next to the old ones.
+//!
+//! ```
+//! rugmat::compatibility::verify_golden().unwrap();
+//! ```
+use crate::RugMat;
+use crate::rugmat_io::Metadata;
+use rug::Float;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// One reference file and what loading it must produce
+struct Golden {
+    name: &'static str,
+    bytes: &'static [u8],
+    version: u8,
+    matrix: fn() -> RugMat,
+    metadata: fn() -> Option<Metadata>,
+}
+
+const GOLDEN: [Golden; 3] = [
+    Golden {
+        name: "v1_mixed.rmat",
+        bytes: include_bytes!("../golden/v1_mixed.rmat"),
+        version: 1,
+        matrix: mixed,
+        metadata: || None,
+    },
+    Golden {
+        name: "v2_mixed.rmat",
+        bytes: include_bytes!("../golden/v2_mixed.rmat"),
+        version: 2,
+        matrix: mixed,
+        metadata: || None,
+    },
+    Golden {
+        name: "v2_uniform_meta.rmat",
+        bytes: include_bytes!("../golden/v2_uniform_meta.rmat"),
+        version: 2,
+        matrix: uniform,
+        metadata: || Some(uniform_metadata()),
+    },
+];
+
+/// 2×3 with a different precision per entry and the special values whose
+/// sign and exponent encodings are easiest to break
+fn mixed() -> RugMat {
+    let data = vec![
+        Float::with_val(96, 1) / 3u32,
+        -Float::with_val(53, 0),
+        Float::with_val(64, f64::INFINITY),
+        -Float::with_val(128, Float::i_exp(1, -1000)),
+        Float::with_val(200, Float::parse("1e300").unwrap()),
+        Float::with_val(256, rug::float::Constant::Pi),
+    ];
+    RugMat::from_col_major(2, 3, data)
+}
+
+/// 3×2 at a shared precision of 192 bits, stored in the compact encoding
+fn uniform() -> RugMat {
+    let precision = 192;
+    let data = vec![
+        Float::with_val(precision, 2).sqrt(),
+        -Float::with_val(precision, 3).sqrt(),
+        Float::with_val(precision, f64::NAN),
+        -Float::with_val(precision, 0),
+        Float::with_val(precision, f64::NEG_INFINITY),
+        Float::with_val(precision, 1) / 7u32,
+    ];
+    RugMat::from_col_major(3, 2, data)
+}
+
+fn uniform_metadata() -> Metadata {
+    let mut scalars = BTreeMap::new();
+    scalars.insert("third".to_string(), Float::with_val(77, 1) / 3u32);
+    scalars.insert(
+        "ln2".to_string(),
+        Float::with_val(300, rug::float::Constant::Log2),
+    );
+    let mut extra = BTreeMap::new();
+    extra.insert("kernel".to_string(), "Matérn ν=5/2".to_string());
+    Metadata {
+        creator: Some("rugmat golden".to_string()),
+        timestamp: Some(1_700_000_000),
+        description: Some("compact entries with metadata".to_string()),
+        extra,
+        scalars,
+    }
+}
+
+/// A golden file that no longer loads, or loads to different contents
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenMismatch {
+    pub file: &'static str,
+    pub reason: String,
+}
+
+impl fmt::Display for GoldenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "golden file {}: {}", self.file, self.reason)
+    }
+}
+
+impl std::error::Error for GoldenMismatch {}
+
+/// Same precision, sign and value, with NaN equal to NaN
+fn identical(a: &Float, b: &Float) -> bool {
+    a.prec() == b.prec()
+        && a.is_sign_negative() == b.is_sign_negative()
+        && (a == b || (a.is_nan() && b.is_nan()))
+}
+
+/// Names of the embedded golden files, oldest format first
+pub fn golden_files() -> impl Iterator<Item = &'static str> {
+    GOLDEN.iter().map(|g| g.name)
+}
+
+/// Load every embedded golden file and compare its version, shape, entries
+/// (bit for bit, precision included) and metadata with the expected ones;
+/// returns the first mismatch
+pub fn verify_golden() -> Result<(), GoldenMismatch> {
+    for golden in &GOLDEN {
+        let fail = |reason: String| GoldenMismatch {
+            file: golden.name,
+            reason,
+        };
+        let (info, loaded) =
+            RugMat::load_from_bytes(golden.bytes).map_err(|e| fail(e.to_string()))?;
+        if info.version != golden.version {
+            return Err(fail(format!(
+                "header says version {}, expected {}",
+                info.version, golden.version
+            )));
+        }
+
+        let expected = (golden.matrix)();
+        if loaded.shape() != expected.shape() {
+            return Err(fail(format!(
+                "shape {} instead of {}",
+                loaded.shape(),
+                expected.shape()
+            )));
+        }
+        let entries = loaded.as_slice().iter().zip(expected.as_slice());
+        if let Some(k) = entries.clone().position(|(a, b)| !identical(a, b)) {
+            let (a, b) = entries.clone().nth(k).unwrap();
+            return Err(fail(format!(
+                "entry ({}, {}) is {} at {} bits, expected {} at {} bits",
+                k % expected.nrows(),
+                k / expected.nrows(),
+                a,
+                a.prec(),
+                b,
+                b.prec()
+            )));
+        }
+
+        let metadata = (golden.metadata)();
+        let scalars_match = match (&info.metadata, &metadata) {
+            (Some(a), Some(b)) => {
+                a.scalars.len() == b.scalars.len()
+                    && a.scalars
+                        .iter()
+                        .zip(&b.scalars)
+                        .all(|((ka, va), (kb, vb))| ka == kb && identical(va, vb))
+            }
+            _ => true,
+        };
+        if info.metadata != metadata || !scalars_match {
+            return Err(fail(format!(
+                "metadata {:?}, expected {:?}",
+                info.metadata, metadata
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_golden_files_load() {
+    verify_golden().unwrap();
+    assert_eq!(golden_files().count(), GOLDEN.len());
+}
+
+#[test]
+fn test_damaged_golden_is_rejected() {
+    // A flipped limb bit in the last entry keeps v1's length fields intact
+    // but must trip the trailing checksum
+    let golden = &GOLDEN[0];
+    let mut bytes = golden.bytes.to_vec();
+    let at = bytes.len() - 32 - 1;
+    bytes[at] ^= 1;
+    let err = RugMat::load_from_bytes(&bytes).unwrap_err();
+    assert_eq!(err.to_string(), "Checksum mismatch");
+}
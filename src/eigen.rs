@@ -0,0 +1,1036 @@
+// eigen.rs: dense eigensolvers
+use crate::RugMat;
+use crate::cholesky::NotPositiveDefinite;
+use crate::krylov::lanczos_tridiagonal;
+use crate::lu::LU;
+use crate::qr::{apply_reflector, householder_in_place};
+use crate::rugmat::dot;
+use crate::structured::symmetric_tridiagonal_eigen;
+use crate::svd::working_tol;
+use rayon::prelude::*;
+use rug::ops::CompleteRound;
+use rug::{Complex, Float};
+
+impl RugMat {
+    /// Eigen-decomposition A = Z Λ Zᵗ of a symmetric matrix.
+    ///
+    /// Householder tridiagonalization followed by
+    /// [`symmetric_tridiagonal_eigen`], whose max_iters and tol it takes.
+    /// Returns the eigenvalues in ascending order and Z with the matching
+    /// orthonormal eigenvectors as columns. Symmetric matrices are already
+    /// balanced, so unlike [`RugMat::eigenvalues`] no scaling is applied.
+    /// Panics if the tridiagonal QR does not converge within `max_iters`
+    /// sweeps.
+    pub fn eigh(&self, max_iters: usize, tol: f64) -> (Vec<Float>, RugMat) {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let mut a = self.clone();
+        let mut reflectors: Vec<(Vec<Float>, Float)> = Vec::new();
+        let mut off = Vec::with_capacity(n.saturating_sub(1));
+
+        for k in 0..n.saturating_sub(2) {
+            let (head, trailing) = a.as_mut_slice().split_at_mut((k + 1) * n);
+            let x = &mut head[k * n + k + 1..];
+            let tau = householder_in_place(x, precision);
+            off.push(x[0].clone());
+            if tau.is_zero() {
+                continue;
+            }
+            let mut v = x.to_vec();
+            v[0] = Float::with_val(precision, 1);
+
+            // B ← H B H for the trailing block, via p = τBv and
+            // w = p - (τ/2)(pᵗv) v, so that B ← B - v wᵗ - w vᵗ
+            let r = n - k - 1;
+            let p: Vec<Float> = (0..r)
+                .into_par_iter()
+                .map(|i| {
+                    let mut acc = Float::with_val(precision, 0);
+                    for (j, vj) in v.iter().enumerate() {
+                        acc += &trailing[j * n + k + 1 + i] * vj;
+                    }
+                    acc * &tau
+                })
+                .collect();
+            let mut ptv = Float::with_val(precision, 0);
+            for (pi, vi) in p.iter().zip(&v) {
+                ptv += pi * vi;
+            }
+            let half: Float = (&tau * &ptv).complete(precision) / 2u32;
+            let w: Vec<Float> = p
+                .iter()
+                .zip(&v)
+                .map(|(pi, vi)| pi - (&half * vi).complete(precision))
+                .collect();
+            trailing.par_chunks_mut(n).enumerate().for_each(|(j, col)| {
+                for (i, bij) in col[k + 1..].iter_mut().enumerate() {
+                    *bij -= (&v[i] * &w[j]).complete(precision);
+                    *bij -= (&w[i] * &v[j]).complete(precision);
+                }
+            });
+            reflectors.push((v, tau));
+        }
+        if n >= 2 {
+            off.push(a[(n - 1, n - 2)].clone());
+        }
+        let diag: Vec<Float> = (0..n).map(|k| a[(k, k)].clone()).collect();
+
+        let (values, mut z) = symmetric_tridiagonal_eigen(&diag, &off, max_iters, tol);
+        // Z ← Q Z with Q = H₀⋯Hₙ₋₃; reflector k acts on entries k+1..
+        z.as_mut_slice().par_chunks_mut(n).for_each(|col| {
+            for (k, (v, tau)) in reflectors.iter().enumerate().rev() {
+                apply_reflector(v, tau, &mut col[k + 1..]);
+            }
+        });
+        (values, z)
+    }
+}
+
+/// Reduce a square matrix to upper Hessenberg form by Householder
+/// similarity transforms, zeroing the entries below the subdiagonal. When
+/// `q` is given (initially the identity) it accumulates the transforms, so
+/// that A = Q H Qᵗ.
+pub(crate) fn hessenberg_in_place(a: &mut RugMat, mut q: Option<&mut RugMat>) {
+    let n = a.nrows();
+    let precision = a.as_slice()[0].prec();
+    for k in 0..n.saturating_sub(2) {
+        let (head, trailing) = a.as_mut_slice().split_at_mut((k + 1) * n);
+        let x = &mut head[k * n + k + 1..];
+        let tau = householder_in_place(x, precision);
+        if tau.is_zero() {
+            continue;
+        }
+        let mut v = x.to_vec();
+        v[0] = Float::with_val(precision, 1);
+        for xi in &mut x[1..] {
+            *xi = Float::with_val(precision, 0);
+        }
+
+        // A ← H A on columns k+1.., then A ← A H on all rows
+        trailing
+            .par_chunks_mut(n)
+            .for_each(|col| apply_reflector(&v, &tau, &mut col[k + 1..]));
+        reflect_columns(trailing, n, &v, &tau);
+        if let Some(q) = q.as_deref_mut() {
+            reflect_columns(&mut q.as_mut_slice()[(k + 1) * n..], n, &v, &tau);
+        }
+    }
+}
+
+/// M ← M (I - τvvᵗ) for the columns of `cols` (column-major, n rows)
+fn reflect_columns(cols: &mut [Float], n: usize, v: &[Float], tau: &Float) {
+    let precision = tau.prec();
+    let w: Vec<Float> = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let mut acc = Float::with_val(precision, 0);
+            for (j, vj) in v.iter().enumerate() {
+                acc += &cols[j * n + i] * vj;
+            }
+            acc * tau
+        })
+        .collect();
+    cols.par_chunks_mut(n).enumerate().for_each(|(j, col)| {
+        for (aij, wi) in col.iter_mut().zip(&w) {
+            *aij -= (wi * &v[j]).complete(precision);
+        }
+    });
+}
+
+/// Real Schur decomposition A = Q T Qᵗ from [`RugMat::schur`]
+#[derive(Debug, Clone)]
+pub struct Schur {
+    /// Orthogonal
+    pub q: RugMat,
+    /// Quasi-upper-triangular: 1×1 blocks hold real eigenvalues and 2×2
+    /// blocks complex conjugate pairs
+    pub t: RugMat,
+}
+
+impl RugMat {
+    /// Eigenvalues of a general square matrix, in no particular order.
+    ///
+    /// The matrix is first balanced (see [`RugMat::balance`]), then reduced
+    /// to Hessenberg form by Householder reflections and finished by Francis
+    /// double-shift QR, so complex pairs are found in real arithmetic.
+    /// A subdiagonal entry is dropped once it is below tol times its two
+    /// diagonal neighbours; `max_iters` bounds the sweeps spent on any one
+    /// eigenvalue, with exceptional shifts every 10 sweeps. Panics if an
+    /// eigenvalue is still not split off after `max_iters` sweeps.
+    pub fn eigenvalues(&self, max_iters: usize, tol: f64) -> Vec<Complex> {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let mut hess = self.balance().balanced;
+        hessenberg_in_place(&mut hess, None);
+        let precision = hess.as_slice()[0].prec();
+        let (wr, wi) = francis_qr(&mut hess, None, max_iters, tol);
+        wr.into_iter()
+            .zip(wi)
+            .map(|(re, im)| Complex::with_val(precision, (re, im)))
+            .collect()
+    }
+
+    /// Real Schur decomposition A = Q T Qᵗ with Q orthogonal and T
+    /// quasi-upper-triangular, the backbone for functions of nonsymmetric
+    /// matrices.
+    ///
+    /// Hessenberg reduction and Francis double-shift QR as in
+    /// [`RugMat::eigenvalues`] (same max_iters and tol), but with every
+    /// transform applied to the whole matrix and accumulated into Q. Real
+    /// eigenvalue pairs are split into 1×1 blocks, so only complex pairs
+    /// remain as 2×2 blocks. No balancing is done since it is not
+    /// orthogonal. Panics, like [`RugMat::eigenvalues`], if the QR iteration
+    /// runs out of sweeps.
+    pub fn schur(&self, max_iters: usize, tol: f64) -> Schur {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let mut t = self.clone();
+        let mut q = RugMat::identity(n, precision);
+        hessenberg_in_place(&mut t, Some(&mut q));
+        francis_qr(&mut t, Some(&mut q), max_iters, tol);
+        for j in 0..n {
+            for i in (j + 2)..n {
+                t[(i, j)] = Float::with_val(precision, 0);
+            }
+        }
+        Schur { q, t }
+    }
+}
+
+/// Francis double-shift QR on an upper Hessenberg matrix, returning the
+/// real and imaginary parts of its eigenvalues.
+///
+/// Without `z_acc` only the active window is updated, which suffices for
+/// the eigenvalues. With it the transforms reach the whole matrix, so
+/// `hess` ends in real Schur form, and are accumulated into its columns.
+/// Panics once `max_iters` sweeps are spent on one eigenvalue.
+fn francis_qr(
+    hess: &mut RugMat,
+    mut z_acc: Option<&mut RugMat>,
+    max_iters: usize,
+    tol: f64,
+) -> (Vec<Float>, Vec<Float>) {
+    let n = hess.nrows();
+    let precision = hess.as_slice()[0].prec();
+    let full = z_acc.is_some();
+    let h = hess.as_mut_slice();
+    macro_rules! a {
+        ($i:expr, $j:expr) => {
+            h[($j) as usize * n + ($i) as usize]
+        };
+    }
+    let abs = |x: &Float| Float::with_val(precision, x.abs_ref());
+    let zero = || Float::with_val(precision, 0);
+    let tol = Float::with_val(precision, tol);
+
+    let mut anorm = zero();
+    for j in 0..n {
+        for i in 0..n.min(j + 2) {
+            anorm += abs(&h[j * n + i]);
+        }
+    }
+    let mut wr = vec![zero(); n];
+    let mut wi = vec![zero(); n];
+    // Accumulated exceptional shifts
+    let mut t = zero();
+    let mut nn = n as isize - 1;
+    while nn >= 0 {
+        let mut its = 0;
+        loop {
+            let mut l = nn;
+            while l >= 1 {
+                let mut s = abs(&a!(l - 1, l - 1)) + abs(&a!(l, l));
+                if s.is_zero() {
+                    s = anorm.clone();
+                }
+                if abs(&a!(l, l - 1)) <= (&tol * &s).complete(precision) {
+                    a!(l, l - 1) = zero();
+                    break;
+                }
+                l -= 1;
+            }
+
+            let mut x = a!(nn, nn).clone();
+            if l == nn {
+                wr[nn as usize] = x + &t;
+                if full {
+                    a!(nn, nn) = wr[nn as usize].clone();
+                }
+                nn -= 1;
+                break;
+            }
+            let mut y = a!(nn - 1, nn - 1).clone();
+            let mut w = (&a!(nn, nn - 1) * &a!(nn - 1, nn)).complete(precision);
+            if l == nn - 1 {
+                // Trailing 2×2 block: a real pair or a complex pair
+                let p: Float = (&y - &x).complete(precision) / 2u32;
+                let q = p.clone().square() + &w;
+                let mut z = abs(&q).sqrt();
+                x += &t;
+                if full {
+                    a!(nn, nn) = x.clone();
+                    a!(nn - 1, nn - 1) = y + &t;
+                }
+                let (hi, lo) = (nn as usize, nn as usize - 1);
+                if q >= 0 {
+                    z = if p.is_sign_negative() { p - z } else { p + z };
+                    wr[lo] = (&x + &z).complete(precision);
+                    wr[hi] = if z.is_zero() {
+                        wr[lo].clone()
+                    } else {
+                        x - w / &z
+                    };
+                    if full {
+                        // Rotate the real pair into two 1×1 blocks
+                        let sub = a!(nn, nn - 1).clone();
+                        let s = abs(&sub) + abs(&z);
+                        let (mut p, mut q) = (sub / &s, z / &s);
+                        let r = (p.clone().square() + q.clone().square()).sqrt();
+                        p /= &r;
+                        q /= &r;
+                        let rotate = |u: &mut Float, v: &mut Float| {
+                            let new_u =
+                                (&q * &*u).complete(precision) + (&p * &*v).complete(precision);
+                            let new_v =
+                                (&q * &*v).complete(precision) - (&p * &*u).complete(precision);
+                            *u = new_u;
+                            *v = new_v;
+                        };
+                        for j in (nn - 1) as usize..n {
+                            let (top, bottom) = h.split_at_mut(j * n + nn as usize);
+                            rotate(&mut top[j * n + nn as usize - 1], &mut bottom[0]);
+                        }
+                        let (left, right) = h.split_at_mut(nn as usize * n);
+                        let left = &mut left[(nn as usize - 1) * n..];
+                        for (u, v) in left.iter_mut().zip(right.iter_mut()).take(nn as usize + 1) {
+                            rotate(u, v);
+                        }
+                        if let Some(zm) = z_acc.as_deref_mut() {
+                            let (left, right) = zm.as_mut_slice().split_at_mut(nn as usize * n);
+                            let left = &mut left[(nn as usize - 1) * n..];
+                            for (u, v) in left.iter_mut().zip(right.iter_mut()) {
+                                rotate(u, v);
+                            }
+                        }
+                        a!(nn, nn - 1) = zero();
+                    }
+                } else {
+                    wr[lo] = (&x + &p).complete(precision);
+                    wr[hi] = x + p;
+                    wi[lo] = -z.clone();
+                    wi[hi] = z;
+                }
+                nn -= 2;
+                break;
+            }
+
+            assert!(
+                its < max_iters,
+                "Hessenberg QR did not converge within {} sweeps",
+                max_iters
+            );
+            if its > 0 && its % 10 == 0 {
+                t += &x;
+                for i in 0..=nn {
+                    a!(i, i) -= &x;
+                }
+                let s = abs(&a!(nn, nn - 1)) + abs(&a!(nn - 1, nn - 2));
+                x = Float::with_val(precision, 0.75) * &s;
+                y = x.clone();
+                w = Float::with_val(precision, -0.4375) * s.square();
+            }
+            its += 1;
+
+            // Look for two consecutive small subdiagonal entries
+            let mut m = nn - 2;
+            let (mut p, mut q, mut r);
+            loop {
+                let z = a!(m, m).clone();
+                let rr = (&x - &z).complete(precision);
+                let ss = (&y - &z).complete(precision);
+                p = ((&rr * &ss).complete(precision) - &w) / &a!(m + 1, m) + &a!(m, m + 1);
+                q = (&a!(m + 1, m + 1) - &z).complete(precision) - &rr - &ss;
+                r = a!(m + 2, m + 1).clone();
+                let s = abs(&p) + abs(&q) + abs(&r);
+                p /= &s;
+                q /= &s;
+                r /= &s;
+                if m == l {
+                    break;
+                }
+                let u = abs(&a!(m, m - 1)) * (abs(&q) + abs(&r));
+                let v = abs(&p) * (abs(&a!(m - 1, m - 1)) + abs(&z) + abs(&a!(m + 1, m + 1)));
+                if u <= tol.clone() * v {
+                    break;
+                }
+                m -= 1;
+            }
+            for i in m + 2..=nn {
+                a!(i, i - 2) = zero();
+                if i != m + 2 {
+                    a!(i, i - 3) = zero();
+                }
+            }
+
+            // Double-shift QR step on rows and columns l..=nn
+            for k in m..nn {
+                if k != m {
+                    p = a!(k, k - 1).clone();
+                    q = a!(k + 1, k - 1).clone();
+                    r = if k != nn - 1 {
+                        a!(k + 2, k - 1).clone()
+                    } else {
+                        zero()
+                    };
+                    x = abs(&p) + abs(&q) + abs(&r);
+                    if !x.is_zero() {
+                        p /= &x;
+                        q /= &x;
+                        r /= &x;
+                    }
+                }
+                let mut s = (p.clone().square() + q.clone().square() + r.clone().square()).sqrt();
+                if p.is_sign_negative() {
+                    s = -s;
+                }
+                if s.is_zero() {
+                    continue;
+                }
+                if k == m {
+                    if l != m {
+                        a!(k, k - 1) = -a!(k, k - 1).clone();
+                    }
+                } else {
+                    a!(k, k - 1) = -(&s * &x).complete(precision);
+                }
+                p += &s;
+                x = (&p / &s).complete(precision);
+                y = (&q / &s).complete(precision);
+                let z = (&r / &s).complete(precision);
+                q /= &p;
+                r /= &p;
+                let j_end = if full { n as isize - 1 } else { nn };
+                for j in k..=j_end {
+                    let mut pp = a!(k, j).clone() + (&q * &a!(k + 1, j)).complete(precision);
+                    if k != nn - 1 {
+                        pp += &r * &a!(k + 2, j);
+                        a!(k + 2, j) -= (&pp * &z).complete(precision);
+                    }
+                    a!(k + 1, j) -= (&pp * &y).complete(precision);
+                    a!(k, j) -= (&pp * &x).complete(precision);
+                }
+                let i_start = if full { 0 } else { l };
+                for i in i_start..=nn.min(k + 3) {
+                    let mut pp = (&x * &a!(i, k)).complete(precision)
+                        + (&y * &a!(i, k + 1)).complete(precision);
+                    if k != nn - 1 {
+                        pp += &z * &a!(i, k + 2);
+                        a!(i, k + 2) -= (&pp * &r).complete(precision);
+                    }
+                    a!(i, k + 1) -= (&pp * &q).complete(precision);
+                    a!(i, k) -= pp;
+                }
+                if let Some(zm) = z_acc.as_deref_mut() {
+                    let k = k as usize;
+                    for i in 0..n {
+                        let mut pp = (&x * &zm[(i, k)]).complete(precision)
+                            + (&y * &zm[(i, k + 1)]).complete(precision);
+                        if k + 1 != nn as usize {
+                            pp += &z * &zm[(i, k + 2)];
+                            zm[(i, k + 2)] -= (&pp * &r).complete(precision);
+                        }
+                        zm[(i, k + 1)] -= (&pp * &q).complete(precision);
+                        zm[(i, k)] -= pp;
+                    }
+                }
+            }
+        }
+    }
+
+    (wr, wi)
+}
+
+impl RugMat {
+    /// Generalized symmetric-definite eigenproblem A x = λ B x for
+    /// symmetric A and symmetric positive definite B.
+    ///
+    /// With B = L Lᵗ it solves the standard problem C = L⁻¹ A L⁻ᵗ by
+    /// [`RugMat::eigh`] (max_iters and tol are passed on) and maps its
+    /// eigenvectors back by X = L⁻ᵗ Z. Returns the eigenvalues ascending and
+    /// X with XᵗBX = I and XᵗAX = Λ, or the Cholesky failure of B.
+    pub fn eigh_generalized(
+        &self,
+        b: &RugMat,
+        max_iters: usize,
+        tol: f64,
+    ) -> Result<(Vec<Float>, RugMat), NotPositiveDefinite> {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        assert_eq!(
+            (b.nrows(), b.ncols()),
+            (self.nrows(), self.ncols()),
+            "Matrices must have the same shape"
+        );
+        let n = self.nrows();
+        let chol = b.cholesky()?;
+
+        // W = L⁻¹A, then C = L⁻¹Wᵗ = L⁻¹AL⁻ᵗ by symmetry of A
+        let mut w = self.clone();
+        w.as_mut_slice()
+            .par_chunks_mut(n)
+            .for_each(|col| col.clone_from_slice(&chol.solve_lower(col)));
+        let mut c = w.transpose();
+        c.as_mut_slice()
+            .par_chunks_mut(n)
+            .for_each(|col| col.clone_from_slice(&chol.solve_lower(col)));
+        c.symmetrize();
+
+        let (values, mut x) = c.eigh(max_iters, tol);
+        x.as_mut_slice()
+            .par_chunks_mut(n)
+            .for_each(|col| col.clone_from_slice(&chol.solve_upper(col)));
+        Ok((values, x))
+    }
+}
+
+/// Approximate eigenpairs from a projection method
+#[derive(Debug, Clone)]
+pub struct RitzPairs {
+    /// Ritz values, largest magnitude first
+    pub values: Vec<Float>,
+    /// Orthonormal Ritz vectors as columns, matching `values`
+    pub vectors: RugMat,
+    pub iterations: usize,
+    /// Whether every pair met the residual tolerance
+    pub converged: bool,
+}
+
+impl RugMat {
+    /// Dominant k-dimensional invariant subspace of a symmetric matrix by
+    /// simultaneous (block power) iteration with Rayleigh-Ritz extraction.
+    ///
+    /// Each step multiplies the current Ritz basis by A, orthonormalizes it
+    /// with QR and diagonalizes the projected k×k matrix. Stops once every
+    /// pair has ‖A x - θ x‖ <= tol·|θ|, or after `iters` steps. Convergence
+    /// depends only on |λₖ₊₁/λₖ|, so clusters inside the block cost nothing.
+    pub fn subspace_iteration(&self, k: usize, iters: usize, tol: f64) -> RitzPairs {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        assert!(k >= 1 && k <= n, "Block size must be in 1..=n");
+        let precision = self.as_slice()[0].prec();
+        let tol = Float::with_val(precision, tol);
+
+        // Hilbert-like start block: full rank with no structural zeros
+        let mut z = RugMat::new(n, k, precision);
+        for j in 0..k {
+            for i in 0..n {
+                z[(i, j)] = Float::with_val(precision, i + j + 1).recip();
+            }
+        }
+        let mut pairs = RitzPairs {
+            values: Vec::new(),
+            vectors: z.clone(),
+            iterations: 0,
+            converged: false,
+        };
+
+        for it in 1..=iters {
+            let q = z.qr().q_thin();
+            let aq = self.matmul(&q);
+            let mut t = q.transpose().matmul(&aq);
+            t.symmetrize();
+            let (mut theta, mut s) = t.eigh(30 * k, working_tol(precision));
+            let mut order: Vec<usize> = (0..k).collect();
+            order.sort_by(|&a, &b| {
+                let (a, b) = (theta[a].clone().abs(), theta[b].clone().abs());
+                b.partial_cmp(&a).unwrap()
+            });
+            let mut sorted = RugMat::new(k, k, precision);
+            for (c, &j) in order.iter().enumerate() {
+                sorted.as_mut_slice()[c * k..(c + 1) * k]
+                    .clone_from_slice(&s.as_slice()[j * k..(j + 1) * k]);
+            }
+            s = sorted;
+            theta = order.iter().map(|&j| theta[j].clone()).collect();
+
+            let x = q.matmul(&s);
+            z = aq.matmul(&s);
+            let converged = (0..k).all(|j| {
+                let r: Vec<Float> = (0..n)
+                    .map(|i| z[(i, j)].clone() - (&theta[j] * &x[(i, j)]).complete(precision))
+                    .collect();
+                RugMat::norm2_vec(&r) <= tol.clone() * theta[j].clone().abs()
+            });
+            pairs = RitzPairs {
+                values: theta,
+                vectors: x,
+                iterations: it,
+                converged,
+            };
+            if converged {
+                break;
+            }
+        }
+        pairs
+    }
+}
+
+/// Extreme eigenvalues of a symmetric matrix from [`RugMat::lanczos`]
+#[derive(Debug, Clone)]
+pub struct LanczosEigen {
+    /// Largest Ritz values, descending
+    pub largest: Vec<Float>,
+    /// Smallest Ritz values, ascending
+    pub smallest: Vec<Float>,
+    /// Lanczos steps taken (fewer than requested on breakdown)
+    pub steps: usize,
+}
+
+impl RugMat {
+    /// The k largest and k smallest eigenvalues of a symmetric matrix from
+    /// `max_iters` Lanczos steps with full reorthogonalization.
+    ///
+    /// Only matrix-vector products are needed. The extremes converge first,
+    /// also inside clusters where power iteration stalls; at high precision
+    /// the O(n·m) storage and work of reorthogonalization is cheap compared
+    /// to the products themselves. Panics if the eigenvalues of the m×m
+    /// Lanczos matrix do not converge within 30·m QR sweeps.
+    pub fn lanczos(&self, k: usize, max_iters: usize) -> LanczosEigen {
+        self.lanczos_with_reorthogonalization(k, max_iters, true)
+    }
+
+    /// [`RugMat::lanczos`] with reorthogonalization optional. Without it
+    /// memory stays at a few vectors, but converged values can reappear
+    /// as spurious copies in the lists.
+    pub fn lanczos_with_reorthogonalization(
+        &self,
+        k: usize,
+        max_iters: usize,
+        full: bool,
+    ) -> LanczosEigen {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let precision = self.as_slice()[0].prec();
+        let v0: Vec<Float> = (1..=self.nrows())
+            .map(|i| Float::with_val(precision, i))
+            .collect();
+        let steps = max_iters.min(self.nrows());
+        let (alpha, beta) = lanczos_tridiagonal(self, &v0, steps, full);
+        let m = alpha.len();
+        let (values, _) =
+            symmetric_tridiagonal_eigen(&alpha, &beta, 30 * m, working_tol(precision));
+        let k = k.min(m);
+        LanczosEigen {
+            largest: values.iter().rev().take(k).cloned().collect(),
+            smallest: values[..k].to_vec(),
+            steps: m,
+        }
+    }
+}
+
+/// A single eigenpair refined by a shifted iteration
+#[derive(Debug, Clone)]
+pub struct EigenPair {
+    pub value: Float,
+    /// Unit-norm eigenvector
+    pub vector: Vec<Float>,
+    pub iterations: usize,
+    /// ‖A x - λ x‖ at return
+    pub residual: Float,
+    /// Whether the residual reached n·ε·‖A‖_F at the working precision
+    pub converged: bool,
+}
+
+impl RugMat {
+    /// LU of A - σI; a shift that is exactly an eigenvalue (zero pivot) is
+    /// nudged by a relative ε so the solve stays defined
+    fn shifted_lu(&self, shift: &Float) -> LU {
+        let precision = self.as_slice()[0].prec();
+        let mut sigma = Float::with_val(precision, shift);
+        loop {
+            let mut b = self.clone();
+            for i in 0..self.nrows() {
+                b[(i, i)] -= &sigma;
+            }
+            let lu = b.lu_decompose_pivot();
+            if (0..self.nrows()).all(|i| !lu.lu[(i, i)].is_zero()) {
+                return lu;
+            }
+            let nudge = (sigma.clone().abs() + 1u32) * working_tol(precision);
+            sigma += nudge;
+        }
+    }
+
+    /// Unit x, its Rayleigh quotient xᵗAx and the residual norm
+    fn rayleigh(&self, x: &[Float]) -> (Float, Float) {
+        let ax = self.matmul_vec(x);
+        let rho = dot(x, &ax);
+        let r: Vec<Float> = ax
+            .iter()
+            .zip(x)
+            .map(|(a, xi)| a.clone() - (&rho * xi).complete(a.prec()))
+            .collect();
+        (rho, RugMat::norm2_vec(&r))
+    }
+
+    fn normalized(mut x: Vec<Float>) -> Vec<Float> {
+        let norm = RugMat::norm2_vec(&x);
+        for xi in &mut x {
+            *xi /= &norm;
+        }
+        x
+    }
+
+    /// Real eigenvalue of A closest to `shift`, with its eigenvector, by
+    /// inverse iteration with A - σI factored once.
+    ///
+    /// Converges linearly with ratio |λ - σ| / |λ' - σ| for the next
+    /// closest λ', so a good shift gives full precision in a few solves.
+    /// The eigenvalue is the Rayleigh quotient of the final vector.
+    pub fn eigenvalue_nearest(&self, shift: &Float, max_iters: usize) -> EigenPair {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let threshold = self.frobenius_norm() * working_tol(precision) * n as u32;
+        let lu = self.shifted_lu(shift);
+
+        let mut x = RugMat::normalized((1..=n).map(|i| Float::with_val(precision, i)).collect());
+        let (mut value, mut residual) = self.rayleigh(&x);
+        let mut iterations = 0;
+        while iterations < max_iters && residual > threshold {
+            x = RugMat::normalized(lu.solve(&x));
+            (value, residual) = self.rayleigh(&x);
+            iterations += 1;
+        }
+        EigenPair {
+            converged: residual <= threshold,
+            value,
+            vector: x,
+            iterations,
+            residual,
+        }
+    }
+
+    /// Rayleigh quotient iteration from `x0`: inverse iteration whose shift
+    /// is updated to the current Rayleigh quotient every step.
+    ///
+    /// Refactors A - ρI each step but converges cubically for symmetric A
+    /// (quadratically otherwise) to the eigenpair x0 is closest to in
+    /// angle, so a handful of steps reach any precision.
+    pub fn rayleigh_quotient_iteration(&self, x0: &[Float], max_iters: usize) -> EigenPair {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        assert_eq!(x0.len(), self.nrows(), "Start vector has the wrong length");
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let threshold = self.frobenius_norm() * working_tol(precision) * n as u32;
+
+        let mut x = RugMat::normalized(x0.iter().map(|v| Float::with_val(precision, v)).collect());
+        let (mut value, mut residual) = self.rayleigh(&x);
+        let mut iterations = 0;
+        while iterations < max_iters && residual > threshold {
+            x = RugMat::normalized(self.shifted_lu(&value).solve(&x));
+            (value, residual) = self.rayleigh(&x);
+            iterations += 1;
+        }
+        EigenPair {
+            converged: residual <= threshold,
+            value,
+            vector: x,
+            iterations,
+            residual,
+        }
+    }
+}
+
+#[test]
+fn test_eigh_reconstructs_symmetric() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(
+        [[4, 1, -2, 2], [1, 2, 0, 1], [-2, 0, 3, -2], [2, 1, -2, -1]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let (values, z) = a.eigh(200, 1e-36);
+    assert!(values.windows(2).all(|w| w[0] <= w[1]));
+
+    let mut zl = z.clone();
+    zl.scale_cols(&values);
+    assert!(zl.matmul(&z.transpose()).compare(&a).max_abs < 1e-30);
+    let id = RugMat::identity(4, precision);
+    assert!(z.transpose().matmul(&z).compare(&id).max_abs < 1e-30);
+}
+
+#[test]
+fn test_eigenvalues_of_similar_companion() {
+    let precision = 128;
+    let f = |v: i32| Float::with_val(precision, v);
+    // Companion matrix of (x - 1)(x - 2)(x² + 1) = x⁴ - 3x³ + 3x² - 3x + 2
+    let mut c = RugMat::new(4, 4, precision);
+    for i in 1..4 {
+        c[(i, i - 1)] = f(1);
+    }
+    for (i, v) in [-2, 3, -3, 3].into_iter().enumerate() {
+        c[(i, 3)] = f(v);
+    }
+    // Hide the Hessenberg structure behind a similarity S C S⁻¹
+    let s = RugMat::from_vecvec(
+        [[1, 2, 0, 1], [0, 1, 3, 0], [1, 0, 1, 2], [0, 1, 0, 1]]
+            .iter()
+            .map(|r| r.iter().map(|&v| f(v)).collect())
+            .collect(),
+    );
+    let a = s.matmul(&c).matmul(&s.lu_decompose_pivot().inverse());
+
+    let mut values = a.eigenvalues(100, 1e-36);
+    values.sort_by(|x, y| {
+        x.real()
+            .partial_cmp(y.real())
+            .unwrap()
+            .then(x.imag().partial_cmp(y.imag()).unwrap())
+    });
+    for (lambda, (re, im)) in values.iter().zip([(0i32, -1i32), (0, 1), (1, 0), (2, 0)]) {
+        assert!((lambda.real().clone() - re).abs() < 1e-30);
+        assert!((lambda.imag().clone() - im).abs() < 1e-30);
+    }
+}
+
+#[test]
+fn test_subspace_iteration_clustered_dominant() {
+    let precision = 128;
+    let n = 6;
+    // Q diag(10, 9.99, -9.98, 1, 0.5, 0.25) Qᵗ with Q a dense reflector
+    let lambda = [10.0, 9.99, -9.98, 1.0, 0.5, 0.25];
+    let v: Vec<Float> = (1..=n).map(|i| Float::with_val(precision, i)).collect();
+    let vtv = dot(&v, &v);
+    let mut q = RugMat::identity(n, precision);
+    for j in 0..n {
+        for i in 0..n {
+            q[(i, j)] -= (&v[i] * &v[j]).complete(precision) * 2u32 / &vtv;
+        }
+    }
+    let mut a = q.clone();
+    a.scale_cols(&lambda.map(|l| Float::with_val(precision, l)));
+    let a = a.matmul(&q.transpose());
+
+    let pairs = a.subspace_iteration(3, 200, 1e-30);
+    assert!(pairs.converged);
+    for (theta, expected) in pairs.values.iter().zip([10.0, 9.99, -9.98]) {
+        assert!((theta.clone() - Float::with_val(precision, expected)).abs() < 1e-28);
+    }
+    let id = RugMat::identity(3, precision);
+    let x = &pairs.vectors;
+    assert!(x.transpose().matmul(x).compare(&id).max_abs < 1e-30);
+}
+
+#[test]
+fn test_lanczos_extremes_of_laplacian() {
+    let precision = 128;
+    let n = 20;
+    // 1-D Laplacian tridiag(-1, 2, -1), eigenvalues 2 - 2cos(jπ/(n+1))
+    let mut a = RugMat::new(n, n, precision);
+    for i in 0..n {
+        a[(i, i)] = Float::with_val(precision, 2);
+        if i + 1 < n {
+            a[(i, i + 1)] = Float::with_val(precision, -1);
+            a[(i + 1, i)] = Float::with_val(precision, -1);
+        }
+    }
+    let pi = Float::with_val(precision, rug::float::Constant::Pi);
+    let exact = |j: usize| -> Float {
+        let angle = (&pi * j as u32).complete(precision) / (n as u32 + 1);
+        2 - angle.cos() * 2u32
+    };
+
+    let eig = a.lanczos(2, n);
+    assert_eq!(eig.steps, n);
+    for (j, value) in eig.largest.iter().enumerate() {
+        assert!((value.clone() - exact(n - j)).abs() < 1e-30);
+    }
+    for (j, value) in eig.smallest.iter().enumerate() {
+        assert!((value.clone() - exact(j + 1)).abs() < 1e-30);
+    }
+
+    // Ritz values interlace, so a short run stays inside the spectrum; before
+    // orthogonality is lost the plain recurrence gives the same values
+    let plain = a.lanczos_with_reorthogonalization(1, 8, false);
+    let full = a.lanczos(1, 8);
+    assert!(plain.largest[0] < exact(n) && plain.smallest[0] > exact(1));
+    assert!((plain.largest[0].clone() - &full.largest[0]).abs() < 1e-25);
+}
+
+#[test]
+fn test_eigenvalue_nearest_and_rayleigh_quotient_iteration() {
+    let precision = 256;
+    let a = RugMat::from_vecvec(
+        [[4, 1, -2, 2], [1, 2, 0, 1], [-2, 0, 3, -2], [2, 1, -2, -1]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let (values, _) = a.eigh(200, 1e-70);
+    for lambda in &values {
+        let shift = lambda.clone() + 0.1f64;
+        let pair = a.eigenvalue_nearest(&shift, 200);
+        assert!(pair.converged);
+        assert!((pair.value - lambda).abs() < 1e-70);
+    }
+
+    // An exact eigenvalue as shift must not break the solve
+    let pair = a.eigenvalue_nearest(&values[0], 10);
+    assert!((pair.value - &values[0]).abs() < 1e-70);
+
+    let x0: Vec<Float> = (0..4).map(|i| Float::with_val(precision, i)).collect();
+    let pair = a.rayleigh_quotient_iteration(&x0, 20);
+    assert!(pair.converged && pair.iterations <= 8);
+    assert!(
+        values
+            .iter()
+            .any(|l| (l.clone() - &pair.value).abs() < 1e-70)
+    );
+
+    // Nonsymmetric with real spectrum {2, 3, 5}
+    let t = RugMat::from_vecvec(
+        [[2, 1, 0], [0, 3, 1], [0, 0, 5]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let pair = t.eigenvalue_nearest(&Float::with_val(precision, 2.9), 200);
+    assert!(pair.converged);
+    assert!((pair.value - 3u32).abs() < 1e-70);
+}
+
+#[test]
+fn test_eigh_generalized() {
+    let precision = 128;
+    let mat = |rows: [[i32; 3]; 3]| {
+        RugMat::from_vecvec(
+            rows.iter()
+                .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+                .collect(),
+        )
+    };
+    let a = mat([[2, -1, 0], [-1, 2, -1], [0, -1, 2]]);
+    let b = mat([[4, 1, 0], [1, 3, 1], [0, 1, 2]]);
+    let (values, x) = a.eigh_generalized(&b, 200, 1e-35).unwrap();
+    assert!(values.windows(2).all(|w| w[0] <= w[1]));
+
+    let xt = x.transpose();
+    let gram = xt.matmul(&b).matmul(&x);
+    assert!(gram.compare(&RugMat::identity(3, precision)).max_abs < 1e-30);
+    let mut lambda = RugMat::new(3, 3, precision);
+    for (i, v) in values.iter().enumerate() {
+        lambda[(i, i)] = v.clone();
+    }
+    assert!(xt.matmul(&a).matmul(&x).compare(&lambda).max_abs < 1e-30);
+
+    // det(A - λB) vanishes at every eigenvalue
+    for v in &values {
+        let mut shifted = a.clone();
+        for (s, bij) in shifted.as_mut_slice().iter_mut().zip(b.as_slice()) {
+            *s -= (v * bij).complete(precision);
+        }
+        assert!(shifted.lu_decompose_pivot().det().abs() < 1e-30);
+    }
+
+    let indefinite = mat([[1, 2, 0], [2, 1, 0], [0, 0, 1]]);
+    assert!(a.eigh_generalized(&indefinite, 200, 1e-35).is_err());
+}
+
+#[test]
+fn test_schur_decomposition() {
+    let precision = 128;
+    let f = |v: i32| Float::with_val(precision, v);
+    // (x - 1)(x - 2)(x² + 1) hidden behind a similarity, as above, plus a
+    // dense nonnormal 5×5 with real eigenvalues 1..=5
+    let mut c = RugMat::new(4, 4, precision);
+    for i in 1..4 {
+        c[(i, i - 1)] = f(1);
+    }
+    for (i, v) in [-2, 3, -3, 3].into_iter().enumerate() {
+        c[(i, 3)] = f(v);
+    }
+    let s = RugMat::from_vecvec(
+        [[1, 2, 0, 1], [0, 1, 3, 0], [1, 0, 1, 2], [0, 1, 0, 1]]
+            .iter()
+            .map(|r| r.iter().map(|&v| f(v)).collect())
+            .collect(),
+    );
+    let a = s.matmul(&c).matmul(&s.lu_decompose_pivot().inverse());
+    let mut u = RugMat::new(5, 5, precision);
+    for j in 0..5 {
+        for i in 0..=j {
+            u[(i, j)] = if i == j { f(j as i32 + 1) } else { f(3) };
+        }
+    }
+    let mut g = RugMat::new(5, 5, precision);
+    for j in 0..5 {
+        for i in 0..5 {
+            g[(i, j)] = Float::with_val(precision, 1) / (i + j + 1) as u32 + (i == j) as u32;
+        }
+    }
+    let b = g.matmul(&u).matmul(&g.lu_decompose_pivot().inverse());
+
+    for (m, complex_blocks) in [(&a, 1), (&b, 0)] {
+        let n = m.nrows();
+        let schur = m.schur(200, 1e-36);
+        let (q, t) = (&schur.q, &schur.t);
+        let qtq = q.transpose().matmul(q);
+        assert!(qtq.compare(&RugMat::identity(n, precision)).max_abs < 1e-30);
+        let back = q.matmul(t).matmul(&q.transpose());
+        assert!(back.compare(m).max_abs < 1e-28);
+
+        // Quasi-triangular, 2×2 blocks only for complex pairs and never
+        // adjacent
+        let mut blocks = 0;
+        for j in 0..n {
+            for i in (j + 2)..n {
+                assert!(t[(i, j)].is_zero());
+            }
+            if j + 1 < n && !t[(j + 1, j)].is_zero() {
+                blocks += 1;
+                assert!(j == 0 || t[(j, j - 1)].is_zero());
+            }
+        }
+        assert_eq!(blocks, complex_blocks);
+    }
+
+    let t = b.schur(200, 1e-36).t;
+    let mut diag: Vec<Float> = (0..5).map(|i| t[(i, i)].clone()).collect();
+    diag.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    for (i, d) in diag.into_iter().enumerate() {
+        assert!((d - (i + 1) as u32).abs() < 1e-28);
+    }
+}
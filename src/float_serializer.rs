@@ -1,6 +1,5 @@
 use gmp_mpfr_sys::mpfr;
 use rug::Float;
-use std::io::Cursor;
 use std::io::{Read, Write};
 use std::slice;
 
@@ -8,11 +7,11 @@ use std::slice;
 pub fn write_float<W: Write>(writer: &mut W, f: &Float) -> std::io::Result<()> {
     let raw = unsafe { &*f.as_raw() }; // raw: *const __mpfr_struct
     let prec = raw.prec as u32;
-    let exp = raw.exp as i64;
+    let exp = raw.exp;
     let sign = if f.is_sign_positive() { 1i8 } else { -1i8 };
 
     // Each GMP limb is u64 (assumed here)
-    let n_limbs = ((prec + 63) / 64) as usize;
+    let n_limbs = prec.div_ceil(64) as usize;
     let limbs = unsafe { slice::from_raw_parts(raw.d.as_ptr(), n_limbs) };
 
     writer.write_all(&prec.to_le_bytes())?;
@@ -27,50 +26,188 @@ pub fn write_float<W: Write>(writer: &mut W, f: &Float) -> std::io::Result<()> {
     Ok(())
 }
 
-/// Deserialize a Float by restoring precision, sign, exponent, and limb data.
-pub fn read_float<R: Read>(reader: &mut R) -> std::io::Result<Float> {
-    let mut buf4 = [0u8; 4];
-    let mut buf1 = [0u8; 1];
-    let mut buf8 = [0u8; 8];
+/// Bytes before the limbs: precision, sign, exponent and limb count
+const HEADER_BYTES: usize = 4 + 1 + 8 + 8;
 
-    reader.read_exact(&mut buf4)?;
-    let prec = u32::from_le_bytes(buf4);
+/// MPFR's encodings of zero, NaN and infinity in the exponent field
+const EXP_ZERO: mpfr::exp_t = -mpfr::exp_t::MAX;
+const EXP_NAN: mpfr::exp_t = 1 - mpfr::exp_t::MAX;
+const EXP_INF: mpfr::exp_t = 2 - mpfr::exp_t::MAX;
 
-    reader.read_exact(&mut buf1)?;
-    let sign = i8::from_le_bytes(buf1);
+/// Validate a serialized header and allocate a Float whose limb buffer will
+/// receive the limbs; the limb count must match the precision exactly so the
+/// copy cannot run past the MPFR allocation.
+fn parse_header(header: &[u8; HEADER_BYTES]) -> std::io::Result<(Float, i8, usize)> {
+    let invalid = |msg| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    let prec = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    let sign = header[4] as i8;
+    let exp = i64::from_le_bytes(header[5..13].try_into().unwrap());
+    let n_limbs = u64::from_le_bytes(header[13..21].try_into().unwrap());
 
-    reader.read_exact(&mut buf8)?;
-    let exp = i64::from_le_bytes(buf8);
+    check_precision(prec)?;
+    if n_limbs != u64::from(prec.div_ceil(64)) {
+        return Err(invalid("Limb count does not match precision"));
+    }
+    Ok((allocate(prec, exp)?, sign, n_limbs as usize))
+}
 
-    reader.read_exact(&mut buf8)?;
-    let n_limbs = u64::from_le_bytes(buf8) as usize;
+/// Reject precisions MPFR cannot allocate
+pub fn check_precision(prec: u32) -> std::io::Result<()> {
+    if prec < rug::float::prec_min() || prec > rug::float::prec_max() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Float precision out of range",
+        ));
+    }
+    Ok(())
+}
 
-    let mut limbs = vec![0u64; n_limbs];
-    for i in 0..n_limbs {
-        reader.read_exact(&mut buf8)?;
-        limbs[i] = u64::from_le_bytes(buf8);
+/// Accept only the special-value codes or an exponent inside the current
+/// emin..=emax range
+fn check_exponent(exp: i64) -> std::io::Result<mpfr::exp_t> {
+    let (emin, emax) = unsafe { (mpfr::get_emin(), mpfr::get_emax()) };
+    match mpfr::exp_t::try_from(exp) {
+        Ok(e @ (EXP_ZERO | EXP_NAN | EXP_INF)) => Ok(e),
+        Ok(e) if (emin..=emax).contains(&e) => Ok(e),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Float exponent out of range",
+        )),
     }
+}
 
-    // Allocate a new Float and replace its internal data
+fn allocate(prec: u32, exp: i64) -> std::io::Result<Float> {
+    let exp = check_exponent(exp)?;
     let mut f = Float::new(prec);
     let raw = unsafe { &mut *f.as_raw_mut() };
-
-    raw.prec = prec as mpfr::prec_t;
     raw.exp = exp;
+    Ok(f)
+}
 
-    unsafe {
-        std::ptr::copy_nonoverlapping(limbs.as_ptr(), raw.d.as_ptr(), n_limbs);
+/// A regular number's mantissa must be normalized: the top bit of the most
+/// significant limb set and every bit below the precision clear
+fn check_mantissa(f: &Float, n_limbs: usize) -> std::io::Result<()> {
+    let raw = unsafe { &*f.as_raw() };
+    if raw.exp <= EXP_INF {
+        return Ok(());
     }
+    let limbs = unsafe { slice::from_raw_parts(raw.d.as_ptr(), n_limbs) };
+    let unused = n_limbs as u32 * 64 - f.prec();
+    let low_mask = (1u64 << unused) - 1;
+    if limbs[n_limbs - 1] >> 63 == 0 || limbs[0] & low_mask != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Float mantissa is not normalized",
+        ));
+    }
+    Ok(())
+}
+
+/// The limb buffer of a Float allocated with exactly `n_limbs` limbs
+fn limbs_mut(f: &mut Float, n_limbs: usize) -> &mut [u64] {
+    let raw = unsafe { &mut *f.as_raw_mut() };
+    unsafe { slice::from_raw_parts_mut(raw.d.as_ptr(), n_limbs) }
+}
 
+fn finish(mut f: Float, sign: i8) -> Float {
     if sign < 0 {
         f = -f;
     }
+    f
+}
 
-    Ok(f)
+/// Deserialize a Float by restoring precision, sign, exponent, and limb data.
+///
+/// The limbs are read straight into the new Float's MPFR allocation.
+pub fn read_float<R: Read>(reader: &mut R) -> std::io::Result<Float> {
+    let mut header = [0u8; HEADER_BYTES];
+    reader.read_exact(&mut header)?;
+    let (mut f, sign, n_limbs) = parse_header(&header)?;
+
+    let limbs = limbs_mut(&mut f, n_limbs);
+    let bytes = unsafe { slice::from_raw_parts_mut(limbs.as_mut_ptr() as *mut u8, n_limbs * 8) };
+    reader.read_exact(bytes)?;
+    for limb in limbs.iter_mut() {
+        *limb = u64::from_le(*limb);
+    }
+    check_mantissa(&f, n_limbs)?;
+
+    Ok(finish(f, sign))
+}
+
+/// Deserialize a Float from the front of `bytes`, copying the limbs once
+/// from the slice into the MPFR allocation. Returns the Float and the
+/// number of bytes consumed.
+pub fn read_float_from_slice(bytes: &[u8]) -> std::io::Result<(Float, usize)> {
+    let truncated = || std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Truncated Float");
+    let header: &[u8; HEADER_BYTES] = bytes
+        .get(..HEADER_BYTES)
+        .ok_or_else(truncated)?
+        .try_into()
+        .unwrap();
+    let (mut f, sign, n_limbs) = parse_header(header)?;
+
+    let used = HEADER_BYTES + n_limbs * 8;
+    let src = bytes.get(HEADER_BYTES..used).ok_or_else(truncated)?;
+    for (limb, chunk) in limbs_mut(&mut f, n_limbs)
+        .iter_mut()
+        .zip(src.chunks_exact(8))
+    {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    check_mantissa(&f, n_limbs)?;
+
+    Ok((finish(f, sign), used))
+}
+
+/// Size of one compact entry at precision `prec`: sign, exponent and limbs
+pub fn compact_float_len(prec: u32) -> usize {
+    1 + 8 + 8 * prec.div_ceil(64) as usize
+}
+
+/// Serialize a Float without its precision and limb count, for containers
+/// that store one shared precision; `f` must have that precision.
+pub fn write_float_compact<W: Write>(writer: &mut W, f: &Float) -> std::io::Result<()> {
+    let raw = unsafe { &*f.as_raw() };
+    let n_limbs = f.prec().div_ceil(64) as usize;
+    let limbs = unsafe { slice::from_raw_parts(raw.d.as_ptr(), n_limbs) };
+    let sign = if f.is_sign_positive() { 1i8 } else { -1i8 };
+
+    writer.write_all(&sign.to_le_bytes())?;
+    writer.write_all(&raw.exp.to_le_bytes())?;
+    for &limb in limbs {
+        writer.write_all(&limb.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Deserialize one compact entry at precision `prec` from exactly
+/// [`compact_float_len`]`(prec)` bytes; `prec` must pass [`check_precision`].
+pub fn read_float_compact_from_slice(bytes: &[u8], prec: u32) -> std::io::Result<Float> {
+    if bytes.len() != compact_float_len(prec) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Compact Float has the wrong length",
+        ));
+    }
+    let sign = bytes[0] as i8;
+    let exp = i64::from_le_bytes(bytes[1..9].try_into().unwrap());
+    let n_limbs = prec.div_ceil(64) as usize;
+    let mut f = allocate(prec, exp)?;
+    for (limb, chunk) in limbs_mut(&mut f, n_limbs)
+        .iter_mut()
+        .zip(bytes[9..].chunks_exact(8))
+    {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    check_mantissa(&f, n_limbs)?;
+    Ok(finish(f, sign))
 }
 
 #[test]
 fn round_trip_float_precision() {
+    use std::io::Cursor;
+
     let original = Float::with_val(
         256,
         Float::parse("3.14159265358979323846264338327950288419716939937510").unwrap(),
@@ -90,3 +227,89 @@ fn round_trip_float_precision() {
         restored
     );
 }
+
+#[test]
+fn slice_reader_matches_and_rejects_bad_limb_count() {
+    let values = [
+        Float::with_val(200, -1) / 3u32,
+        Float::with_val(64, 0),
+        Float::with_val(53, f64::INFINITY),
+    ];
+    let mut buf = Vec::new();
+    for v in &values {
+        write_float(&mut buf, v).unwrap();
+    }
+
+    let mut rest = &buf[..];
+    for v in &values {
+        let (f, used) = read_float_from_slice(rest).unwrap();
+        assert_eq!(f.prec(), v.prec());
+        assert_eq!(&f, v);
+        assert_eq!(f.is_sign_negative(), v.is_sign_negative());
+        rest = &rest[used..];
+    }
+    assert!(rest.is_empty());
+
+    // A limb count larger than the precision allows must not be copied
+    let mut bad = buf.clone();
+    bad[13..21].copy_from_slice(&100u64.to_le_bytes());
+    assert!(read_float_from_slice(&bad).is_err());
+    assert!(read_float(&mut &bad[..]).is_err());
+    assert!(read_float_from_slice(&buf[..30]).is_err());
+}
+
+#[test]
+fn readers_reject_invalid_exponent_and_mantissa() {
+    // 200 bits in four limbs: 56 unused bits at the bottom of the first limb
+    let mut buf = Vec::new();
+    write_float(&mut buf, &(Float::with_val(200, -1) / 3u32)).unwrap();
+    let rejects = |bytes: &[u8]| {
+        read_float_from_slice(bytes).is_err() && read_float(&mut &bytes[..]).is_err()
+    };
+    assert!(!rejects(&buf));
+
+    // Past emax, and between the special-value codes and emin
+    for exp in [i64::MAX, EXP_INF + 1] {
+        let mut bad = buf.clone();
+        bad[5..13].copy_from_slice(&exp.to_le_bytes());
+        assert!(rejects(&bad));
+    }
+
+    // Top bit of the most significant limb cleared
+    let mut bad = buf.clone();
+    bad[HEADER_BYTES + 31] &= 0x7f;
+    assert!(rejects(&bad));
+
+    // A bit set below the precision
+    let mut bad = buf.clone();
+    bad[HEADER_BYTES] |= 1;
+    assert!(rejects(&bad));
+
+    // The special values carry no mantissa to check
+    let mut nan = Vec::new();
+    write_float(&mut nan, &Float::with_val(200, rug::float::Special::Nan)).unwrap();
+    assert!(read_float_from_slice(&nan).unwrap().0.is_nan());
+}
+
+#[test]
+fn compact_reader_rejects_invalid_exponent_and_mantissa() {
+    let prec = 200;
+    let mut buf = Vec::new();
+    write_float_compact(&mut buf, &(Float::with_val(prec, -1) / 3u32)).unwrap();
+    assert_eq!(
+        read_float_compact_from_slice(&buf, prec).unwrap(),
+        Float::with_val(prec, -1) / 3u32
+    );
+
+    for exp in [i64::MAX, EXP_INF + 1] {
+        let mut bad = buf.clone();
+        bad[1..9].copy_from_slice(&exp.to_le_bytes());
+        assert!(read_float_compact_from_slice(&bad, prec).is_err());
+    }
+    let mut bad = buf.clone();
+    bad[9 + 31] &= 0x7f;
+    assert!(read_float_compact_from_slice(&bad, prec).is_err());
+    let mut bad = buf.clone();
+    bad[9] |= 1;
+    assert!(read_float_compact_from_slice(&bad, prec).is_err());
+}
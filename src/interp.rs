@@ -0,0 +1,133 @@
+// interp.rs: barycentric polynomial and rational interpolation
+use rayon::prelude::*;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Interpolant in the second (true) barycentric form
+/// r(x) = Σ wⱼfⱼ/(x - xⱼ) / Σ wⱼ/(x - xⱼ).
+///
+/// Evaluation is O(n) and stable for any weights below, without ever
+/// setting up the ill-conditioned Vandermonde system.
+#[derive(Debug, Clone)]
+pub struct BarycentricInterpolant {
+    pub nodes: Vec<Float>,
+    pub values: Vec<Float>,
+    pub weights: Vec<Float>,
+}
+
+impl BarycentricInterpolant {
+    /// The polynomial interpolant of degree n-1 through (nodes, values)
+    pub fn polynomial(nodes: &[Float], values: &[Float]) -> Self {
+        assert_eq!(nodes.len(), values.len());
+        BarycentricInterpolant {
+            nodes: nodes.to_vec(),
+            values: values.to_vec(),
+            weights: barycentric_weights(nodes),
+        }
+    }
+
+    /// The Floater-Hormann rational interpolant with blending degree d
+    pub fn floater_hormann(nodes: &[Float], values: &[Float], d: usize) -> Self {
+        assert_eq!(nodes.len(), values.len());
+        BarycentricInterpolant {
+            nodes: nodes.to_vec(),
+            values: values.to_vec(),
+            weights: floater_hormann_weights(nodes, d),
+        }
+    }
+
+    /// r(x), returning the data value exactly when x is a node
+    pub fn eval(&self, x: &Float) -> Float {
+        let precision = x.prec();
+        let mut num = Float::with_val(precision, 0);
+        let mut den = Float::with_val(precision, 0);
+        for ((xj, fj), wj) in self.nodes.iter().zip(&self.values).zip(&self.weights) {
+            let diff = (x - xj).complete(precision);
+            if diff.is_zero() {
+                return fj.clone();
+            }
+            let t = (wj / &diff).complete(precision);
+            num += (&t * fj).complete(precision);
+            den += t;
+        }
+        num / den
+    }
+}
+
+/// Classical barycentric weights wⱼ = 1 / Πₖ≠ⱼ (xⱼ - xₖ) for distinct
+/// nodes, computed in parallel over j
+pub fn barycentric_weights(nodes: &[Float]) -> Vec<Float> {
+    nodes
+        .par_iter()
+        .enumerate()
+        .map(|(j, xj)| {
+            let precision = xj.prec();
+            let mut prod = Float::with_val(precision, 1);
+            for (k, xk) in nodes.iter().enumerate() {
+                if k != j {
+                    prod *= (xj - xk).complete(precision);
+                }
+            }
+            prod.recip()
+        })
+        .collect()
+}
+
+/// Floater-Hormann weights for increasing nodes x₀ < … < xₙ and blending
+/// degree 0 <= d <= n:
+/// wₖ = (-1)^(k-d) Σ_{i ∈ Jₖ} Π_{j=i..i+d, j≠k} 1/|xₖ - xⱼ|
+/// with Jₖ = {i : 0 <= i <= n-d, k-d <= i <= k}.
+///
+/// The interpolant has no real poles and reproduces polynomials of degree
+/// up to d; d = n gives back the polynomial interpolant.
+pub fn floater_hormann_weights(nodes: &[Float], d: usize) -> Vec<Float> {
+    let n = nodes.len() - 1;
+    assert!(d <= n, "Blending degree exceeds the number of intervals");
+    nodes
+        .par_iter()
+        .enumerate()
+        .map(|(k, xk)| {
+            let precision = xk.prec();
+            let mut sum = Float::with_val(precision, 0);
+            for i in k.saturating_sub(d)..=k.min(n - d) {
+                let mut term = Float::with_val(precision, 1);
+                for (j, xj) in nodes.iter().enumerate().take(i + d + 1).skip(i) {
+                    if j != k {
+                        term /= (xk - xj).complete(precision).abs();
+                    }
+                }
+                sum += term;
+            }
+            if (k + d) % 2 == 1 { -sum } else { sum }
+        })
+        .collect()
+}
+
+#[test]
+fn test_barycentric_reproduces_polynomials() {
+    let precision = 192;
+    let f = |v: i32| Float::with_val(precision, v);
+    let cubic = |x: &Float| -> Float { x.clone().square() * x - x.clone() * 2u32 + 1u32 };
+    let nodes: Vec<Float> = (0..5).map(|i| f(i) / 4u32).collect();
+    let values: Vec<Float> = nodes.iter().map(cubic).collect();
+
+    let p = BarycentricInterpolant::polynomial(&nodes, &values);
+    let x = Float::with_val(precision, 0.3);
+    assert!((p.eval(&x) - cubic(&x)).abs() < 1e-50);
+    assert_eq!(p.eval(&nodes[2]), values[2]);
+
+    // d = n coincides with the polynomial weights up to a common factor
+    let fh = floater_hormann_weights(&nodes, 4);
+    let ratio = (&fh[0] / &p.weights[0]).complete(precision);
+    for (a, b) in fh.iter().zip(&p.weights) {
+        assert!((a.clone() / b - &ratio).abs() < 1e-50);
+    }
+
+    // Floater-Hormann with d = 2 is exact for quadratics on many nodes
+    let nodes: Vec<Float> = (0..12).map(|i| f(i) / 11u32).collect();
+    let quad = |x: &Float| -> Float { x.clone().square() * 3u32 - x.clone() + 2u32 };
+    let values: Vec<Float> = nodes.iter().map(quad).collect();
+    let r = BarycentricInterpolant::floater_hormann(&nodes, &values, 2);
+    let x = Float::with_val(precision, 0.4321);
+    assert!((r.eval(&x) - quad(&x)).abs() < 1e-50);
+}
@@ -0,0 +1,138 @@
+// kernel.rs: kernel matrices from point sets and standard covariance kernels
+use crate::RugMat;
+use rayon::prelude::*;
+use rug::Assign;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+impl RugMat {
+    /// K with kᵢⱼ = kernel(xᵢ, yⱼ), rounded to `precision`.
+    ///
+    /// Columns are assembled in parallel, each written in place by the
+    /// thread that evaluates it.
+    pub fn kernel_matrix<P, K>(points_x: &[P], points_y: &[P], kernel: K, precision: u32) -> RugMat
+    where
+        P: Sync,
+        K: Fn(&P, &P) -> Float + Sync,
+    {
+        let rows = points_x.len();
+        let mut k = RugMat::new(rows, points_y.len(), precision);
+        if rows == 0 {
+            return k;
+        }
+        k.as_mut_slice()
+            .par_chunks_mut(rows)
+            .zip(points_y.par_iter())
+            .for_each(|(col, y)| {
+                for (kij, x) in col.iter_mut().zip(points_x) {
+                    kij.assign(kernel(x, y));
+                }
+            });
+        k
+    }
+}
+
+/// Squared Euclidean distance ‖x - y‖² at the precision of x
+pub fn squared_distance(x: &[Float], y: &[Float]) -> Float {
+    assert_eq!(x.len(), y.len());
+    let precision = x[0].prec();
+    let mut acc = Float::with_val(precision, 0);
+    for (a, b) in x.iter().zip(y) {
+        acc += (a - b).complete(precision).square();
+    }
+    acc
+}
+
+/// Squared-exponential kernel σ² exp(-‖x - y‖² / (2ℓ²))
+#[derive(Debug, Clone)]
+pub struct Rbf {
+    pub length_scale: Float,
+    pub variance: Float,
+}
+
+impl Rbf {
+    pub fn eval(&self, x: &[Float], y: &[Float]) -> Float {
+        let l2: Float = self.length_scale.clone().square() * 2u32;
+        (-(squared_distance(x, y) / l2)).exp() * &self.variance
+    }
+}
+
+/// Smoothness of a [`Matern`] kernel; the half-integer cases have closed forms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaternNu {
+    Half,
+    ThreeHalves,
+    FiveHalves,
+}
+
+/// Matérn kernel σ² kᵥ(‖x - y‖ / ℓ)
+#[derive(Debug, Clone)]
+pub struct Matern {
+    pub nu: MaternNu,
+    pub length_scale: Float,
+    pub variance: Float,
+}
+
+impl Matern {
+    pub fn eval(&self, x: &[Float], y: &[Float]) -> Float {
+        let precision = self.length_scale.prec();
+        let r = squared_distance(x, y).sqrt() / &self.length_scale;
+        let k = match self.nu {
+            MaternNu::Half => (-r).exp(),
+            MaternNu::ThreeHalves => {
+                let s = r * Float::with_val(precision, 3).sqrt();
+                (-s.clone()).exp() * (s + 1u32)
+            }
+            MaternNu::FiveHalves => {
+                let s = r * Float::with_val(precision, 5).sqrt();
+                let poly: Float = s.clone().square() / 3u32 + &s + 1u32;
+                (-s).exp() * poly
+            }
+        };
+        k * &self.variance
+    }
+}
+
+#[test]
+fn test_kernel_matrix_rbf_and_matern() {
+    let precision = 128;
+    let f = |v: f64| Float::with_val(precision, v);
+    let xs: Vec<Vec<Float>> = [0.0, 0.5, 2.0]
+        .iter()
+        .map(|&v| vec![f(v), f(0.0)])
+        .collect();
+    let ys: Vec<Vec<Float>> = vec![vec![f(0.0), f(1.0)], vec![f(2.0), f(0.0)]];
+
+    let rbf = Rbf {
+        length_scale: f(1.0),
+        variance: f(2.0),
+    };
+    let k = RugMat::kernel_matrix(&xs, &ys, |x, y| rbf.eval(x, y), precision);
+    assert_eq!((k.nrows(), k.ncols()), (3, 2));
+    // ‖(0.5, 0) - (0, 1)‖² = 1.25
+    let expected: Float = f(-0.625).exp() * 2u32;
+    assert!((k[(1, 0)].clone() - expected).abs() < 1e-35);
+    assert_eq!(k[(2, 1)], 2);
+    let empty = RugMat::kernel_matrix(&[], &ys, |x, y| rbf.eval(x, y), precision);
+    assert_eq!((empty.nrows(), empty.ncols()), (0, 2));
+
+    let half = Matern {
+        nu: MaternNu::Half,
+        length_scale: f(0.5),
+        variance: f(1.0),
+    };
+    let d = [f(2.0), f(0.0)];
+    let origin = [f(0.0), f(0.0)];
+    assert!((half.eval(&origin, &d) - f(-4.0).exp()).abs() < 1e-35);
+    for nu in [MaternNu::ThreeHalves, MaternNu::FiveHalves] {
+        let m = Matern {
+            nu,
+            length_scale: f(1.0),
+            variance: f(3.0),
+        };
+        assert_eq!(m.eval(&d, &d), 3);
+        let near = m.eval(&origin, &[f(0.1), f(0.0)]);
+        let far = m.eval(&origin, &d);
+        assert!(near > far && far > 0);
+    }
+}
@@ -0,0 +1,183 @@
+// krylov.rs: Krylov subspace bases and projections
+use crate::RugMat;
+use crate::operator::LinearOperator;
+use crate::rugmat::dot;
+use rug::ops::CompleteRound;
+use rug::{Complex, Float};
+
+/// Result of [`arnoldi`]: A Vₘ = Vₘ₊₁ H̄ₘ with orthonormal columns in V
+#[derive(Debug, Clone)]
+pub struct Arnoldi {
+    /// (m+1)×m upper Hessenberg projection H̄ₘ
+    pub h: RugMat,
+    /// n×(m+1) orthonormal basis Vₘ₊₁ of the Krylov space; on breakdown
+    /// the last column is zero
+    pub basis: RugMat,
+}
+
+impl Arnoldi {
+    /// Number of steps taken, m
+    pub fn steps(&self) -> usize {
+        self.h.ncols()
+    }
+
+    /// The square m×m projection Hₘ = Vₘᵗ A Vₘ
+    pub fn square_h(&self) -> RugMat {
+        let m = self.h.ncols();
+        let mut hm = RugMat::new(m, m, self.h.as_slice()[0].prec());
+        for j in 0..m {
+            hm.as_mut_slice()[j * m..(j + 1) * m]
+                .clone_from_slice(&self.h.as_slice()[j * (m + 1)..j * (m + 1) + m]);
+        }
+        hm
+    }
+
+    /// Ritz values, the eigenvalues of Hₘ, in no particular order. Panics if
+    /// the Hessenberg QR needs more than 30·m sweeps for one of them.
+    pub fn ritz_values(&self, tol: f64) -> Vec<Complex> {
+        self.square_h().eigenvalues(30 * self.steps(), tol)
+    }
+}
+
+/// k steps of Arnoldi on a square operator started at v0, stopping early
+/// when the Krylov space becomes invariant.
+///
+/// Modified Gram-Schmidt is repeated once to keep the basis orthogonal to
+/// working precision.
+pub fn arnoldi<A: LinearOperator + ?Sized>(a: &A, v0: &[Float], k: usize) -> Arnoldi {
+    let n = a.rows();
+    assert_eq!(a.cols(), n, "Arnoldi requires a square operator");
+    assert_eq!(v0.len(), n);
+    let precision = v0[0].prec();
+
+    let beta = RugMat::norm2_vec(v0);
+    let mut basis: Vec<Vec<Float>> =
+        vec![v0.iter().map(|x| (x / &beta).complete(precision)).collect()];
+    let mut h = RugMat::new(k + 1, k, precision);
+
+    for j in 0..k {
+        let mut w = a.apply(&basis[j]);
+        for _ in 0..2 {
+            for (i, vi) in basis.iter().enumerate() {
+                let c = dot(vi, &w);
+                for (wl, vl) in w.iter_mut().zip(vi) {
+                    *wl -= (&c * vl).complete(precision);
+                }
+                h[(i, j)] += c;
+            }
+        }
+        let norm = RugMat::norm2_vec(&w);
+        h[(j + 1, j)] = Float::with_val(precision, &norm);
+        if norm.is_zero() {
+            let m = j + 1;
+            let mut hm = RugMat::new(m + 1, m, precision);
+            for c in 0..m {
+                hm.as_mut_slice()[c * (m + 1)..(c + 1) * (m + 1)]
+                    .clone_from_slice(&h.as_slice()[c * (k + 1)..c * (k + 1) + m + 1]);
+            }
+            basis.push(vec![Float::with_val(precision, 0); n]);
+            return Arnoldi {
+                h: hm,
+                basis: columns_to_mat(basis, n),
+            };
+        }
+        basis.push(w.into_iter().map(|x| x / &norm).collect());
+    }
+    Arnoldi {
+        h,
+        basis: columns_to_mat(basis, n),
+    }
+}
+
+fn columns_to_mat(columns: Vec<Vec<Float>>, rows: usize) -> RugMat {
+    let cols = columns.len();
+    RugMat::from_col_major(rows, cols, columns.into_iter().flatten().collect())
+}
+
+/// Diagonal α and off-diagonal β of the m×m tridiagonal Tₘ = Vₘᵗ A Vₘ from
+/// m <= k steps of symmetric Lanczos started at v0. Stops early when the
+/// Krylov space becomes invariant.
+///
+/// With `reorthogonalize` every new vector is orthogonalized (twice)
+/// against the whole basis, which prevents spurious copies of converged
+/// Ritz values at the cost of storing the basis. Without it only the
+/// three-term recurrence is kept and memory stays at two vectors.
+pub(crate) fn lanczos_tridiagonal<A: LinearOperator + ?Sized>(
+    a: &A,
+    v0: &[Float],
+    k: usize,
+    reorthogonalize: bool,
+) -> (Vec<Float>, Vec<Float>) {
+    let n = a.rows();
+    assert_eq!(a.cols(), n, "Lanczos requires a square operator");
+    assert_eq!(v0.len(), n);
+    let precision = v0[0].prec();
+
+    let norm = RugMat::norm2_vec(v0);
+    let mut q: Vec<Float> = v0.iter().map(|x| (x / &norm).complete(precision)).collect();
+    let mut q_prev = vec![Float::with_val(precision, 0); n];
+    let mut basis = Vec::new();
+    let mut alpha = Vec::with_capacity(k);
+    let mut beta: Vec<Float> = Vec::with_capacity(k);
+
+    for j in 0..k {
+        let mut w = a.apply(&q);
+        let aj = dot(&q, &w);
+        let b_prev = beta.last().cloned();
+        for (i, wi) in w.iter_mut().enumerate() {
+            *wi -= (&aj * &q[i]).complete(precision);
+            if let Some(b) = &b_prev {
+                *wi -= (b * &q_prev[i]).complete(precision);
+            }
+        }
+        alpha.push(aj);
+        if reorthogonalize {
+            basis.push(q.clone());
+            for _ in 0..2 {
+                for v in &basis {
+                    let c = dot(v, &w);
+                    for (wl, vl) in w.iter_mut().zip(v) {
+                        *wl -= (&c * vl).complete(precision);
+                    }
+                }
+            }
+        }
+        if j + 1 == k {
+            break;
+        }
+        let b = RugMat::norm2_vec(&w);
+        if b.is_zero() {
+            break;
+        }
+        q_prev = std::mem::replace(&mut q, w.into_iter().map(|x| x / &b).collect());
+        beta.push(b);
+    }
+    (alpha, beta)
+}
+
+#[test]
+fn test_arnoldi_relation_and_breakdown() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(
+        [[2, 1, 0, 3], [1, -1, 2, 0], [0, 4, 1, 1], [1, 0, 2, 2]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let v0: Vec<Float> = (1..=4).map(|i| Float::with_val(precision, i)).collect();
+    let arn = arnoldi(&a, &v0, 3);
+    assert_eq!(arn.steps(), 3);
+    let v = &arn.basis;
+    let vm = RugMat::from_col_major(4, 3, v.as_slice()[..4 * 3].to_vec());
+    assert!(a.matmul(&vm).compare(&v.matmul(&arn.h)).max_abs < 1e-30);
+    let id = RugMat::identity(4, precision);
+    assert!(v.transpose().matmul(v).compare(&id).max_abs < 1e-30);
+
+    // An eigenvector start spans an invariant space after one step
+    let d = RugMat::diagonal_from_f64(&[3.0, 1.0], precision);
+    let e1 = [Float::with_val(precision, 1), Float::with_val(precision, 0)];
+    let arn = arnoldi(&d, &e1, 2);
+    assert_eq!(arn.steps(), 1);
+    let ritz = arn.ritz_values(1e-36);
+    assert!(*ritz[0].real() == 3 && ritz[0].imag().is_zero());
+}
@@ -1,5 +1,51 @@
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod cholesky;
+pub mod cod;
+pub mod compare;
+pub mod compatibility;
+pub mod eigen;
 pub mod float_serializer;
+pub mod gp;
+pub mod interp;
+pub mod kernel;
+pub mod krylov;
+pub mod lse;
+pub mod lu;
+pub mod nearness;
+pub mod newton;
+pub mod nnls;
+pub mod nonnormal;
+pub mod operator;
+pub mod poly;
+pub mod preconditioner;
+pub mod prelude;
+pub mod provenance;
+pub mod qr;
+pub mod robust;
 pub mod rugmat;
+pub mod rugmat_fmt;
 pub mod rugmat_io;
+pub mod scaling;
+pub mod shaped;
+pub mod small;
+pub mod solvers;
+pub mod stochastic;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+pub mod structured;
+pub mod svd;
+pub mod tikhonov;
+pub mod tls;
+pub mod triangular;
+pub mod tsqr;
 
-pub use rugmat::RugMat;
+pub use operator::{
+    BlockDiagonal, ColumnScaled, DeflatedOperator, DiagonalOperator, LinearOperator, LowRank,
+    OpProduct, OpScaled, OpShift, OpSum, OpTranspose,
+};
+pub use rugmat::{RugMat, RugVec};
+pub use svd::SvdMethod;
+
+#[cfg(feature = "rug-reexport")]
+pub use rug;
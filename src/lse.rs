@@ -0,0 +1,203 @@
+// lse.rs: least squares with linear equality constraints
+use crate::RugMat;
+use crate::svd::working_tol;
+use rug::Float;
+use rug::ops::CompleteRound;
+use std::fmt;
+
+/// Returned by [`RugMat::solve_lse`] when the problem has no unique solution
+#[derive(Debug, Clone, PartialEq)]
+pub enum LseError {
+    /// C does not have full row rank, so Cx = d is redundant or inconsistent
+    DependentConstraints,
+    /// [A; C] does not have full column rank, so the minimizer is not unique
+    NotUnique,
+}
+
+impl fmt::Display for LseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LseError::DependentConstraints => {
+                write!(f, "constraint matrix does not have full row rank")
+            }
+            LseError::NotUnique => {
+                write!(f, "stacked matrix [A; C] does not have full column rank")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LseError {}
+
+/// Whether every diagonal entry of the leading k×k triangle of a QR factor
+/// is above k·u times the largest one
+fn full_rank(r: &RugMat, k: usize) -> bool {
+    let diag: Vec<Float> = (0..k).map(|j| r[(j, j)].clone().abs()).collect();
+    let Some(largest) = diag.iter().max_by(|p, q| p.total_cmp(q)) else {
+        return true;
+    };
+    let tol = largest.clone() * (k as f64 * working_tol(largest.prec()));
+    !largest.is_zero() && diag.iter().all(|rjj| *rjj > tol)
+}
+
+/// Solve Rᵗy = d with R the upper triangle of the leading block of r
+fn forward_substitute_transposed(r: &RugMat, d: &[Float]) -> Vec<Float> {
+    let mut y = d.to_vec();
+    for i in 0..y.len() {
+        for j in 0..i {
+            let update = (&r[(j, i)] * &y[j]).complete(y[i].prec());
+            y[i] -= update;
+        }
+        y[i] /= &r[(i, i)];
+    }
+    y
+}
+
+impl RugMat {
+    /// Minimize ‖Ax - b‖ subject to Cx = d, for C p×n with full row rank and
+    /// [A; C] with full column rank, by the null-space method.
+    ///
+    /// With Cᵗ = Q R, x = Q₁y₁ + Q₂y₂ where Rᵗy₁ = d fixes the constrained
+    /// part and y₂ is the least-squares solution of (AQ₂) y₂ = b - AQ₁y₁.
+    /// Both steps are orthogonal, so the constraints hold to working
+    /// precision regardless of how A is scaled against C.
+    pub fn solve_lse(&self, b: &[Float], c: &RugMat, d: &[Float]) -> Result<Vec<Float>, LseError> {
+        let (m, n) = (self.nrows(), self.ncols());
+        let p = c.nrows();
+        assert_eq!(c.ncols(), n, "C must have as many columns as A");
+        assert_eq!(b.len(), m, "b has the wrong dimension");
+        assert_eq!(d.len(), p, "d has the wrong dimension");
+        if p > n {
+            return Err(LseError::DependentConstraints);
+        }
+        if m + p < n {
+            return Err(LseError::NotUnique);
+        }
+        if p == 0 {
+            let qr = self.qr();
+            return if full_rank(&qr.qr, n) {
+                Ok(qr.solve_least_squares(b))
+            } else {
+                Err(LseError::NotUnique)
+            };
+        }
+
+        let qr_c = c.transpose().qr();
+        if !full_rank(&qr_c.qr, p) {
+            return Err(LseError::DependentConstraints);
+        }
+        let mut y = forward_substitute_transposed(&qr_c.qr, d);
+        if p < n {
+            // AQ = (QᵗAᵗ)ᵗ, whose first p columns are AQ₁ and the rest AQ₂
+            let aq = qr_c.apply_qt_mat(&self.transpose()).transpose();
+            let aq1 = RugMat::from_col_major(m, p, aq.as_slice()[..m * p].to_vec());
+            let aq2 = RugMat::from_col_major(m, n - p, aq.as_slice()[m * p..].to_vec());
+            let rhs: Vec<Float> = b
+                .iter()
+                .zip(aq1.matmul_vec(&y))
+                .map(|(bi, ai)| (bi - &ai).complete(bi.prec()))
+                .collect();
+            let qr_a = aq2.qr();
+            if !full_rank(&qr_a.qr, n - p) {
+                return Err(LseError::NotUnique);
+            }
+            y.extend(qr_a.solve_least_squares(&rhs));
+        }
+        Ok(qr_c.apply_q(&y))
+    }
+}
+
+#[test]
+fn test_lse_matches_kkt_system() {
+    // Cubic fit to samples of exp on [0, 1] that interpolates both endpoints
+    let precision = 256;
+    let (m, n) = (12, 4);
+    let mut a = RugMat::new(m, n, precision);
+    let mut b = Vec::with_capacity(m);
+    for i in 0..m {
+        let t = Float::with_val(precision, i) / (m - 1) as u32;
+        let mut power = Float::with_val(precision, 1);
+        for j in 0..n {
+            a[(i, j)] = power.clone();
+            power *= &t;
+        }
+        b.push(t.exp());
+    }
+    let mut c = RugMat::new(2, n, precision);
+    for j in 0..n {
+        c[(0, j)] = Float::with_val(precision, (j == 0) as u32);
+        c[(1, j)] = Float::with_val(precision, 1);
+    }
+    let d = vec![
+        Float::with_val(precision, 1),
+        Float::with_val(precision, 1).exp(),
+    ];
+
+    let x = a.solve_lse(&b, &c, &d).unwrap();
+    for (ci, di) in c.matmul_vec(&x).iter().zip(&d) {
+        assert!((ci - di).complete(precision).abs() < 1e-70);
+    }
+
+    // [AᵗA Cᵗ; C 0] [x; μ] = [Aᵗb; d]
+    let ata = a.transpose().matmul(&a);
+    let mut kkt = RugMat::new(n + 2, n + 2, precision);
+    for j in 0..n {
+        for i in 0..n {
+            kkt[(i, j)] = ata[(i, j)].clone();
+        }
+        for i in 0..2 {
+            kkt[(n + i, j)] = c[(i, j)].clone();
+            kkt[(j, n + i)] = c[(i, j)].clone();
+        }
+    }
+    let mut rhs = a.matmul_transpose_vec(&b);
+    rhs.extend(d);
+    let expected = kkt.lu_decompose_pivot().solve(&rhs);
+    for (xi, ei) in x.iter().zip(&expected) {
+        assert!((xi - ei).complete(precision).abs() < 1e-60);
+    }
+}
+
+#[test]
+fn test_lse_rejects_degenerate_problems() {
+    let precision = 128;
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0, 0.0], precision);
+    let b = vec![Float::with_val(precision, 1); 3];
+
+    // The same constraint twice
+    let mut c = RugMat::new(2, 3, precision);
+    for i in 0..2 {
+        c[(i, 0)] = Float::with_val(precision, 1);
+        c[(i, 1)] = Float::with_val(precision, -1);
+    }
+    let d = vec![Float::with_val(precision, 0); 2];
+    assert_eq!(a.solve_lse(&b, &c, &d), Err(LseError::DependentConstraints));
+
+    // x₃ is seen by neither A nor C
+    let c = RugMat::from_col_major(
+        1,
+        3,
+        vec![
+            Float::with_val(precision, 1),
+            Float::with_val(precision, 1),
+            Float::with_val(precision, 0),
+        ],
+    );
+    let d = vec![Float::with_val(precision, 1)];
+    assert_eq!(a.solve_lse(&b, &c, &d), Err(LseError::NotUnique));
+
+    // Constraining x₃ instead makes the problem well posed
+    let c = RugMat::from_col_major(
+        1,
+        3,
+        vec![
+            Float::with_val(precision, 0),
+            Float::with_val(precision, 0),
+            Float::with_val(precision, 1),
+        ],
+    );
+    let x = a.solve_lse(&b, &c, &d).unwrap();
+    assert_eq!(x[2], 1);
+    assert!((x[0].clone() - 1u32).abs() < 1e-35);
+    assert!((x[1].clone() - 0.5f64).abs() < 1e-35);
+}
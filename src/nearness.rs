@@ -0,0 +1,143 @@
+// nearness.rs: nearest structured matrices in the Frobenius norm
+use crate::RugMat;
+use crate::svd::{JACOBI_SWEEPS, working_tol};
+use rug::{Assign, Float};
+
+impl RugMat {
+    /// Projection of a symmetric matrix onto the positive semidefinite cone,
+    /// computed as (B + H) / 2 where H = V Σ Vᵗ is the symmetric polar factor of B.
+    fn psd_projection(b: &RugMat) -> RugMat {
+        let precision = b.as_slice()[0].prec();
+        let svd = b.svd_jacobi(JACOBI_SWEEPS, working_tol(precision));
+        let n = b.nrows();
+
+        // H = V Σ Vᵗ
+        let mut sigma_vt = svd.vt.clone();
+        for j in 0..n {
+            for i in 0..n {
+                sigma_vt[(i, j)] *= &svd.s[i];
+            }
+        }
+        let h = svd.vt.transpose().matmul(&sigma_vt);
+
+        let mut x = b.clone();
+        for (xi, hi) in x.as_mut_slice().iter_mut().zip(h.as_slice()) {
+            *xi += hi;
+            *xi /= 2;
+        }
+        x.symmetrize();
+        x
+    }
+
+    /// Nearest symmetric positive semidefinite matrix in the Frobenius norm
+    /// (Higham, 1988): symmetrize A, then average it with its polar factor,
+    /// which zeroes the negative part of the spectrum and keeps the rest.
+    pub fn nearest_spd(&self) -> RugMat {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let mut b = self.clone();
+        b.symmetrize();
+        Self::psd_projection(&b)
+    }
+
+    /// Nearest correlation matrix (symmetric PSD with unit diagonal) using
+    /// Higham's (2002) alternating projections with Dykstra's correction.
+    ///
+    /// Iterates until the relative change between the PSD and unit-diagonal
+    /// iterates drops below `tol` (in the Frobenius norm) or `max_iters` is hit.
+    pub fn nearest_correlation(&self, tol: f64, max_iters: usize) -> RugMat {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let precision = self.as_slice()[0].prec();
+        let n = self.nrows();
+        let tol = Float::with_val(precision, tol);
+
+        let mut y = self.clone();
+        y.symmetrize();
+        let mut correction = RugMat::new(n, n, precision);
+        // Workspaces overwritten in place every iteration
+        let mut r = RugMat::new(n, n, precision);
+        let mut diff = RugMat::new(n, n, precision);
+
+        for _ in 0..max_iters {
+            // R = Y - ΔS, X = P_S(R), ΔS = X - R
+            r.copy_from(&y);
+            for (ri, ci) in r.as_mut_slice().iter_mut().zip(correction.as_slice()) {
+                *ri -= ci;
+            }
+            let x = Self::psd_projection(&r);
+            for ((ci, xi), ri) in correction
+                .as_mut_slice()
+                .iter_mut()
+                .zip(x.as_slice())
+                .zip(r.as_slice())
+            {
+                ci.assign(xi - ri);
+            }
+
+            // Y = P_U(X): restore the unit diagonal
+            y.copy_from(&x);
+            for i in 0..n {
+                y[(i, i)].assign(1);
+            }
+
+            diff.copy_from(&x);
+            for (di, yi) in diff.as_mut_slice().iter_mut().zip(y.as_slice()) {
+                *di -= yi;
+            }
+            let converged = diff.frobenius_norm() <= tol.clone() * y.frobenius_norm();
+            if converged {
+                break;
+            }
+        }
+        y
+    }
+}
+
+#[test]
+fn test_nearest_spd_clamps_negative_eigenvalue() {
+    let precision = 128;
+    // Eigenvalues 3 and -1 with eigenvectors (1, 1) and (1, -1)
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 2)],
+        vec![Float::with_val(precision, 2), Float::with_val(precision, 1)],
+    ]);
+    let x = a.nearest_spd();
+    for j in 0..2 {
+        for i in 0..2 {
+            assert!((x[(i, j)].to_f64() - 1.5).abs() < 1e-30);
+        }
+    }
+}
+
+#[test]
+fn test_nearest_correlation_higham_example() {
+    let precision = 128;
+    let one = || Float::with_val(precision, 1);
+    let zero = || Float::with_val(precision, 0);
+    let a = RugMat::from_vecvec(vec![
+        vec![one(), one(), zero()],
+        vec![one(), one(), one()],
+        vec![zero(), one(), one()],
+    ]);
+    let x = a.nearest_correlation(1e-20, 1000);
+
+    // Reference values from Higham (2002), Section 5
+    let expected = [
+        [1.0, 0.7607, 0.1573],
+        [0.7607, 1.0, 0.7607],
+        [0.1573, 0.7607, 1.0],
+    ];
+    for (i, row) in expected.iter().enumerate() {
+        for (j, &e) in row.iter().enumerate() {
+            assert!((x[(i, j)].to_f64() - e).abs() < 1e-4);
+        }
+    }
+    assert!(x.is_symmetric(0.0));
+}
@@ -0,0 +1,168 @@
+// newton.rs: Newton iterations for implicit time stepping with Jacobian reuse
+use crate::RugMat;
+use crate::lu::LU;
+use rug::Float;
+
+/// When [`NewtonLoop`] evaluates and factors a fresh Jacobian
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactorizationPolicy {
+    /// Every Newton iteration (full Newton)
+    EveryIteration,
+    /// Once at the start of every [`NewtonLoop::solve`] call
+    EveryStep,
+    /// Keep the factorization for up to `max_age` solves (time steps), and
+    /// refactor early whenever the residual fails to halve
+    Reuse { max_age: usize },
+}
+
+/// Outcome of one [`NewtonLoop::solve`]
+#[derive(Debug, Clone)]
+pub struct NewtonStep {
+    pub iterations: usize,
+    /// ‖F(x)‖₂ at the returned x
+    pub residual_norm: Float,
+    pub converged: bool,
+    /// Jacobian factorizations performed during this solve
+    pub factorizations: usize,
+}
+
+/// Solves the nonlinear system F(x) = 0 of each step of an implicit
+/// integrator, reusing LU factorizations of the Jacobian across Newton
+/// iterations and time steps according to a [`FactorizationPolicy`].
+#[derive(Debug, Clone)]
+pub struct NewtonLoop {
+    pub policy: FactorizationPolicy,
+    pub max_iters: usize,
+    /// Converged once ‖F(x)‖₂ <= tol
+    pub tol: f64,
+    lu: Option<LU>,
+    /// Solves since the cached factorization was computed
+    age: usize,
+    /// Factorizations over the lifetime of the loop
+    pub total_factorizations: usize,
+}
+
+impl NewtonLoop {
+    pub fn new(policy: FactorizationPolicy, max_iters: usize, tol: f64) -> Self {
+        NewtonLoop {
+            policy,
+            max_iters,
+            tol,
+            lu: None,
+            age: 0,
+            total_factorizations: 0,
+        }
+    }
+
+    /// Drop the cached factorization, e.g. after a step size change that
+    /// makes the old iteration matrix I - hJ useless
+    pub fn invalidate(&mut self) {
+        self.lu = None;
+    }
+
+    /// Newton iteration for residual(x) = 0 starting from x, updated in
+    /// place. `jacobian` is only called when the policy asks for a new
+    /// factorization.
+    pub fn solve<F, J>(&mut self, x: &mut [Float], mut residual: F, mut jacobian: J) -> NewtonStep
+    where
+        F: FnMut(&[Float]) -> Vec<Float>,
+        J: FnMut(&[Float]) -> RugMat,
+    {
+        let precision = x[0].prec();
+        let tol = Float::with_val(precision, self.tol);
+        let mut factorizations = 0;
+        let mut refactor = |lu: &mut Option<LU>, x: &[Float]| {
+            *lu = Some(jacobian(x).lu_decompose_pivot());
+            factorizations += 1;
+        };
+
+        let stale = match self.policy {
+            FactorizationPolicy::EveryIteration | FactorizationPolicy::EveryStep => true,
+            FactorizationPolicy::Reuse { max_age } => self.age >= max_age,
+        };
+        if stale || self.lu.is_none() {
+            refactor(&mut self.lu, x);
+            self.age = 0;
+        }
+        self.age += 1;
+
+        let mut f = residual(x);
+        let mut norm = RugMat::norm2_vec(&f);
+        let mut iterations = 0;
+        let mut fresh = true;
+        while norm > tol && iterations < self.max_iters {
+            if iterations > 0 && self.policy == FactorizationPolicy::EveryIteration {
+                refactor(&mut self.lu, x);
+                fresh = true;
+            }
+            let dx = self.lu.as_ref().unwrap().solve(&f);
+            for (xi, di) in x.iter_mut().zip(&dx) {
+                *xi -= di;
+            }
+            iterations += 1;
+            let f_new = residual(x);
+            let norm_new = RugMat::norm2_vec(&f_new);
+            // A stale Jacobian that no longer contracts gets replaced once
+            if !fresh && norm_new.clone() * 2u32 > norm {
+                refactor(&mut self.lu, x);
+                self.age = 1;
+                fresh = true;
+            } else {
+                fresh = false;
+            }
+            f = f_new;
+            norm = norm_new;
+        }
+
+        self.total_factorizations += factorizations;
+        NewtonStep {
+            iterations,
+            converged: norm <= tol,
+            residual_norm: norm,
+            factorizations,
+        }
+    }
+}
+
+#[test]
+fn test_newton_loop_backward_euler_reuse() {
+    let precision = 128;
+    // Stiff nonlinear system y' = f(y), f = (-1000 (y₀ - y₁²), -y₁), by
+    // backward Euler: F(z) = z - y - h f(z) = 0
+    let h = Float::with_val(precision, 0.01);
+    let step = |policy, steps: usize| {
+        let mut newton = NewtonLoop::new(policy, 20, 1e-35);
+        let mut y = vec![Float::with_val(precision, 2), Float::with_val(precision, 1)];
+        for _ in 0..steps {
+            let y_old = y.clone();
+            let residual = |z: &[Float]| {
+                let f0: Float = (z[1].clone().square() - &z[0]) * 1000u32;
+                let f1 = -z[1].clone();
+                vec![
+                    z[0].clone() - &y_old[0] - f0 * &h,
+                    z[1].clone() - &y_old[1] - f1 * &h,
+                ]
+            };
+            let jacobian = |z: &[Float]| {
+                let hk = Float::with_val(precision, &h * 1000u32);
+                RugMat::from_vecvec(vec![
+                    vec![hk.clone() + 1u32, -(hk * &z[1]) * 2u32],
+                    vec![Float::with_val(precision, 0), h.clone() + 1u32],
+                ])
+            };
+            let out = newton.solve(&mut y, residual, jacobian);
+            assert!(out.converged);
+        }
+        (y, newton.total_factorizations)
+    };
+
+    let (y_full, full) = step(FactorizationPolicy::EveryIteration, 10);
+    let (y_step, per_step) = step(FactorizationPolicy::EveryStep, 10);
+    let (y_reuse, reused) = step(FactorizationPolicy::Reuse { max_age: 5 }, 10);
+    assert_eq!(per_step, 10);
+    assert!(reused < per_step && per_step < full);
+    for ((a, b), c) in y_full.iter().zip(&y_step).zip(&y_reuse) {
+        assert!((a.clone() - b).abs() < 1e-34);
+        assert!((a.clone() - c).abs() < 1e-34);
+    }
+}
@@ -0,0 +1,459 @@
+// nnls.rs: non-negative and box-constrained least squares
+use crate::RugMat;
+use crate::rugmat::dot;
+use crate::svd::working_tol;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Result of [`RugMat::nnls`]
+#[derive(Debug, Clone)]
+pub struct NnlsFit {
+    pub x: Vec<Float>,
+    /// Gradient w = Aᵗ(b - Ax) of -½‖Ax - b‖²; at the optimum wⱼ = 0 where
+    /// xⱼ > 0 and wⱼ <= 0 where xⱼ = 0
+    pub dual: Vec<Float>,
+    pub residual_norm: Float,
+    /// Least-squares solves on the passive set
+    pub iterations: usize,
+    /// Whether the optimality conditions were met before `max_iters`
+    pub converged: bool,
+}
+
+/// The columns of `a` listed in `cols`, in that order
+fn select_columns(a: &RugMat, cols: &[usize]) -> RugMat {
+    let rows = a.nrows();
+    let data = cols
+        .iter()
+        .flat_map(|&j| a.as_slice()[j * rows..(j + 1) * rows].iter().cloned())
+        .collect();
+    RugMat::from_col_major(rows, cols.len(), data)
+}
+
+fn gradient(a: &RugMat, x: &[Float], b: &[Float]) -> Vec<Float> {
+    let r: Vec<Float> = a
+        .matmul_vec(x)
+        .into_iter()
+        .zip(b)
+        .map(|(ax, bi)| bi.clone() - ax)
+        .collect();
+    a.matmul_transpose_vec(&r)
+}
+
+impl RugMat {
+    /// Non-negative least squares min ‖Ax - b‖ subject to x >= 0.
+    ///
+    /// Lawson–Hanson: a passive set of free variables grows by the one with
+    /// the largest gradient wⱼ while some wⱼ exceeds tol·‖Aᵗb‖∞, and the
+    /// least-squares solution on the passive set (by QR) is pulled back
+    /// along the segment from the current x whenever it leaves the feasible
+    /// region. `max_iters` bounds the number of those solves; 3n is ample
+    /// in practice.
+    ///
+    /// The columns entering the passive set must stay linearly independent,
+    /// which the method guarantees in exact arithmetic. A variable that
+    /// enters with a non-positive value, possible only through rounding,
+    /// ends the iteration as converged.
+    pub fn nnls(&self, b: &[Float], max_iters: usize, tol: f64) -> NnlsFit {
+        assert_eq!(b.len(), self.nrows(), "b must have one entry per row");
+        let precision = self.as_slice()[0].prec();
+        let n = self.ncols();
+        let zero = Float::with_val(precision, 0);
+        let mut x = vec![zero.clone(); n];
+        let mut passive = vec![false; n];
+        let threshold = self
+            .matmul_transpose_vec(b)
+            .into_iter()
+            .map(Float::abs)
+            .fold(zero.clone(), |m, v| if v > m { v } else { m })
+            * tol;
+        let mut iterations = 0;
+        let mut converged = false;
+
+        'outer: while iterations < max_iters {
+            let w = gradient(self, &x, b);
+            let entering = (0..n)
+                .filter(|&j| !passive[j] && w[j] > threshold)
+                .max_by(|&i, &j| w[i].total_cmp(&w[j]));
+            let Some(t) = entering else {
+                converged = true;
+                break;
+            };
+            passive[t] = true;
+
+            loop {
+                let cols: Vec<usize> = (0..n).filter(|&j| passive[j]).collect();
+                let z_p = select_columns(self, &cols).qr().solve_least_squares(b);
+                iterations += 1;
+                let mut z = vec![zero.clone(); n];
+                for (&j, zj) in cols.iter().zip(z_p) {
+                    z[j] = zj;
+                }
+                if cols
+                    .iter()
+                    .all(|&j| z[j].is_sign_positive() && !z[j].is_zero())
+                {
+                    x = z;
+                    break;
+                }
+                if x[t].is_zero() && (z[t].is_sign_negative() || z[t].is_zero()) {
+                    // t cannot enter: its gradient was a rounding artefact
+                    passive[t] = false;
+                    converged = true;
+                    break 'outer;
+                }
+
+                // Step from x towards z as far as feasibility allows; the
+                // variable that blocks the step leaves the passive set
+                let (blocking, alpha) = cols
+                    .iter()
+                    .filter(|&&j| z[j].is_sign_negative() || z[j].is_zero())
+                    .map(|&j| {
+                        let gap = (&x[j] - &z[j]).complete(precision);
+                        (j, (&x[j] / &gap).complete(precision))
+                    })
+                    .min_by(|p, q| p.1.total_cmp(&q.1))
+                    .unwrap();
+                for &j in &cols {
+                    let step = (&z[j] - &x[j]).complete(precision) * &alpha;
+                    x[j] += step;
+                    if j == blocking || x[j].is_sign_negative() || x[j].is_zero() {
+                        x[j] = zero.clone();
+                        passive[j] = false;
+                    }
+                }
+                if iterations >= max_iters {
+                    break 'outer;
+                }
+            }
+        }
+
+        let dual = gradient(self, &x, b);
+        let r: Vec<Float> = self
+            .matmul_vec(&x)
+            .into_iter()
+            .zip(b)
+            .map(|(ax, bi)| ax - bi)
+            .collect();
+        NnlsFit {
+            residual_norm: dot(&r, &r).sqrt(),
+            x,
+            dual,
+            iterations,
+            converged,
+        }
+    }
+}
+
+/// Result of [`RugMat::bounded_least_squares`]
+#[derive(Debug, Clone)]
+pub struct BoundedFit {
+    pub x: Vec<Float>,
+    pub residual_norm: Float,
+    /// Norm of Aᵗ(b - Ax) with the components pushing against an active
+    /// bound removed; zero at the optimum
+    pub projected_gradient_norm: Float,
+    /// Projected-gradient steps, each followed by CG on the free variables
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Armijo sufficient-decrease constant of the projected search
+const ARMIJO: f64 = 1e-4;
+
+/// Clamp every entry of x into [lower, upper]
+fn project(x: &mut [Float], lower: &[Float], upper: &[Float]) {
+    for ((xi, l), u) in x.iter_mut().zip(lower).zip(upper) {
+        if *xi < *l {
+            xi.clone_from(l);
+        } else if *xi > *u {
+            xi.clone_from(u);
+        }
+    }
+}
+
+/// w with the components that would leave the box from a bound zeroed
+fn projected(w: &[Float], x: &[Float], lower: &[Float], upper: &[Float]) -> Vec<Float> {
+    let precision = w[0].prec();
+    w.iter()
+        .zip(x)
+        .zip(lower.iter().zip(upper))
+        .map(|((wi, xi), (l, u))| {
+            let blocked = (xi <= l && wi.is_sign_negative()) || (xi >= u && wi.is_sign_positive());
+            if blocked {
+                Float::with_val(precision, 0)
+            } else {
+                wi.clone()
+            }
+        })
+        .collect()
+}
+
+fn half_squared_residual(a: &RugMat, x: &[Float], b: &[Float]) -> Float {
+    let r: Vec<Float> = a
+        .matmul_vec(x)
+        .into_iter()
+        .zip(b)
+        .map(|(ax, bi)| ax - bi)
+        .collect();
+    dot(&r, &r) / 2u32
+}
+
+impl RugMat {
+    /// Least squares min ‖Ax - b‖ subject to lower <= x <= upper, with
+    /// infinite entries for one-sided or free variables.
+    ///
+    /// Gradient projection with subspace CG (Moré–Toraldo): each iteration
+    /// takes a projected steepest-descent step, backtracking until the
+    /// Armijo condition holds, to settle which bounds are active, then runs
+    /// CG on the normal equations of the free variables until it converges
+    /// or reaches a bound. Stops when the projected gradient falls to the
+    /// working precision relative to ‖Aᵗb‖ + ‖Aᵗ(b - Ax₀)‖, x₀ being the
+    /// projection of 0; on a face where A has full column rank the CG phase
+    /// solves exactly, so few iterations are needed once the active set is
+    /// right.
+    pub fn bounded_least_squares(
+        &self,
+        b: &[Float],
+        lower: &[Float],
+        upper: &[Float],
+    ) -> BoundedFit {
+        assert_eq!(b.len(), self.nrows(), "b must have one entry per row");
+        assert_eq!(lower.len(), self.ncols(), "One lower bound per column");
+        assert_eq!(upper.len(), self.ncols(), "One upper bound per column");
+        assert!(
+            lower.iter().zip(upper).all(|(l, u)| l <= u),
+            "Lower bounds must not exceed upper bounds"
+        );
+        let precision = self.as_slice()[0].prec();
+        let n = self.ncols();
+        let max_iters = 10 * n + 10;
+
+        let mut x = vec![Float::with_val(precision, 0); n];
+        project(&mut x, lower, upper);
+        let reference = RugMat::norm2_vec(&self.matmul_transpose_vec(b))
+            + RugMat::norm2_vec(&gradient(self, &x, b));
+        let threshold = reference * (16.0 * working_tol(precision));
+        let mut iterations = 0;
+        let mut converged = false;
+
+        while iterations < max_iters {
+            let w = gradient(self, &x, b);
+            let pw = projected(&w, &x, lower, upper);
+            if RugMat::norm2_vec(&pw) <= threshold {
+                converged = true;
+                break;
+            }
+            iterations += 1;
+
+            // Projected search along w from the exact step of the
+            // unconstrained line minimization
+            let apw = self.matmul_vec(&pw);
+            let curvature = dot(&apw, &apw);
+            let mut t = if curvature.is_zero() {
+                Float::with_val(precision, 1)
+            } else {
+                dot(&pw, &pw) / curvature
+            };
+            let f0 = half_squared_residual(self, &x, b);
+            for _ in 0..precision {
+                let mut trial: Vec<Float> = x
+                    .iter()
+                    .zip(&w)
+                    .map(|(xi, wi)| (wi * &t).complete(precision) + xi)
+                    .collect();
+                project(&mut trial, lower, upper);
+                let decrease: Float = trial
+                    .iter()
+                    .zip(&x)
+                    .zip(&w)
+                    .map(|((ti, xi), wi)| (ti - xi).complete(precision) * wi)
+                    .fold(Float::with_val(precision, 0), |acc, v| acc + v);
+                if half_squared_residual(self, &trial, b) <= &f0 - decrease * ARMIJO {
+                    x = trial;
+                    break;
+                }
+                t /= 2u32;
+            }
+
+            // CG on the free variables until converged or blocked by a bound
+            let free: Vec<bool> = (0..n).map(|j| lower[j] < x[j] && x[j] < upper[j]).collect();
+            let restrict = |v: Vec<Float>| -> Vec<Float> {
+                v.into_iter()
+                    .zip(&free)
+                    .map(|(vi, &f)| if f { vi } else { Float::with_val(precision, 0) })
+                    .collect()
+            };
+            let mut r = restrict(gradient(self, &x, b));
+            let mut p = r.clone();
+            let mut rs = dot(&r, &r);
+            for _ in 0..free.iter().filter(|&&f| f).count() {
+                if rs.clone().sqrt() <= threshold {
+                    break;
+                }
+                let ap = self.matmul_vec(&p);
+                let pap = dot(&ap, &ap);
+                if pap.is_zero() {
+                    break;
+                }
+                let alpha = (&rs / &pap).complete(precision);
+                // Largest step keeping every free variable inside its bounds
+                let mut blocking = None;
+                let mut step_max = alpha.clone();
+                for j in (0..n).filter(|&j| free[j] && !p[j].is_zero()) {
+                    let bound = if p[j].is_sign_positive() {
+                        &upper[j]
+                    } else {
+                        &lower[j]
+                    };
+                    let room = (bound - &x[j]).complete(precision) / &p[j];
+                    if room < step_max {
+                        step_max = room;
+                        blocking = Some((j, bound));
+                    }
+                }
+                for (xj, pj) in x.iter_mut().zip(&p) {
+                    *xj += (&step_max * pj).complete(precision);
+                }
+                if let Some((j, bound)) = blocking {
+                    x[j].clone_from(bound);
+                    project(&mut x, lower, upper);
+                    break;
+                }
+                let atap = restrict(self.matmul_transpose_vec(&ap));
+                for (ri, qi) in r.iter_mut().zip(&atap) {
+                    *ri -= (&alpha * qi).complete(precision);
+                }
+                let rs_new = dot(&r, &r);
+                let beta = (&rs_new / &rs).complete(precision);
+                for (pi, ri) in p.iter_mut().zip(&r) {
+                    *pi *= &beta;
+                    *pi += ri;
+                }
+                rs = rs_new;
+            }
+        }
+
+        let pw = projected(&gradient(self, &x, b), &x, lower, upper);
+        BoundedFit {
+            residual_norm: (half_squared_residual(self, &x, b) * 2u32).sqrt(),
+            projected_gradient_norm: RugMat::norm2_vec(&pw),
+            x,
+            iterations,
+            converged,
+        }
+    }
+}
+
+#[test]
+fn test_nnls_matches_unconstrained_when_interior() {
+    let precision = 128;
+    let a = RugMat::diagonal_from_f64(&[2.0, 3.0, 4.0], precision);
+    let b: Vec<Float> = [1.0, 6.0, 2.0]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let fit = a.nnls(&b, 10, 1e-30);
+    assert!(fit.converged);
+    assert_eq!(fit.x, [0.5, 2.0, 0.5]);
+    assert!(fit.residual_norm.is_zero());
+}
+
+#[test]
+fn test_nnls_clamps_and_satisfies_kkt() {
+    let precision = 128;
+    // Unconstrained solution (1, -1) of a well-conditioned 3×2 problem
+    let data = [1.0, 1.0, 0.0, 1.0, -1.0, 1.0]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let a = RugMat::from_col_major(3, 2, data);
+    let b: Vec<Float> = [0.0, 2.0, -1.0]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let fit = a.nnls(&b, 10, 1e-30);
+    assert!(fit.converged);
+    // With x₂ = 0 the best x₁ is (Aᵗb)₁ / ‖a₁‖² = 1
+    assert_eq!(fit.x, [1.0, 0.0]);
+    assert!(fit.dual[0].clone().abs() < 1e-35);
+    assert!(fit.dual[1] < 0);
+}
+
+#[test]
+fn test_nnls_recovers_sparse_exponential_mixture() {
+    // b(t) = 2e^{-2t} + ½e^{-5t} sampled on [0, 4], fitted by eight decay
+    // rates: a moment-type problem whose columns are nearly dependent
+    let precision = 256;
+    let (m, rates) = (40, 1..=8u32);
+    let mut a = RugMat::new(m, rates.clone().count(), precision);
+    for (j, rate) in rates.enumerate() {
+        for i in 0..m {
+            let t = Float::with_val(precision, i) / 10u32;
+            a[(i, j)] = (-(t * rate)).exp();
+        }
+    }
+    let mut truth = vec![Float::with_val(precision, 0); 8];
+    truth[1] = Float::with_val(precision, 2);
+    truth[4] = Float::with_val(precision, 0.5);
+    let b = a.matmul_vec(&truth);
+
+    let fit = a.nnls(&b, 24, 1e-60);
+    assert!(fit.converged);
+    for (x, t) in fit.x.iter().zip(&truth) {
+        assert!((x - t).complete(precision).abs() < 1e-40, "{} vs {}", x, t);
+    }
+    assert!(fit.residual_norm < 1e-60);
+}
+
+#[test]
+fn test_bounded_least_squares_interior_nonnegative_and_box() {
+    let precision = 128;
+    let data = (0..24)
+        .map(|k| Float::with_val(precision, ((k * 7) % 11) as f64 - 5.0) / 4u32)
+        .collect();
+    let a = RugMat::from_col_major(6, 4, data);
+    let b: Vec<Float> = (0..6)
+        .map(|i| Float::with_val(precision, i as f64 - 2.5))
+        .collect();
+    let inf = Float::with_val(precision, f64::INFINITY);
+    let free = |value: &Float| vec![value.clone(); 4];
+    let close = |p: &[Float], q: &[Float]| {
+        p.iter()
+            .zip(q)
+            .all(|(s, t)| (s - t).complete(precision).abs() < 1e-30)
+    };
+
+    // Infinite bounds: ordinary least squares
+    let fit = a.bounded_least_squares(&b, &free(&-inf.clone()), &free(&inf));
+    assert!(fit.converged);
+    assert!(close(&fit.x, &a.qr().solve_least_squares(&b)));
+
+    // [0, ∞): the NNLS solution
+    let zero = Float::with_val(precision, 0);
+    let fit = a.bounded_least_squares(&b, &free(&zero), &free(&inf));
+    assert!(fit.converged);
+    assert!(close(&fit.x, &a.nnls(&b, 20, 1e-30).x));
+
+    // A box: KKT conditions, which suffice for this convex problem
+    let (lo, hi) = (
+        Float::with_val(precision, -0.5),
+        Float::with_val(precision, 0.5),
+    );
+    let fit = a.bounded_least_squares(&b, &free(&lo), &free(&hi));
+    assert!(fit.converged);
+    let w = gradient(&a, &fit.x, &b);
+    let mut active = 0;
+    for (xj, wj) in fit.x.iter().zip(&w) {
+        assert!(*xj >= lo && *xj <= hi);
+        if *xj == lo {
+            assert!(*wj <= 1e-30);
+            active += 1;
+        } else if *xj == hi {
+            assert!(*wj >= -1e-30);
+            active += 1;
+        } else {
+            assert!(wj.clone().abs() < 1e-30);
+        }
+    }
+    assert!(active > 0, "the box should bind");
+}
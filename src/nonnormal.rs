@@ -0,0 +1,296 @@
+// nonnormal.rs: diagnostics for non-normal matrices
+use crate::RugMat;
+use crate::krylov::arnoldi;
+use crate::svd::working_tol;
+use rayon::prelude::*;
+use rug::ops::CompleteRound;
+use rug::{Complex, Float};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// QR sweeps allowed per eigenvalue in the small projected problems
+const SWEEPS_PER_VALUE: usize = 30;
+
+/// Box [re_min, re_max] × [-im_max, im_max] around the field of values of
+/// an Arnoldi projection
+#[derive(Debug, Clone)]
+pub struct NumericalRangeBounds {
+    pub re_min: Float,
+    pub re_max: Float,
+    pub im_max: Float,
+    /// Number of Arnoldi steps actually taken (fewer on breakdown)
+    pub steps: usize,
+}
+
+impl RugMat {
+    /// Estimate the field of values W(A) = {x*Ax : x ∈ ℂⁿ, ‖x‖ = 1} from
+    /// k >= 1 Arnoldi steps started at the normalized vector (1, 2, …, n),
+    /// which unlike the ones vector is not an eigenvector of typical
+    /// structured test matrices.
+    ///
+    /// W(Hₖ) ⊆ W(A), so the bounds grow towards the true extent as k grows.
+    /// The real range is the spectrum of the symmetric part of Hₖ and the
+    /// imaginary extent the norm of its skew part (Bendixson). A positive
+    /// re_min means 0 ∉ W(Hₖ), the condition behind the classical GMRES
+    /// convergence bounds.
+    pub fn numerical_range_bounds(&self, k: usize) -> NumericalRangeBounds {
+        assert!(k >= 1, "Need at least one Arnoldi step");
+        let precision = self.as_slice()[0].prec();
+        let k = k.min(self.nrows());
+        let v0: Vec<Float> = (1..=self.nrows())
+            .map(|i| Float::with_val(precision, i))
+            .collect();
+        let arn = arnoldi(self, &v0, k);
+        let m = arn.steps();
+        let hm = arn.square_h();
+        let mut sym = hm.clone();
+        sym.symmetrize();
+        let ht = hm.transpose();
+        let mut skew = hm;
+        for (x, y) in skew.as_mut_slice().iter_mut().zip(ht.as_slice()) {
+            *x -= y;
+            *x /= 2u32;
+        }
+
+        let tol = working_tol(precision);
+        let (values, _) = sym.eigh(SWEEPS_PER_VALUE * m, tol);
+        let im_max = skew.singular_values(tol).swap_remove(0);
+        NumericalRangeBounds {
+            re_min: values[0].clone(),
+            re_max: values[m - 1].clone(),
+            im_max,
+            steps: m,
+        }
+    }
+}
+
+/// Scalar measures of how far a square matrix is from normal
+#[derive(Debug, Clone)]
+pub struct NonNormality {
+    /// ‖AᵗA - AAᵗ‖_F, zero exactly when A is normal
+    pub commutator: Float,
+    /// Henrici's departure from normality √(‖A‖_F² - Σ|λᵢ|²), the
+    /// Frobenius norm of the strictly upper part of any Schur form
+    pub henrici: Float,
+    /// henrici / ‖A‖_F, between 0 (normal) and 1 (nilpotent)
+    pub relative_henrici: Float,
+}
+
+impl RugMat {
+    /// Non-normality diagnostics. When `relative_henrici` is not small the
+    /// eigenvalues say little about transient behaviour or iterative solver
+    /// convergence, and pseudospectra are the safer tool.
+    ///
+    /// The eigenvalues come from [`RugMat::eigenvalues`] allowing 30·n
+    /// sweeps each; this panics if one of them needs more.
+    pub fn non_normality_measures(&self) -> NonNormality {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let precision = self.as_slice()[0].prec();
+        let at = self.transpose();
+        let mut commutator = at.matmul(self);
+        for (x, y) in commutator
+            .as_mut_slice()
+            .iter_mut()
+            .zip(self.matmul(&at).as_slice())
+        {
+            *x -= y;
+        }
+
+        let fro = self.frobenius_norm();
+        let mut departure = (&fro * &fro).complete(precision);
+        let values = self.eigenvalues(SWEEPS_PER_VALUE * self.nrows(), working_tol(precision));
+        for lambda in &values {
+            departure -= Float::with_val(precision, lambda.norm_ref());
+        }
+        // Rounding can leave a tiny negative residue for normal matrices
+        if departure.is_sign_negative() {
+            departure = Float::with_val(precision, 0);
+        }
+        let henrici = departure.sqrt();
+        let relative_henrici = if fro.is_zero() {
+            Float::with_val(precision, 0)
+        } else {
+            (&henrici / &fro).complete(precision)
+        };
+        NonNormality {
+            commutator: commutator.frobenius_norm(),
+            henrici,
+            relative_henrici,
+        }
+    }
+}
+
+/// σmin(A - zI) over a grid of points z, for contouring ε-pseudospectra
+#[derive(Debug, Clone)]
+pub struct Pseudospectrum {
+    pub points: Vec<Complex>,
+    /// σmin(A - zI) at each point; z lies in the ε-pseudospectrum iff it is < ε
+    pub sigma_min: Vec<Float>,
+    pub levels: Vec<f64>,
+}
+
+impl Pseudospectrum {
+    /// nx×ny grid over [re.0, re.1] × [im.0, im.1], real part varying fastest
+    pub fn grid(
+        re: (f64, f64),
+        im: (f64, f64),
+        nx: usize,
+        ny: usize,
+        precision: u32,
+    ) -> Vec<Complex> {
+        assert!(
+            nx >= 2 && ny >= 2,
+            "Grid needs at least two points per axis"
+        );
+        let coord = |(lo, hi): (f64, f64), k: usize, count: usize| {
+            let span = Float::with_val(precision, hi) - lo;
+            span * k as u32 / (count as u32 - 1) + lo
+        };
+        let mut points = Vec::with_capacity(nx * ny);
+        for j in 0..ny {
+            for i in 0..nx {
+                points.push(Complex::with_val(
+                    precision,
+                    (coord(re, i, nx), coord(im, j, ny)),
+                ));
+            }
+        }
+        points
+    }
+
+    /// Whether points[k] lies in the ε-pseudospectrum for ε = levels[level]
+    pub fn contains(&self, k: usize, level: usize) -> bool {
+        self.sigma_min[k] < self.levels[level]
+    }
+
+    /// Write re, im and sigma_min columns, plus one 0/1 column per level.
+    ///
+    /// Values use scientific notation with 17 significant digits, so
+    /// contours far below f64 resolution survive the export.
+    pub fn export_csv(&self, path: &str) -> std::io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        write!(w, "re,im,sigma_min")?;
+        for eps in &self.levels {
+            write!(w, ",eps_{:e}", eps)?;
+        }
+        writeln!(w)?;
+        for (k, (z, sigma)) in self.points.iter().zip(&self.sigma_min).enumerate() {
+            write!(w, "{:.17e},{:.17e},{:.17e}", z.real(), z.imag(), sigma)?;
+            for level in 0..self.levels.len() {
+                write!(w, ",{}", self.contains(k, level) as u8)?;
+            }
+            writeln!(w)?;
+        }
+        w.flush()
+    }
+}
+
+impl RugMat {
+    /// Smallest singular value of A - zI at every grid point, in parallel.
+    ///
+    /// The complex shift is handled through the real embedding
+    /// [A - xI, yI; -yI, A - xI] of A - (x + iy)I, whose singular values
+    /// are those of A - zI, each repeated twice.
+    pub fn pseudospectrum(&self, grid: &[Complex], levels: &[f64]) -> Pseudospectrum {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let tol = working_tol(precision);
+        let sigma_min = grid
+            .par_iter()
+            .map(|z| {
+                let mut m = RugMat::new(2 * n, 2 * n, precision);
+                m.set_block(0, 0, self);
+                m.set_block(n, n, self);
+                for i in 0..n {
+                    m[(i, i)] -= z.real();
+                    m[(n + i, n + i)] -= z.real();
+                    m[(i, n + i)] = Float::with_val(precision, z.imag());
+                    m[(n + i, i)] = -Float::with_val(precision, z.imag());
+                }
+                m.singular_values(tol).pop().unwrap()
+            })
+            .collect();
+        Pseudospectrum {
+            points: grid.to_vec(),
+            sigma_min,
+            levels: levels.to_vec(),
+        }
+    }
+}
+
+#[test]
+fn test_numerical_range_of_rotation_and_symmetric() {
+    let precision = 128;
+    // W of the quarter-turn rotation is the segment i[-1, 1]
+    let rot = RugMat::from_vecvec(vec![
+        vec![
+            Float::with_val(precision, 0),
+            Float::with_val(precision, -1),
+        ],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 0)],
+    ]);
+    let nr = rot.numerical_range_bounds(2);
+    assert!(nr.re_min.clone().abs() < 1e-35 && nr.re_max.clone().abs() < 1e-35);
+    assert!((nr.im_max - 1u32).abs() < 1e-35);
+
+    let sym = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 2), Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 2)],
+    ]);
+    let nr = sym.numerical_range_bounds(5);
+    assert!((nr.re_min - 1u32).abs() < 1e-30);
+    assert!((nr.re_max - 3u32).abs() < 1e-30);
+    assert!(nr.im_max < 1e-30);
+}
+
+#[test]
+fn test_non_normality_of_jordan_block() {
+    let precision = 128;
+    let f = |v: i32| Float::with_val(precision, v);
+    // [1 1; 0 1]: AᵗA - AAᵗ = diag(-1, 1), ‖A‖_F² = 3 and Σ|λ|² = 2
+    let jordan = RugMat::from_vecvec(vec![vec![f(1), f(1)], vec![f(0), f(1)]]);
+    let m = jordan.non_normality_measures();
+    assert!((m.commutator - Float::with_val(precision, 2).sqrt()).abs() < 1e-30);
+    assert!((m.henrici.clone() - 1u32).abs() < 1e-15);
+    assert!((m.relative_henrici - Float::with_val(precision, 3).recip_sqrt()).abs() < 1e-15);
+
+    let rot = RugMat::from_vecvec(vec![vec![f(0), f(-1)], vec![f(1), f(0)]]);
+    let m = rot.non_normality_measures();
+    assert!(m.commutator.is_zero());
+    assert!(m.henrici < 1e-15);
+}
+
+#[test]
+fn test_pseudospectrum_of_normal_matrix() {
+    let precision = 128;
+    // For normal A, σmin(A - zI) is the distance from z to the spectrum
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0], precision);
+    let grid = Pseudospectrum::grid((1.0, 2.0), (-0.5, 0.5), 3, 3, precision);
+    assert_eq!(grid.len(), 9);
+    let ps = a.pseudospectrum(&grid, &[0.1, 1.0]);
+
+    // Centre point 1.5 + 0i and corner 1.5 + 0.5i
+    assert!((ps.sigma_min[4].clone() - 0.5f64).abs() < 1e-30);
+    let corner = Float::with_val(precision, 0.5).sqrt();
+    assert!((ps.sigma_min[7].clone() - corner).abs() < 1e-30);
+    assert!(ps.contains(4, 1) && !ps.contains(4, 0));
+    // Grid points on the spectrum itself
+    assert!(ps.sigma_min[3] < 1e-30 && ps.contains(3, 0));
+
+    let path = std::env::temp_dir().join("rugmat_pseudospectrum_test.csv");
+    ps.export_csv(path.to_str().unwrap()).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("re,im,sigma_min,eps_1e-1,eps_1e0"));
+    assert_eq!(lines.count(), 9);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,643 @@
+// operator.rs: matrix-free linear operators accepted by the iterative solvers
+use crate::RugMat;
+use crate::rugmat::Transpose;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// A linear map that can be applied together with its transpose.
+///
+/// Implemented by [`RugMat`]; anything implementing it can be handed to the
+/// solvers in [`crate::solvers`] without being materialized as a matrix.
+pub trait LinearOperator: Sync {
+    /// Dimension of the output space
+    fn rows(&self) -> usize;
+    /// Dimension of the input space
+    fn cols(&self) -> usize;
+    /// y = A x
+    fn apply(&self, x: &[Float]) -> Vec<Float>;
+    /// y = Aᵗ x
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float>;
+}
+
+impl LinearOperator for RugMat {
+    fn rows(&self) -> usize {
+        self.nrows()
+    }
+
+    fn cols(&self) -> usize {
+        self.ncols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.matmul_vec(x)
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.matmul_transpose_vec(x)
+    }
+}
+
+impl LinearOperator for Transpose<'_> {
+    fn rows(&self) -> usize {
+        Transpose::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        Transpose::cols(self)
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.mat.matmul_transpose_vec(x)
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.mat.matmul_vec(x)
+    }
+}
+
+/// The deflated operator (I - VVᵗ) A (I - VVᵗ) for a square A and a basis V
+/// with orthonormal columns.
+///
+/// Solving with it finds the solution orthogonal to span(V), which removes
+/// known null-space components, e.g. the constant vector of a graph Laplacian.
+pub struct DeflatedOperator<'a, A: LinearOperator + ?Sized> {
+    pub op: &'a A,
+    pub basis: &'a RugMat,
+}
+
+impl<'a, A: LinearOperator + ?Sized> DeflatedOperator<'a, A> {
+    pub fn new(op: &'a A, basis: &'a RugMat) -> Self {
+        assert_eq!(op.rows(), op.cols(), "Deflation requires a square operator");
+        assert_eq!(basis.nrows(), op.rows(), "Basis has the wrong dimension");
+        DeflatedOperator { op, basis }
+    }
+
+    /// (I - VVᵗ) x
+    pub fn project(&self, x: &[Float]) -> Vec<Float> {
+        let coeffs = self.basis.matmul_transpose_vec(x);
+        let along = self.basis.matmul_vec(&coeffs);
+        x.iter()
+            .zip(&along)
+            .map(|(xi, ai)| (xi - ai).complete(xi.prec()))
+            .collect()
+    }
+}
+
+impl<A: LinearOperator + ?Sized> LinearOperator for DeflatedOperator<'_, A> {
+    fn rows(&self) -> usize {
+        self.op.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.op.cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.project(&self.op.apply(&self.project(x)))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.project(&self.op.apply_transpose(&self.project(x)))
+    }
+}
+
+/// Right column scaling A D with D = diag(scale).
+///
+/// Solve with it for y, then recover x = D y with [`ColumnScaled::unscale`].
+pub struct ColumnScaled<'a, A: LinearOperator + ?Sized> {
+    pub op: &'a A,
+    pub scale: Vec<Float>,
+}
+
+impl<'a, A: LinearOperator + ?Sized> ColumnScaled<'a, A> {
+    pub fn new(op: &'a A, scale: Vec<Float>) -> Self {
+        assert_eq!(scale.len(), op.cols(), "Scale has the wrong dimension");
+        ColumnScaled { op, scale }
+    }
+
+    /// x = D y
+    pub fn unscale(&self, y: &[Float]) -> Vec<Float> {
+        y.iter()
+            .zip(&self.scale)
+            .map(|(yi, di)| (yi * di).complete(yi.prec()))
+            .collect()
+    }
+}
+
+impl<'a> ColumnScaled<'a, RugMat> {
+    /// D = diag(1/‖aⱼ‖), leaving all-zero columns unscaled
+    pub fn by_column_norms(mat: &'a RugMat) -> Self {
+        let scale = mat
+            .column_norms2()
+            .into_iter()
+            .map(|n2| {
+                if n2.is_zero() {
+                    Float::with_val(n2.prec(), 1)
+                } else {
+                    n2.sqrt().recip()
+                }
+            })
+            .collect();
+        ColumnScaled { op: mat, scale }
+    }
+}
+
+impl<A: LinearOperator + ?Sized> LinearOperator for ColumnScaled<'_, A> {
+    fn rows(&self) -> usize {
+        self.op.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.op.cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.op.apply(&self.unscale(x))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.unscale(&self.op.apply_transpose(x))
+    }
+}
+
+/// diag(d), stored as just the diagonal
+pub struct DiagonalOperator {
+    pub diag: Vec<Float>,
+}
+
+impl DiagonalOperator {
+    pub fn new(diag: Vec<Float>) -> Self {
+        DiagonalOperator { diag }
+    }
+}
+
+impl LinearOperator for DiagonalOperator {
+    fn rows(&self) -> usize {
+        self.diag.len()
+    }
+
+    fn cols(&self) -> usize {
+        self.diag.len()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        assert_eq!(x.len(), self.diag.len(), "Vector has the wrong dimension");
+        x.iter()
+            .zip(&self.diag)
+            .map(|(xi, di)| (xi * di).complete(xi.prec()))
+            .collect()
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.apply(x)
+    }
+}
+
+/// diag(B₁, …, Bₖ) with blocks of any shape; the zero blocks off the
+/// diagonal are never stored
+pub struct BlockDiagonal<A: LinearOperator> {
+    pub blocks: Vec<A>,
+}
+
+impl<A: LinearOperator> BlockDiagonal<A> {
+    pub fn new(blocks: Vec<A>) -> Self {
+        BlockDiagonal { blocks }
+    }
+
+    /// Apply f(Bᵢ, xᵢ) to each slice xᵢ of length in(Bᵢ) and concatenate
+    fn blockwise(
+        &self,
+        x: &[Float],
+        input: impl Fn(&A) -> usize,
+        f: impl Fn(&A, &[Float]) -> Vec<Float>,
+    ) -> Vec<Float> {
+        let total: usize = self.blocks.iter().map(&input).sum();
+        assert_eq!(x.len(), total, "Vector has the wrong dimension");
+        let mut offset = 0;
+        let mut y = Vec::new();
+        for block in &self.blocks {
+            let len = input(block);
+            y.extend(f(block, &x[offset..offset + len]));
+            offset += len;
+        }
+        y
+    }
+}
+
+impl<A: LinearOperator> LinearOperator for BlockDiagonal<A> {
+    fn rows(&self) -> usize {
+        self.blocks.iter().map(|b| b.rows()).sum()
+    }
+
+    fn cols(&self) -> usize {
+        self.blocks.iter().map(|b| b.cols()).sum()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.blockwise(x, |b| b.cols(), |b, xi| b.apply(xi))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.blockwise(x, |b| b.rows(), |b, xi| b.apply_transpose(xi))
+    }
+}
+
+/// U Vᵗ for U m×k and V n×k, storing (m + n)k entries instead of mn
+pub struct LowRank {
+    pub u: RugMat,
+    pub v: RugMat,
+}
+
+impl LowRank {
+    pub fn new(u: RugMat, v: RugMat) -> Self {
+        assert_eq!(u.ncols(), v.ncols(), "U and V must have the same rank");
+        LowRank { u, v }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.u.ncols()
+    }
+}
+
+impl LinearOperator for LowRank {
+    fn rows(&self) -> usize {
+        self.u.nrows()
+    }
+
+    fn cols(&self) -> usize {
+        self.v.nrows()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.u.matmul_vec(&self.v.matmul_transpose_vec(x))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.v.matmul_vec(&self.u.matmul_transpose_vec(x))
+    }
+}
+
+/// References to operators are operators, so the combinators below can
+/// either own their parts or borrow them
+impl<T: LinearOperator + ?Sized> LinearOperator for &T {
+    fn rows(&self) -> usize {
+        (**self).rows()
+    }
+
+    fn cols(&self) -> usize {
+        (**self).cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        (**self).apply(x)
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        (**self).apply_transpose(x)
+    }
+}
+
+fn add(x: Vec<Float>, y: &[Float]) -> Vec<Float> {
+    x.into_iter().zip(y).map(|(xi, yi)| xi + yi).collect()
+}
+
+fn scale(x: Vec<Float>, factor: &Float) -> Vec<Float> {
+    x.into_iter().map(|xi| xi * factor).collect()
+}
+
+/// Aᵗ of any operator; for a [`RugMat`], [`Transpose`] does the same
+pub struct OpTranspose<A: LinearOperator> {
+    pub op: A,
+}
+
+impl<A: LinearOperator> OpTranspose<A> {
+    pub fn new(op: A) -> Self {
+        OpTranspose { op }
+    }
+}
+
+impl<A: LinearOperator> LinearOperator for OpTranspose<A> {
+    fn rows(&self) -> usize {
+        self.op.cols()
+    }
+
+    fn cols(&self) -> usize {
+        self.op.rows()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.op.apply_transpose(x)
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.op.apply(x)
+    }
+}
+
+/// A + B for operators of the same shape
+pub struct OpSum<A: LinearOperator, B: LinearOperator> {
+    pub left: A,
+    pub right: B,
+}
+
+impl<A: LinearOperator, B: LinearOperator> OpSum<A, B> {
+    pub fn new(left: A, right: B) -> Self {
+        assert_eq!(
+            (left.rows(), left.cols()),
+            (right.rows(), right.cols()),
+            "Summands must have the same shape"
+        );
+        OpSum { left, right }
+    }
+}
+
+impl<A: LinearOperator, B: LinearOperator> LinearOperator for OpSum<A, B> {
+    fn rows(&self) -> usize {
+        self.left.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.left.cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        add(self.left.apply(x), &self.right.apply(x))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        add(self.left.apply_transpose(x), &self.right.apply_transpose(x))
+    }
+}
+
+/// αA for a scalar α
+pub struct OpScaled<A: LinearOperator> {
+    pub op: A,
+    pub factor: Float,
+}
+
+impl<A: LinearOperator> OpScaled<A> {
+    pub fn new(factor: Float, op: A) -> Self {
+        OpScaled { op, factor }
+    }
+}
+
+impl<A: LinearOperator> LinearOperator for OpScaled<A> {
+    fn rows(&self) -> usize {
+        self.op.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.op.cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        scale(self.op.apply(x), &self.factor)
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        scale(self.op.apply_transpose(x), &self.factor)
+    }
+}
+
+/// AB, applied right to left, so A's columns must match B's rows
+pub struct OpProduct<A: LinearOperator, B: LinearOperator> {
+    pub left: A,
+    pub right: B,
+}
+
+impl<A: LinearOperator, B: LinearOperator> OpProduct<A, B> {
+    pub fn new(left: A, right: B) -> Self {
+        assert_eq!(
+            left.cols(),
+            right.rows(),
+            "Inner dimensions of the product must agree"
+        );
+        OpProduct { left, right }
+    }
+}
+
+impl<A: LinearOperator, B: LinearOperator> LinearOperator for OpProduct<A, B> {
+    fn rows(&self) -> usize {
+        self.left.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.right.cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.left.apply(&self.right.apply(x))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.right.apply_transpose(&self.left.apply_transpose(x))
+    }
+}
+
+/// A + σI for a square A
+pub struct OpShift<A: LinearOperator> {
+    pub op: A,
+    pub sigma: Float,
+}
+
+impl<A: LinearOperator> OpShift<A> {
+    pub fn new(op: A, sigma: Float) -> Self {
+        assert_eq!(op.rows(), op.cols(), "A shift requires a square operator");
+        OpShift { op, sigma }
+    }
+}
+
+impl<A: LinearOperator> LinearOperator for OpShift<A> {
+    fn rows(&self) -> usize {
+        self.op.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.op.cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        add(self.op.apply(x), &scale(x.to_vec(), &self.sigma))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        add(self.op.apply_transpose(x), &scale(x.to_vec(), &self.sigma))
+    }
+}
+
+#[test]
+fn test_deflated_cg_on_periodic_laplacian() {
+    use crate::solvers::conjugate_gradient;
+
+    let precision = 128;
+    let n = 4;
+    // Periodic 1-D Laplacian: singular, with the constant vector as null space
+    let mut lap = RugMat::new(n, n, precision);
+    for i in 0..n {
+        lap[(i, i)] = Float::with_val(precision, 2);
+        lap[((i + 1) % n, i)] = Float::with_val(precision, -1);
+        lap[(i, (i + 1) % n)] = Float::with_val(precision, -1);
+    }
+    let mut ones = RugMat::new(n, 1, precision);
+    let inv_sqrt_n = Float::with_val(precision, n).sqrt().recip();
+    for i in 0..n {
+        ones[(i, 0)] = inv_sqrt_n.clone();
+    }
+
+    let b: Vec<Float> = [1, -1, 2, -2]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let op = DeflatedOperator::new(&lap, &ones);
+    let x = conjugate_gradient(&op, &b, 50);
+
+    let mean: f64 = x.iter().map(|v| v.to_f64()).sum::<f64>() / n as f64;
+    assert!(mean.abs() < 1e-25);
+    let ax = lap.matmul_vec(&x);
+    for (axi, bi) in ax.iter().zip(&b) {
+        assert!((axi.clone() - bi).abs() < 1e-25);
+    }
+}
+
+#[test]
+fn test_composite_normal_operator_matches_materialized() {
+    use crate::solvers::{SolverOptions, gmres_with_info};
+
+    let precision = 128;
+    let (m, n) = (7, 5);
+    let mut a = RugMat::new(m, n, precision);
+    for j in 0..n {
+        for i in 0..m {
+            a[(i, j)] = Float::with_val(precision, (i + 1) * (j + 2) % 5) - 2u32;
+        }
+    }
+    // First differences as the regularizer L, (n-1)×n
+    let mut l = RugMat::new(n - 1, n, precision);
+    for i in 0..n - 1 {
+        l[(i, i)] = Float::with_val(precision, -1);
+        l[(i, i + 1)] = Float::with_val(precision, 1);
+    }
+    let lambda = Float::with_val(precision, 0.3);
+    let sigma = Float::with_val(precision, 2);
+
+    // (AᵗA + λLᵗL) + σI, built without forming any product
+    let op = OpShift::new(
+        OpSum::new(
+            OpProduct::new(OpTranspose::new(&a), &a),
+            OpScaled::new(lambda.clone(), OpProduct::new(OpTranspose::new(&l), &l)),
+        ),
+        sigma.clone(),
+    );
+    let mut dense = a.transpose().matmul(&a);
+    let ltl = l.transpose().matmul(&l);
+    for j in 0..n {
+        for i in 0..n {
+            dense[(i, j)] += (&lambda * &ltl[(i, j)]).complete(precision);
+        }
+        dense[(j, j)] += &sigma;
+    }
+
+    let x: Vec<Float> = (0..n)
+        .map(|k| Float::with_val(precision, k as f64 - 1.5))
+        .collect();
+    for (p, q) in op.apply(&x).iter().zip(dense.matmul_vec(&x)) {
+        assert!((p.clone() - &q).abs() < 1e-30);
+    }
+    for (p, q) in op
+        .apply_transpose(&x)
+        .iter()
+        .zip(dense.matmul_transpose_vec(&x))
+    {
+        assert!((p.clone() - &q).abs() < 1e-30);
+    }
+
+    let b = dense.matmul_vec(&x);
+    let outcome = gmres_with_info(&op, &b, n, &SolverOptions::new(50));
+    assert!(outcome.converged);
+    for (p, q) in outcome.x.iter().zip(&x) {
+        assert!((p.clone() - q).abs() < 1e-30);
+    }
+}
+
+#[test]
+fn test_structured_operators_match_materialized() {
+    use crate::solvers::{SolverOptions, gmres_with_info};
+
+    let precision = 160;
+    let entry = |i: usize, j: usize| Float::with_val(precision, (3 * i + 7 * j) % 11) / 11u32;
+
+    let d: Vec<Float> = (0..6).map(|k| Float::with_val(precision, k + 2)).collect();
+    let diagonal = DiagonalOperator::new(d.clone());
+
+    let mut b1 = RugMat::new(2, 3, precision);
+    let mut b2 = RugMat::new(4, 3, precision);
+    for j in 0..3 {
+        for i in 0..2 {
+            b1[(i, j)] = entry(i, j);
+        }
+        for i in 0..4 {
+            b2[(i, j)] = entry(i + 1, j + 2);
+        }
+    }
+    let blocks = BlockDiagonal::new(vec![b1.clone(), b2.clone()]);
+
+    let mut u = RugMat::new(6, 2, precision);
+    let mut v = RugMat::new(6, 2, precision);
+    for j in 0..2 {
+        for i in 0..6 {
+            u[(i, j)] = entry(i, j + 1);
+            v[(i, j)] = entry(j, i) - 0.5;
+        }
+    }
+    let low_rank = LowRank::new(u.clone(), v.clone());
+    assert_eq!(low_rank.rank(), 2);
+
+    let mut dense_blocks = RugMat::new(6, 6, precision);
+    for j in 0..3 {
+        for i in 0..2 {
+            dense_blocks[(i, j)] = b1[(i, j)].clone();
+        }
+        for i in 0..4 {
+            dense_blocks[(i + 2, j + 3)] = b2[(i, j)].clone();
+        }
+    }
+    let cases: [(&dyn LinearOperator, RugMat); 3] = [
+        (
+            &diagonal,
+            RugMat::from_col_major(6, 6, {
+                let mut data = vec![Float::new(precision); 36];
+                for k in 0..6 {
+                    data[k * 7] = d[k].clone();
+                }
+                data
+            }),
+        ),
+        (&blocks, dense_blocks),
+        (&low_rank, u.matmul(&v.transpose())),
+    ];
+
+    let x: Vec<Float> = (0..6)
+        .map(|k| Float::with_val(precision, 1) / (k + 1) as u32)
+        .collect();
+    for (op, dense) in &cases {
+        for (p, q) in op.apply(&x).iter().zip(dense.matmul_vec(&x)) {
+            assert!((p.clone() - &q).abs() < 1e-40);
+        }
+        for (p, q) in op
+            .apply_transpose(&x)
+            .iter()
+            .zip(dense.matmul_transpose_vec(&x))
+        {
+            assert!((p.clone() - &q).abs() < 1e-40);
+        }
+    }
+
+    // A diagonal plus a low-rank correction, solved without forming either
+    let op = OpSum::new(&diagonal, &low_rank);
+    let rhs = op.apply(&x);
+    let outcome = gmres_with_info(&op, &rhs, 6, &SolverOptions::new(50));
+    assert!(outcome.converged);
+    for (p, q) in outcome.x.iter().zip(&x) {
+        assert!((p.clone() - q).abs() < 1e-40);
+    }
+}
@@ -0,0 +1,75 @@
+// poly.rs: polynomial elimination through Sylvester matrix determinants
+use crate::RugMat;
+use rug::Float;
+
+/// Sylvester matrix of p and q, coefficients given leading term first.
+///
+/// For deg p = m and deg q = n it is (m+n)×(m+n): n shifted rows of p's
+/// coefficients followed by m shifted rows of q's.
+pub fn sylvester_matrix(p: &[Float], q: &[Float], prec: u32) -> RugMat {
+    let (m, n) = (p.len() - 1, q.len() - 1);
+    assert!(m + n >= 1, "Both polynomials are constants");
+    assert!(
+        !p[0].is_zero() && !q[0].is_zero(),
+        "Leading coefficients must be nonzero"
+    );
+    let size = m + n;
+    let mut s = RugMat::new(size, size, prec);
+    for row in 0..n {
+        for (k, c) in p.iter().enumerate() {
+            s[(row, row + k)] = Float::with_val(prec, c);
+        }
+    }
+    for row in 0..m {
+        for (k, c) in q.iter().enumerate() {
+            s[(n + row, row + k)] = Float::with_val(prec, c);
+        }
+    }
+    s
+}
+
+/// Res(p, q) = det of the Sylvester matrix, computed by pivoted LU at
+/// precision `prec`; zero exactly when p and q share a root
+pub fn resultant(p: &[Float], q: &[Float], prec: u32) -> Float {
+    sylvester_matrix(p, q, prec).lu_decompose_pivot().det()
+}
+
+/// Disc(p) = (-1)^(m(m-1)/2) Res(p, p') / aₘ for p of degree m >= 1; zero
+/// exactly when p has a repeated root
+pub fn discriminant(p: &[Float], prec: u32) -> Float {
+    let m = p.len() - 1;
+    assert!(m >= 1, "Discriminant needs degree at least one");
+    if m == 1 {
+        return Float::with_val(prec, 1);
+    }
+    let derivative: Vec<Float> = p[..m]
+        .iter()
+        .enumerate()
+        .map(|(k, c)| Float::with_val(prec, c * (m - k) as u32))
+        .collect();
+    let disc = resultant(p, &derivative, prec) / &p[0];
+    if (m * (m - 1) / 2) % 2 == 1 {
+        -disc
+    } else {
+        disc
+    }
+}
+
+#[test]
+fn test_resultant_and_discriminant() {
+    let prec = 128;
+    let poly = |c: &[i32]| -> Vec<Float> { c.iter().map(|&v| Float::with_val(prec, v)).collect() };
+    // (x - 1)(x - 2) against x - 3: Res = (1 - 3)(2 - 3) = 2
+    let p = poly(&[1, -3, 2]);
+    assert_eq!(resultant(&p, &poly(&[1, -3]), prec), 2);
+    // Shared root x = 2
+    assert!(resultant(&p, &poly(&[1, -2]), prec).abs() < 1e-35);
+
+    // b² - 4ac and, for x³ + ax + b, -4a³ - 27b²; LU pivots divide, so
+    // the cubic cases are exact only up to rounding
+    let close = |x: Float, e: i32| (x - e).abs() < 1e-33;
+    assert!(close(discriminant(&p, prec), 1));
+    assert!(close(discriminant(&poly(&[1, 0, -1, 0]), prec), 4));
+    assert!(close(discriminant(&poly(&[1, 0, -3, 2]), prec), 0));
+    assert!(close(discriminant(&poly(&[2, 0, 0, 1]), prec), -27 * 4));
+}
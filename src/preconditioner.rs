@@ -0,0 +1,438 @@
+// preconditioner.rs: approximate inverses handed to the iterative solvers
+use crate::RugMat;
+use crate::cholesky::{Cholesky, NotPositiveDefinite};
+use crate::lu::{LU, SolveError};
+use crate::operator::LinearOperator;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Application of an approximate inverse M⁻¹ of the matrix a solver works on.
+///
+/// For [`crate::solvers::conjugate_gradient_preconditioned`] M should
+/// approximate AᵗA; for the right-preconditioned LSQR and GMRES it should
+/// approximate A itself.
+pub trait Preconditioner: Sync {
+    /// z = M⁻¹ r
+    fn apply(&self, r: &[Float]) -> Vec<Float>;
+    /// z = M⁻ᵗ r; the default is right for symmetric M
+    fn apply_transpose(&self, r: &[Float]) -> Vec<Float> {
+        self.apply(r)
+    }
+}
+
+/// Diagonal (Jacobi) preconditioner M = diag(d)
+#[derive(Debug, Clone)]
+pub struct Jacobi {
+    inv_diag: Vec<Float>,
+}
+
+impl Jacobi {
+    /// M = diag(aᵢᵢ) of a square matrix with a nonzero diagonal
+    pub fn new(a: &RugMat) -> Self {
+        assert!(
+            a.is_square(),
+            "Jacobi needs a square matrix, got {}",
+            a.shape()
+        );
+        let inv_diag = (0..a.nrows())
+            .map(|i| {
+                assert!(!a[(i, i)].is_zero(), "Zero diagonal entry at {}", i);
+                a[(i, i)].clone().recip()
+            })
+            .collect();
+        Jacobi { inv_diag }
+    }
+
+    /// M = diag(AᵗA) = diag(‖aⱼ‖²) without forming AᵗA, for CG on the
+    /// normal equations; all-zero columns are left unscaled
+    pub fn normal_equations(a: &RugMat) -> Self {
+        let inv_diag = a
+            .column_norms2()
+            .into_iter()
+            .map(|n2| {
+                if n2.is_zero() {
+                    Float::with_val(n2.prec(), 1)
+                } else {
+                    n2.recip()
+                }
+            })
+            .collect();
+        Jacobi { inv_diag }
+    }
+}
+
+impl Preconditioner for Jacobi {
+    fn apply(&self, r: &[Float]) -> Vec<Float> {
+        assert_eq!(r.len(), self.inv_diag.len());
+        r.iter()
+            .zip(&self.inv_diag)
+            .map(|(ri, di)| (ri * di).complete(ri.prec()))
+            .collect()
+    }
+}
+
+/// Symmetric successive over-relaxation,
+/// M = (D + ωL) D⁻¹ (D + ωU) / (ω(2 - ω)) for A = L + D + U.
+///
+/// One application costs a forward and a backward triangular sweep over A.
+/// With ω = 1 this is symmetric Gauss-Seidel.
+#[derive(Debug, Clone)]
+pub struct Ssor {
+    a: RugMat,
+    omega: Float,
+}
+
+impl Ssor {
+    pub fn new(a: &RugMat, omega: f64) -> Self {
+        assert!(
+            a.is_square(),
+            "SSOR needs a square matrix, got {}",
+            a.shape()
+        );
+        assert!(omega > 0.0 && omega < 2.0, "SSOR needs 0 < ω < 2");
+        assert!(
+            (0..a.nrows()).all(|i| !a[(i, i)].is_zero()),
+            "SSOR needs a nonzero diagonal"
+        );
+        Ssor {
+            a: a.clone(),
+            omega: Float::with_val(a.as_slice()[0].prec(), omega),
+        }
+    }
+
+    /// ω(2 - ω) (D + ωU')⁻¹ D (D + ωL')⁻¹ r where entry(i, j) reads A or Aᵗ
+    fn sweep<'s>(&'s self, r: &[Float], entry: impl Fn(usize, usize) -> &'s Float) -> Vec<Float> {
+        let n = self.a.nrows();
+        assert_eq!(r.len(), n);
+        let precision = self.omega.prec();
+        let mut y = r.to_vec();
+        for i in 0..n {
+            for j in 0..i {
+                let update = (&self.omega * entry(i, j)).complete(precision) * &y[j];
+                y[i] -= update;
+            }
+            y[i] /= &self.a[(i, i)];
+        }
+        for (i, yi) in y.iter_mut().enumerate() {
+            *yi *= &self.a[(i, i)];
+        }
+        for i in (0..n).rev() {
+            for j in (i + 1)..n {
+                let update = (&self.omega * entry(i, j)).complete(precision) * &y[j];
+                y[i] -= update;
+            }
+            y[i] /= &self.a[(i, i)];
+        }
+        let factor = (2u32 - self.omega.clone()) * &self.omega;
+        y.into_iter().map(|yi| yi * &factor).collect()
+    }
+}
+
+impl Preconditioner for Ssor {
+    fn apply(&self, r: &[Float]) -> Vec<Float> {
+        self.sweep(r, |i, j| &self.a[(i, j)])
+    }
+
+    fn apply_transpose(&self, r: &[Float]) -> Vec<Float> {
+        self.sweep(r, |i, j| &self.a[(j, i)])
+    }
+}
+
+/// Zero-fill incomplete Cholesky IC(0): A ≈ L Lᵗ with L restricted to the
+/// nonzero pattern of the lower triangle of a symmetric A.
+///
+/// Entries that are exactly zero in A stay zero in L, so for banded or
+/// otherwise sparse A the factor costs far less than a full Cholesky; on a
+/// tridiagonal A it is the exact factor.
+#[derive(Debug, Clone)]
+pub struct Ic0 {
+    chol: Cholesky,
+}
+
+impl Ic0 {
+    /// Fails like [`RugMat::cholesky`] when a pivot is not positive, which
+    /// can happen for positive definite A that is not diagonally dominant
+    pub fn new(a: &RugMat) -> Result<Self, NotPositiveDefinite> {
+        assert!(
+            a.is_square(),
+            "IC(0) needs a square matrix, got {}",
+            a.shape()
+        );
+        let n = a.nrows();
+        let precision = a.as_slice()[0].prec();
+        let mut l = RugMat::new(n, n, precision);
+        for j in 0..n {
+            for i in j..n {
+                l[(i, j)] = a[(i, j)].clone();
+            }
+        }
+
+        for k in 0..n {
+            if l[(k, k)] <= 0 {
+                return Err(NotPositiveDefinite {
+                    column: k,
+                    pivot: l[(k, k)].clone(),
+                });
+            }
+            l[(k, k)].sqrt_mut();
+            let (done, rest) = l.as_mut_slice().split_at_mut((k + 1) * n);
+            let col_k = &mut done[k * n..];
+            let (pivot, below) = col_k[k..].split_at_mut(1);
+            for lik in below.iter_mut().filter(|v| !v.is_zero()) {
+                *lik /= &pivot[0];
+            }
+            let col_k = &done[k * n..];
+            for (offset, col_j) in rest.chunks_mut(n).enumerate() {
+                let j = k + 1 + offset;
+                if col_k[j].is_zero() {
+                    continue;
+                }
+                for i in j..n {
+                    if !a[(i, j)].is_zero() && !col_k[i].is_zero() {
+                        col_j[i] -= (&col_k[i] * &col_k[j]).complete(precision);
+                    }
+                }
+            }
+        }
+        Ok(Ic0 {
+            chol: Cholesky { l },
+        })
+    }
+}
+
+impl Preconditioner for Ic0 {
+    fn apply(&self, r: &[Float]) -> Vec<Float> {
+        self.chol.solve(r)
+    }
+}
+
+/// Zero-fill incomplete LU ILU(0): A ≈ L U with unit lower L and upper U
+/// restricted to the nonzero pattern of A, without pivoting.
+///
+/// Exact for tridiagonal A; in general the dropped fill-in makes M = LU a
+/// cheap approximation whose quality depends on diagonal dominance.
+#[derive(Debug, Clone)]
+pub struct Ilu0 {
+    lu: LU,
+}
+
+impl Ilu0 {
+    /// Fails with [`SolveError::Singular`] when a pivot of U is zero
+    pub fn new(a: &RugMat) -> Result<Self, SolveError> {
+        assert!(
+            a.is_square(),
+            "ILU(0) needs a square matrix, got {}",
+            a.shape()
+        );
+        let n = a.nrows();
+        let precision = a.as_slice()[0].prec();
+        let mut lu = a.clone();
+
+        // IKJ order: row i is eliminated against the finished rows above it
+        for i in 0..n {
+            for k in 0..i {
+                if a[(i, k)].is_zero() {
+                    continue;
+                }
+                if lu[(k, k)].is_zero() {
+                    return Err(SolveError::Singular { column: k });
+                }
+                let lik = (&lu[(i, k)] / &lu[(k, k)]).complete(precision);
+                for j in (k + 1)..n {
+                    if !a[(i, j)].is_zero() {
+                        let update = (&lik * &lu[(k, j)]).complete(precision);
+                        lu[(i, j)] -= update;
+                    }
+                }
+                lu[(i, k)] = lik;
+            }
+        }
+        if let Some(column) = (0..n).find(|&k| lu[(k, k)].is_zero()) {
+            return Err(SolveError::Singular { column });
+        }
+        Ok(Ilu0 {
+            lu: LU {
+                lu,
+                perm: (0..n).collect(),
+            },
+        })
+    }
+}
+
+impl Preconditioner for Ilu0 {
+    fn apply(&self, r: &[Float]) -> Vec<Float> {
+        self.lu.solve(r)
+    }
+
+    fn apply_transpose(&self, r: &[Float]) -> Vec<Float> {
+        self.lu.solve_transpose(r)
+    }
+}
+
+/// Right preconditioned operator A M⁻¹.
+///
+/// Solve with it for y, then recover x = M⁻¹ y with
+/// [`RightPreconditioned::recover`]; the residual b - A x is unchanged.
+pub struct RightPreconditioned<'a, A: LinearOperator + ?Sized, P: Preconditioner + ?Sized> {
+    pub op: &'a A,
+    pub precond: &'a P,
+}
+
+impl<A: LinearOperator + ?Sized, P: Preconditioner + ?Sized> RightPreconditioned<'_, A, P> {
+    /// x = M⁻¹ y
+    pub fn recover(&self, y: &[Float]) -> Vec<Float> {
+        self.precond.apply(y)
+    }
+}
+
+impl<A: LinearOperator + ?Sized, P: Preconditioner + ?Sized> LinearOperator
+    for RightPreconditioned<'_, A, P>
+{
+    fn rows(&self) -> usize {
+        self.op.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.op.cols()
+    }
+
+    fn apply(&self, x: &[Float]) -> Vec<Float> {
+        self.op.apply(&self.precond.apply(x))
+    }
+
+    fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+        self.precond.apply_transpose(&self.op.apply_transpose(x))
+    }
+}
+
+#[test]
+fn test_ssor_inverts_its_own_splitting() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(
+        [[4, -1, 0], [2, 5, -1], [0, 1, 3]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let omega = 1.2;
+    let ssor = Ssor::new(&a, omega);
+
+    // Explicit M = (D + ωL) D⁻¹ (D + ωU) / (ω(2 - ω))
+    let w = Float::with_val(precision, omega);
+    let mut lower = RugMat::new(3, 3, precision);
+    let mut upper = RugMat::new(3, 3, precision);
+    let mut d_inv = RugMat::new(3, 3, precision);
+    for j in 0..3 {
+        for i in 0..3 {
+            if i > j {
+                lower[(i, j)] = (&w * &a[(i, j)]).complete(precision);
+            } else if i < j {
+                upper[(i, j)] = (&w * &a[(i, j)]).complete(precision);
+            } else {
+                lower[(i, i)] = a[(i, i)].clone();
+                upper[(i, i)] = a[(i, i)].clone();
+                d_inv[(i, i)] = a[(i, i)].clone().recip();
+            }
+        }
+    }
+    let mut m = lower.matmul(&d_inv).matmul(&upper);
+    let scale = (2u32 - w.clone()) * &w;
+    for v in m.as_mut_slice() {
+        *v /= &scale;
+    }
+
+    let r: Vec<Float> = [1, -2, 3]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let back = m.matmul_vec(&ssor.apply(&r));
+    let back_t = m.transpose().matmul_vec(&ssor.apply_transpose(&r));
+    for i in 0..3 {
+        assert!((back[i].clone() - &r[i]).abs() < 1e-35);
+        assert!((back_t[i].clone() - &r[i]).abs() < 1e-35);
+    }
+}
+
+#[test]
+fn test_incomplete_factorizations() {
+    use crate::solvers::{SolverOptions, gmres_with_info};
+
+    let precision = 128;
+    // Tridiagonal: no fill-in, so IC(0) and ILU(0) are exact
+    let n = 6;
+    let mut tri = RugMat::new(n, n, precision);
+    for i in 0..n {
+        tri[(i, i)] = Float::with_val(precision, 4);
+        if i + 1 < n {
+            tri[(i, i + 1)] = Float::with_val(precision, -1);
+            tri[(i + 1, i)] = Float::with_val(precision, -1);
+        }
+    }
+    let r: Vec<Float> = (0..n).map(|i| Float::with_val(precision, i)).collect();
+    let ic = Ic0::new(&tri).unwrap();
+    let ilu = Ilu0::new(&tri).unwrap();
+    for z in [ic.apply(&r), ilu.apply(&r), ilu.apply_transpose(&r)] {
+        for (p, q) in tri.matmul_vec(&z).iter().zip(&r) {
+            assert!((p.clone() - q).abs() < 1e-35);
+        }
+    }
+
+    // 2D Laplacian on a 5×5 grid with a varying shift (so its eigenvalues
+    // are distinct): fill-in is dropped, but the factors still cut the
+    // GMRES iteration count
+    let g = 5;
+    let mut lap = RugMat::new(g * g, g * g, precision);
+    for x in 0..g {
+        for y in 0..g {
+            let i = x * g + y;
+            lap[(i, i)] = Float::with_val(precision, 4.0 + 0.3 * (i % 4) as f64);
+            for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                if nx < g && ny < g {
+                    let j = nx * g + ny;
+                    lap[(i, j)] = Float::with_val(precision, -1);
+                    lap[(j, i)] = Float::with_val(precision, -1);
+                }
+            }
+        }
+    }
+    let ic = Ic0::new(&lap).unwrap();
+    let ilu = Ilu0::new(&lap).unwrap();
+    // Both reproduce A exactly on its own nonzero pattern
+    let llt = ic.chol.l.matmul(&ic.chol.l.transpose());
+    let mut l = RugMat::identity(g * g, precision);
+    let mut u = RugMat::new(g * g, g * g, precision);
+    for j in 0..g * g {
+        for i in 0..g * g {
+            let v = ilu.lu.lu[(i, j)].clone();
+            if i > j {
+                l[(i, j)] = v;
+            } else {
+                u[(i, j)] = v;
+            }
+        }
+    }
+    let prod = l.matmul(&u);
+    for j in 0..g * g {
+        for i in 0..g * g {
+            if !lap[(i, j)].is_zero() {
+                assert!((llt[(i, j)].clone() - &lap[(i, j)]).abs() < 1e-35);
+                assert!((prod[(i, j)].clone() - &lap[(i, j)]).abs() < 1e-35);
+            }
+        }
+    }
+
+    let b: Vec<Float> = (0..g * g)
+        .map(|i| Float::with_val(precision, (i * 7) % 11) - 5u32)
+        .collect();
+    let options = SolverOptions::new(50).rel_tol(Float::with_val(precision, 1e-25));
+    let plain = gmres_with_info(&lap, &b, 50, &options);
+    for precond in [&ic as &dyn Preconditioner, &ilu] {
+        let op = RightPreconditioned { op: &lap, precond };
+        let res = gmres_with_info(&op, &b, 50, &options);
+        assert!(res.iterations < plain.iterations);
+        let x = op.recover(&res.x);
+        for (p, q) in lap.matmul_vec(&x).iter().zip(&b) {
+            assert!((p.clone() - q).abs() < 1e-20);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// prelude.rs: glob-importable set of the commonly used types
+//! `use rugmat::prelude::*;` brings in the matrix type, the factorizations,
+//! the solver types and `rug::Float` for constructing inputs.
+//!
+//! ```
+//! use rugmat::prelude::*;
+//!
+//! let a = RugMat::identity(2, 64);
+//! let y = t!(a).mul(&[Float::with_val(64, 1), Float::with_val(64, 2)]);
+//! assert_eq!(y[1], 2);
+//! ```
+
+pub use crate::cholesky::{Cholesky, NotPositiveDefinite};
+pub use crate::cod::COD;
+pub use crate::lse::LseError;
+pub use crate::lu::{LU, SolveError};
+pub use crate::operator::{
+    BlockDiagonal, ColumnScaled, DeflatedOperator, DiagonalOperator, LinearOperator, LowRank,
+    OpProduct, OpScaled, OpShift, OpSum, OpTranspose,
+};
+pub use crate::preconditioner::{Ic0, Ilu0, Jacobi, Preconditioner, Ssor};
+pub use crate::qr::QR;
+pub use crate::rugmat::{RugMat, RugVec, SVD, Shape, ShapeError, Transpose};
+pub use crate::small::SmallRugMat;
+#[allow(deprecated)]
+pub use crate::solvers::SolveResult;
+pub use crate::solvers::{
+    HistoryFormat, IterationRecord, PrecisionEscalation, Scaling, SolveOutcome, SolverOptions,
+};
+pub use crate::svd::{MinNormSolution, SvdMethod};
+pub use crate::t;
+pub use crate::tikhonov::{Filter, FilterFactors, LambdaStrategy, Regularized};
+pub use crate::tls::{TlsError, TotalLeastSquares};
+pub use rug::Float;
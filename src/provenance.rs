@@ -0,0 +1,80 @@
+// provenance.rs: experiment descriptors that make published solver results auditable
+use crate::RugMat;
+use crate::solvers::SolveOutcome;
+use std::fmt::Debug;
+
+/// Everything needed to reproduce or audit a solve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExperimentDescriptor {
+    /// [`RugMat::content_hash`] of the system matrix
+    pub matrix_hash: blake3::Hash,
+    pub rows: usize,
+    pub cols: usize,
+    /// Precision in bits of the system matrix
+    pub precision: u32,
+    /// Debug rendering of the solver configuration used
+    pub options: String,
+    /// Size of the rayon pool the solve ran on
+    pub threads: usize,
+    pub crate_version: String,
+}
+
+impl ExperimentDescriptor {
+    /// Describe a solve of a with the given options in the current environment
+    pub fn new(a: &RugMat, options: &impl Debug) -> Self {
+        ExperimentDescriptor {
+            matrix_hash: a.content_hash(),
+            rows: a.nrows(),
+            cols: a.ncols(),
+            precision: a.as_slice()[0].prec(),
+            options: format!("{:?}", options),
+            threads: rayon::current_num_threads(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// A solver result together with its experiment descriptor
+#[derive(Debug, Clone)]
+pub struct ResultBundle {
+    pub result: SolveOutcome,
+    pub descriptor: ExperimentDescriptor,
+}
+
+impl ResultBundle {
+    pub fn new(result: SolveOutcome, a: &RugMat, options: &impl Debug) -> Self {
+        ResultBundle {
+            result,
+            descriptor: ExperimentDescriptor::new(a, options),
+        }
+    }
+
+    /// Check that a is bit-for-bit the matrix this result was computed from
+    pub fn verify_against(&self, a: &RugMat) -> bool {
+        a.nrows() == self.descriptor.rows
+            && a.ncols() == self.descriptor.cols
+            && a.content_hash() == self.descriptor.matrix_hash
+    }
+}
+
+#[test]
+fn test_result_bundle_verifies_matrix() {
+    use crate::solvers::{SolverOptions, lsqr_with_info};
+    use rug::Float;
+
+    let precision = 128;
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0], precision);
+    let b = vec![Float::with_val(precision, 1); 2];
+    let options = SolverOptions::new(10);
+    let bundle = ResultBundle::new(lsqr_with_info(&a, &b, &options), &a, &options);
+    assert!(bundle.verify_against(&a));
+    assert_eq!(bundle.descriptor.precision, precision);
+    assert!(bundle.descriptor.options.contains("max_iters: 10"));
+
+    // Same values at a different precision are a different experiment
+    let widened = RugMat::diagonal_from_f64(&[1.0, 2.0], 256);
+    assert!(!bundle.verify_against(&widened));
+    let mut perturbed = a.clone();
+    perturbed[(1, 0)] = Float::with_val(precision, 1e-40);
+    assert!(!bundle.verify_against(&perturbed));
+}
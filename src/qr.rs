@@ -0,0 +1,411 @@
+// qr.rs: Householder QR factorization and projections built on it
+use crate::RugMat;
+use rayon::prelude::*;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Householder QR factorization A = Q R of an m×n matrix with m >= n.
+///
+/// Stored compactly as in LAPACK: R occupies the upper triangle of `qr`, the
+/// Householder vectors (with implicit unit leading entry) are stored below
+/// the diagonal, and Hₖ = I - τₖ vₖ vₖᵗ.
+#[derive(Debug, Clone)]
+pub struct QR {
+    pub qr: RugMat,
+    pub tau: Vec<Float>,
+}
+
+impl RugMat {
+    /// Householder QR factorization, parallel over trailing columns
+    pub fn qr(&self) -> QR {
+        assert!(
+            self.nrows() >= self.ncols(),
+            "QR requires rows >= cols (got {}×{})",
+            self.nrows(),
+            self.ncols()
+        );
+        let precision = self.as_slice()[0].prec();
+        let m = self.nrows();
+        let n = self.ncols();
+        let mut a = self.clone();
+        let mut tau = Vec::with_capacity(n);
+
+        for k in 0..n {
+            let (head, trailing) = a.as_mut_slice().split_at_mut((k + 1) * m);
+            let col = &mut head[k * m..];
+            let t = householder_in_place(&mut col[k..], precision);
+
+            if !t.is_zero() {
+                let v = &col[k..];
+                trailing.par_chunks_mut(m).for_each(|target| {
+                    apply_reflector(v, &t, &mut target[k..]);
+                });
+            }
+            tau.push(t);
+        }
+
+        QR { qr: a, tau }
+    }
+}
+
+/// Turn x into [β, v₁..] in place (LAPACK dlarfg) and return τ,
+/// so that (I - τ v vᵗ) x = β e₁ with v = [1, v₁..].
+pub(crate) fn householder_in_place(x: &mut [Float], precision: u32) -> Float {
+    let mut tail_norm2 = Float::with_val(precision, 0);
+    for xi in &x[1..] {
+        tail_norm2 += xi * xi;
+    }
+    if tail_norm2.is_zero() {
+        return Float::with_val(precision, 0);
+    }
+
+    let alpha = x[0].clone();
+    let mut beta = (&alpha * &alpha).complete(precision) + &tail_norm2;
+    beta.sqrt_mut();
+    if alpha.is_sign_positive() {
+        beta = -beta;
+    }
+    let tau = (&beta - &alpha).complete(precision) / &beta;
+    let scale = (&alpha - &beta).complete(precision);
+    for xi in &mut x[1..] {
+        *xi /= &scale;
+    }
+    x[0] = beta;
+    tau
+}
+
+/// y ← (I - τ v vᵗ) y where v[0] is taken as 1
+pub(crate) fn apply_reflector(v: &[Float], tau: &Float, y: &mut [Float]) {
+    let mut w = y[0].clone();
+    for (vi, yi) in v[1..].iter().zip(&y[1..]) {
+        w += vi * yi;
+    }
+    w *= tau;
+    y[0] -= &w;
+    for (vi, yi) in v[1..].iter().zip(&mut y[1..]) {
+        *yi -= (vi * &w).complete(yi.prec());
+    }
+}
+
+/// Solve R x = c using the upper triangle of the leading n×n block of r
+pub(crate) fn back_substitute(r: &RugMat, c: &[Float]) -> Vec<Float> {
+    let n = c.len();
+    let mut x = c.to_vec();
+    for i in (0..n).rev() {
+        for j in (i + 1)..n {
+            let update = (&r[(i, j)] * &x[j]).complete(x[i].prec());
+            x[i] -= update;
+        }
+        x[i] /= &r[(i, i)];
+    }
+    x
+}
+
+impl QR {
+    /// Overwrite x (length m) with Qᵗx
+    fn qt_in_place(&self, x: &mut [Float]) {
+        let m = self.qr.nrows();
+        for (k, tau) in self.tau.iter().enumerate() {
+            let v = &self.qr.as_slice()[k * m + k..(k + 1) * m];
+            apply_reflector(v, tau, &mut x[k..]);
+        }
+    }
+
+    /// Overwrite x (length m) with Qx
+    fn q_in_place(&self, x: &mut [Float]) {
+        let m = self.qr.nrows();
+        for (k, tau) in self.tau.iter().enumerate().rev() {
+            let v = &self.qr.as_slice()[k * m + k..(k + 1) * m];
+            apply_reflector(v, tau, &mut x[k..]);
+        }
+    }
+
+    /// Qᵗb for a vector of length m, applied through the stored reflectors
+    pub fn apply_qt(&self, b: &[Float]) -> Vec<Float> {
+        assert_eq!(b.len(), self.qr.nrows());
+        let mut x = b.to_vec();
+        self.qt_in_place(&mut x);
+        x
+    }
+
+    /// Qy for y of length m, or Q₁y when y has length n
+    pub fn apply_q(&self, y: &[Float]) -> Vec<Float> {
+        let m = self.qr.nrows();
+        assert!(y.len() == m || y.len() == self.qr.ncols());
+        let mut x = y.to_vec();
+        x.resize(m, Float::with_val(self.qr.as_slice()[0].prec(), 0));
+        self.q_in_place(&mut x);
+        x
+    }
+
+    /// QᵗB for an m×k block of right-hand sides, parallel over columns
+    pub fn apply_qt_mat(&self, b: &RugMat) -> RugMat {
+        assert_eq!(b.nrows(), self.qr.nrows());
+        let mut x = b.clone();
+        x.as_mut_slice()
+            .par_chunks_mut(b.nrows())
+            .for_each(|col| self.qt_in_place(col));
+        x
+    }
+
+    /// QY for an m×k block, parallel over columns
+    pub fn apply_q_mat(&self, y: &RugMat) -> RugMat {
+        assert_eq!(y.nrows(), self.qr.nrows());
+        let mut x = y.clone();
+        x.as_mut_slice()
+            .par_chunks_mut(y.nrows())
+            .for_each(|col| self.q_in_place(col));
+        x
+    }
+
+    /// Least-squares solution of min ‖Ax - b‖ for full column rank A:
+    /// x = R⁻¹ (Qᵗb)[..n], without forming Q
+    pub fn solve_least_squares(&self, b: &[Float]) -> Vec<Float> {
+        let c = self.apply_qt(b);
+        back_substitute(&self.qr, &c[..self.qr.ncols()])
+    }
+
+    /// (AᵗA)⁻¹ = R⁻¹R⁻ᵗ, formed from columns of R⁻¹ by back substitution
+    /// rather than by inverting the normal matrix, whose condition number is
+    /// the square of A's
+    pub fn normal_inverse(&self) -> RugMat {
+        let n = self.qr.ncols();
+        let precision = self.qr.as_slice()[0].prec();
+        let r_inv: Vec<Vec<Float>> = (0..n)
+            .into_par_iter()
+            .map(|j| {
+                let mut e = vec![Float::with_val(precision, 0); n];
+                e[j] = Float::with_val(precision, 1);
+                back_substitute(&self.qr, &e)
+            })
+            .collect();
+        let mut g = RugMat::new(n, n, precision);
+        for j in 0..n {
+            for i in 0..=j {
+                // (R⁻¹R⁻ᵗ)ᵢⱼ = Σₖ (R⁻¹)ᵢₖ (R⁻¹)ⱼₖ, columns k of R⁻¹ in r_inv
+                let mut acc = Float::with_val(precision, 0);
+                for col in &r_inv[j..] {
+                    acc += &col[i] * &col[j];
+                }
+                g[(j, i)] = acc.clone();
+                g[(i, j)] = acc;
+            }
+        }
+        g
+    }
+
+    /// The n×n upper triangular factor R
+    pub fn r(&self) -> RugMat {
+        let n = self.qr.ncols();
+        let precision = self.qr.as_slice()[0].prec();
+        let mut r = RugMat::new(n, n, precision);
+        for j in 0..n {
+            for i in 0..=j {
+                r[(i, j)] = self.qr[(i, j)].clone();
+            }
+        }
+        r
+    }
+
+    /// The m×n thin factor Q₁ with orthonormal columns, A = Q₁ R
+    pub fn q_thin(&self) -> RugMat {
+        let (m, n) = (self.qr.nrows(), self.qr.ncols());
+        let precision = self.qr.as_slice()[0].prec();
+        let mut q = RugMat::new(m, n, precision);
+        q.as_mut_slice()
+            .par_chunks_mut(m)
+            .enumerate()
+            .for_each(|(j, col)| {
+                col[j] = Float::with_val(precision, 1);
+                self.q_in_place(col);
+            });
+        q
+    }
+}
+
+/// Result of [`RugMat::lstsq_covariance`]
+#[derive(Debug, Clone)]
+pub struct LstsqCovariance {
+    /// σ² (AᵗA)⁻¹
+    pub covariance: RugMat,
+    /// Standard error of each coefficient, the square root of the diagonal
+    pub std_errors: Vec<Float>,
+}
+
+impl RugMat {
+    /// Covariance σ² (AᵗA)⁻¹ of the least-squares coefficients for noise of
+    /// variance `residual_variance`, usually ‖Ax - b‖² / (m - n).
+    ///
+    /// Built from the QR factor R, so it stays accurate where forming and
+    /// inverting AᵗA would lose twice the digits. A must have full column rank.
+    pub fn lstsq_covariance(&self, residual_variance: &Float) -> LstsqCovariance {
+        let mut covariance = self.qr().normal_inverse();
+        for v in covariance.as_mut_slice() {
+            *v *= residual_variance;
+        }
+        let std_errors = (0..self.ncols())
+            .map(|i| covariance[(i, i)].clone().sqrt())
+            .collect();
+        LstsqCovariance {
+            covariance,
+            std_errors,
+        }
+    }
+}
+
+impl RugMat {
+    /// Orthogonal projection of x onto the column space of self, P x = Q₁Q₁ᵗx.
+    ///
+    /// Assumes full column rank; Q is applied through its reflectors, so only
+    /// the factorization itself is stored.
+    pub fn project_onto_columns(&self, x: &[Float]) -> Vec<Float> {
+        self.qr().project(x)
+    }
+
+    /// Projection of x onto the orthogonal complement of the column space,
+    /// (I - Q₁Q₁ᵗ) x
+    pub fn project_onto_complement(&self, x: &[Float]) -> Vec<Float> {
+        let p = self.project_onto_columns(x);
+        x.iter()
+            .zip(&p)
+            .map(|(xi, pi)| (xi - pi).complete(xi.prec()))
+            .collect()
+    }
+
+    /// Explicit m×m orthogonal projector Q₁Q₁ᵗ onto the column space of self
+    pub fn column_space_projector(&self) -> RugMat {
+        let q = self.qr().q_thin();
+        q.matmul(&q.transpose())
+    }
+}
+
+impl QR {
+    /// Q₁Q₁ᵗ x: rotate into the Q basis, drop the complement, rotate back
+    fn project(&self, x: &[Float]) -> Vec<Float> {
+        assert_eq!(x.len(), self.qr.nrows());
+        let mut y = x.to_vec();
+        self.qt_in_place(&mut y);
+        for yi in &mut y[self.qr.ncols()..] {
+            *yi = Float::with_val(yi.prec(), 0);
+        }
+        self.q_in_place(&mut y);
+        y
+    }
+}
+
+#[test]
+fn test_qr_reconstructs_and_is_orthonormal() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(vec![
+        vec![
+            Float::with_val(precision, 12),
+            Float::with_val(precision, -51),
+        ],
+        vec![
+            Float::with_val(precision, 6),
+            Float::with_val(precision, 167),
+        ],
+        vec![
+            Float::with_val(precision, -4),
+            Float::with_val(precision, 24),
+        ],
+    ]);
+    let qr = a.qr();
+    let q = qr.q_thin();
+    let r = qr.r();
+    assert!(r.is_upper_triangular());
+
+    let rebuilt = q.matmul(&r);
+    let qtq = q.transpose().matmul(&q);
+    let id = RugMat::identity(2, precision);
+    for j in 0..2 {
+        for i in 0..3 {
+            assert!((rebuilt[(i, j)].clone() - &a[(i, j)]).abs() < 1e-30);
+        }
+        for i in 0..2 {
+            assert!((qtq[(i, j)].clone() - &id[(i, j)]).abs() < 1e-30);
+        }
+    }
+}
+
+#[test]
+fn test_projection_onto_columns_and_complement() {
+    let precision = 128;
+    // Column space is the xy-plane
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 0), Float::with_val(precision, 2)],
+        vec![Float::with_val(precision, 0), Float::with_val(precision, 0)],
+    ]);
+    let x = vec![
+        Float::with_val(precision, 3),
+        Float::with_val(precision, -1),
+        Float::with_val(precision, 5),
+    ];
+    let p = a.project_onto_columns(&x);
+    let c = a.project_onto_complement(&x);
+    let expected_p = [3.0, -1.0, 0.0];
+    let expected_c = [0.0, 0.0, 5.0];
+    for i in 0..3 {
+        assert!((p[i].to_f64() - expected_p[i]).abs() < 1e-30);
+        assert!((c[i].to_f64() - expected_c[i]).abs() < 1e-30);
+    }
+
+    let proj = a.column_space_projector();
+    let px = proj.matmul_vec(&x);
+    for i in 0..3 {
+        assert!((px[i].clone() - &p[i]).abs() < 1e-30);
+    }
+}
+
+#[test]
+fn test_qr_least_squares_without_forming_q() {
+    let precision = 128;
+    // Same line fit as the LSQR test: c0 = 5/6, c1 = 3/2
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 0)],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 2)],
+    ]);
+    let b = vec![
+        Float::with_val(precision, 1),
+        Float::with_val(precision, 2),
+        Float::with_val(precision, 4),
+    ];
+    let qr = a.qr();
+    let x = qr.solve_least_squares(&b);
+    assert!((x[0].to_f64() - 5.0 / 6.0).abs() < 1e-30);
+    assert!((x[1].to_f64() - 1.5).abs() < 1e-30);
+
+    // Q is orthogonal, so Q(Qᵗb) = b
+    let back = qr.apply_q(&qr.apply_qt(&b));
+    for (bi, ci) in b.iter().zip(&back) {
+        assert!((bi.clone() - ci).abs() < 1e-30);
+    }
+}
+
+#[test]
+fn test_lstsq_covariance_of_line_fit() {
+    let precision = 192;
+    // Same line fit: AᵗA = [[3, 3], [3, 5]] and the residuals (1, -2, 1)/6
+    // give σ² = 1/6, so the covariance is [[5, -3], [-3, 3]] / 36
+    let a = RugMat::from_vecvec(
+        [0, 1, 2]
+            .iter()
+            .map(|&t| vec![Float::with_val(precision, 1), Float::with_val(precision, t)])
+            .collect(),
+    );
+    let sigma2 = Float::with_val(precision, 1) / 6u32;
+    let cov = a.lstsq_covariance(&sigma2);
+    let expected = [[5, -3], [-3, 3]];
+    for (j, col) in expected.iter().enumerate() {
+        for (i, &v) in col.iter().enumerate() {
+            let e = Float::with_val(precision, v) / 36u32;
+            assert!((cov.covariance[(i, j)].clone() - e).abs() < 1e-55);
+        }
+    }
+    let se0 = Float::with_val(precision, 5).sqrt() / 6u32;
+    let se1 = Float::with_val(precision, 3).sqrt() / 6u32;
+    assert!((cov.std_errors[0].clone() - se0).abs() < 1e-55);
+    assert!((cov.std_errors[1].clone() - se1).abs() < 1e-55);
+}
@@ -1,20 +1,25 @@
-use faer::prelude::*;
+use crate::solvers;
 use rayon::prelude::*;
 use rug::Assign;
 use rug::Float; // or faer::Mat if needed directly
 use rug::ops::{CompleteRound, Pow};
+use std::fmt;
 use std::ops::{Index, IndexMut};
 
+#[derive(Debug, Clone)]
 pub enum PseudoInverseAlgorithm {
     GradientDescent { alpha: Float },
     LSQR,
     ConjugateGradient,
 }
 
+/// Dense vector of MPFR floats, as taken and returned by the solvers
+pub type RugVec = Vec<Float>;
+
 #[macro_export]
 macro_rules! t {
     ($mat:expr) => {
-        Transpose::new(&$mat)
+        $crate::rugmat::Transpose::new(&$mat)
     };
 }
 #[derive(Debug)]
@@ -37,12 +42,14 @@ impl<'a> Transpose<'a> {
         self.mat.matmul_transpose_vec(x)
     }
 
+    #[allow(clippy::misnamed_getters)]
     pub fn rows(&self) -> usize {
-        self.mat.cols
+        self.mat.ncols()
     }
 
+    #[allow(clippy::misnamed_getters)]
     pub fn cols(&self) -> usize {
-        self.mat.rows
+        self.mat.nrows()
     }
 
     pub fn get(&self, i: usize, j: usize) -> &Float {
@@ -50,14 +57,169 @@ impl<'a> Transpose<'a> {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Why [`RugMat::try_from_vecvec`] rejected its input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShapeError {
+    /// No rows, or a first row without entries
+    Empty,
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    MixedPrecision {
+        row: usize,
+        col: usize,
+        expected: u32,
+        found: u32,
+    },
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::Empty => write!(f, "matrix has no entries"),
+            ShapeError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} entries, expected {} like the first row",
+                row, found, expected
+            ),
+            ShapeError::MixedPrecision {
+                row,
+                col,
+                expected,
+                found,
+            } => write!(
+                f,
+                "entry ({}, {}) has precision {}, expected {} like the first entry",
+                row, col, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Row and column counts of a matrix, as returned by [`RugMat::shape`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shape {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Shape {
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// A single row or a single column
+    pub fn is_vector(&self) -> bool {
+        self.rows == 1 || self.cols == 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0 || self.cols == 0
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}×{}", self.rows, self.cols)
+    }
+}
+
+/// Dense matrix of MPFR floats.
+///
+/// Layout guarantee: the slice accessors ([`RugMat::as_slice`],
+/// [`RugMat::col_as_slice`], [`RugMat::raw_parts`] and their `mut`
+/// versions) hand out entries column-major and contiguous, entry (i, j) at
+/// index `j * nrows() + i`, so column j is `[j * nrows(), (j + 1) * nrows())`.
+/// This is part of the public API and will not change.
+///
+/// The public fields are deprecated and will become private, so that the
+/// storage behind the accessors can change (strides, copy-on-write,
+/// disk-backed buffers). Migrate `m.rows`/`m.cols` to [`RugMat::nrows`] and
+/// [`RugMat::ncols`], `m.data` to the slice accessors or
+/// [`RugMat::into_data`], and struct literals to [`RugMat::from_col_major`].
+#[derive(Debug, Clone, Default)]
 pub struct RugMat {
+    #[deprecated(
+        note = "use as_slice(), col_as_slice() or raw_parts(); the field will become private"
+    )]
     pub data: Vec<Float>,
+    #[deprecated(note = "use nrows(); the field will become private")]
     pub rows: usize,
+    #[deprecated(note = "use ncols(); the field will become private")]
     pub cols: usize,
 }
 
+// The storage primitives below are the only code that touches the
+// deprecated fields directly
+#[allow(deprecated)]
 impl RugMat {
+    /// Number of rows
+    pub fn nrows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn ncols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn shape(&self) -> Shape {
+        Shape {
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.shape().is_square()
+    }
+
+    /// True for a single row or a single column
+    pub fn is_vector(&self) -> bool {
+        self.shape().is_vector()
+    }
+
+    /// Number of entries, nrows() · ncols()
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// True when the matrix has no entries
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// All entries, column-major
+    pub fn as_slice(&self) -> &[Float] {
+        &self.data
+    }
+
+    /// All entries, column-major, for in-place updates
+    pub fn as_mut_slice(&mut self) -> &mut [Float] {
+        &mut self.data
+    }
+
+    /// Take ownership of column-major entries as an nrows×ncols matrix
+    pub fn from_col_major(rows: usize, cols: usize, data: Vec<Float>) -> Self {
+        assert_eq!(
+            data.len(),
+            rows * cols,
+            "{} entries cannot form a {}×{} matrix",
+            data.len(),
+            rows,
+            cols
+        );
+        Self { data, rows, cols }
+    }
+
     pub fn new(rows: usize, cols: usize, precision: u32) -> Self {
         let data = vec![Float::with_val(precision, 0); rows * cols];
         Self { data, rows, cols }
@@ -68,13 +230,37 @@ impl RugMat {
         let cols = vv[0].len();
         let mut data = Vec::with_capacity(rows * cols);
         for j in 0..cols {
-            for i in 0..rows {
-                data.push(vv[i][j].clone());
+            for row in &vv {
+                data.push(row[j].clone());
             }
         }
         Self { data, rows, cols }
     }
 
+    /// Column j as a contiguous slice of `rows` entries
+    pub fn col_as_slice(&self, j: usize) -> &[Float] {
+        assert!(j < self.cols, "Column {} out of range", j);
+        &self.data[j * self.rows..(j + 1) * self.rows]
+    }
+
+    /// Column j as a mutable contiguous slice, e.g. for a custom MPFR kernel
+    pub fn col_as_mut_slice(&mut self, j: usize) -> &mut [Float] {
+        assert!(j < self.cols, "Column {} out of range", j);
+        &mut self.data[j * self.rows..(j + 1) * self.rows]
+    }
+
+    /// (entries, rows, cols) with the column-major layout documented on
+    /// [`RugMat`]; each `Float` wraps an `mpfr_t` reachable through
+    /// `Float::as_raw` for FFI
+    pub fn raw_parts(&self) -> (&[Float], usize, usize) {
+        (&self.data, self.rows, self.cols)
+    }
+
+    /// Mutable counterpart of [`RugMat::raw_parts`]; the shape stays fixed
+    pub fn raw_parts_mut(&mut self) -> (&mut [Float], usize, usize) {
+        (&mut self.data, self.rows, self.cols)
+    }
+
     pub fn get(&self, row: usize, col: usize) -> &Float {
         &self.data[col * self.rows + row]
     }
@@ -122,6 +308,7 @@ impl RugMat {
     */
 }
 
+#[allow(deprecated)]
 impl Index<(usize, usize)> for RugMat {
     type Output = Float;
 
@@ -131,6 +318,7 @@ impl Index<(usize, usize)> for RugMat {
     }
 }
 
+#[allow(deprecated)]
 impl IndexMut<(usize, usize)> for RugMat {
     fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
         let (i, j) = index;
@@ -170,14 +358,13 @@ pub fn vec_sub_in_place(a: &[Float], b: &[Float], out: &mut [Float]) {
 
 impl RugMat {
     pub fn matmul(&self, other: &RugMat) -> RugMat {
-        assert_eq!(self.cols, other.rows);
-        let precision = self.data[0].prec();
-        let m = self.rows;
-        let k = self.cols;
-        let n = other.cols;
+        assert_eq!(self.ncols(), other.nrows());
+        let precision = self.as_slice()[0].prec();
+        let m = self.nrows();
+        let k = self.ncols();
+        let n = other.ncols();
 
         let block_size = 32;
-        let mut result = RugMat::new(m, n, precision);
         let mut result_data = vec![Float::with_val(precision, 0); m * n];
 
         result_data
@@ -195,25 +382,33 @@ impl RugMat {
                         }
                     }
                 }
-                for i in 0..m {
-                    col[i] = acc[i].clone();
-                }
+                col.clone_from_slice(&acc);
             });
 
-        result.data = result_data;
-        result
+        RugMat::from_col_major(m, n, result_data)
+    }
+
+    /// Explicit transpose of the matrix (use `t!` for a lazy view)
+    pub fn transpose(&self) -> RugMat {
+        let mut data = Vec::with_capacity(self.len());
+        for i in 0..self.nrows() {
+            for j in 0..self.ncols() {
+                data.push(self[(i, j)].clone());
+            }
+        }
+        RugMat::from_col_major(self.ncols(), self.nrows(), data)
     }
 
     pub fn matmul_vec(&self, v: &[Float]) -> Vec<Float> {
-        assert_eq!(self.cols, v.len());
-        let precision = self.data[0].prec();
-        let m = self.rows;
+        assert_eq!(self.ncols(), v.len());
+        let precision = self.as_slice()[0].prec();
+        let m = self.nrows();
 
         (0..m)
             .into_par_iter()
             .map(|i| {
                 let mut sum = Float::with_val(precision, 0);
-                for j in 0..self.cols {
+                for j in 0..self.ncols() {
                     sum += &self[(i, j)] * &v[j];
                 }
                 sum
@@ -222,15 +417,15 @@ impl RugMat {
     }
 
     pub fn matmul_transpose_vec(&self, v: &[Float]) -> Vec<Float> {
-        assert_eq!(self.rows, v.len());
-        let precision = self.data[0].prec();
-        let n = self.cols;
+        assert_eq!(self.nrows(), v.len());
+        let precision = self.as_slice()[0].prec();
+        let n = self.ncols();
 
         (0..n)
             .into_par_iter()
             .map(|j| {
                 let mut sum = Float::with_val(precision, 0);
-                for i in 0..self.rows {
+                for i in 0..self.nrows() {
                     sum += &self[(i, j)] * &v[i];
                 }
                 sum
@@ -238,10 +433,36 @@ impl RugMat {
             .collect()
     }
 
+    /// out = A v, reusing the Floats already in out
+    pub fn matmul_vec_into(&self, v: &[Float], out: &mut [Float]) {
+        assert_eq!(self.ncols(), v.len());
+        assert_eq!(self.nrows(), out.len());
+        out.par_iter_mut().enumerate().for_each(|(i, sum)| {
+            sum.assign(0);
+            for (j, vj) in v.iter().enumerate() {
+                *sum += &self[(i, j)] * vj;
+            }
+        });
+    }
+
+    /// out = Aᵗ v, reusing the Floats already in out
+    pub fn matmul_transpose_vec_into(&self, v: &[Float], out: &mut [Float]) {
+        assert_eq!(self.nrows(), v.len());
+        assert_eq!(self.ncols(), out.len());
+        out.par_iter_mut()
+            .zip(self.as_slice().par_chunks(self.nrows()))
+            .for_each(|(sum, col)| {
+                sum.assign(0);
+                for (a, vi) in col.iter().zip(v) {
+                    *sum += a * vi;
+                }
+            });
+    }
+
     pub fn dot_columns(&self, i: usize, j: usize) -> Float {
         let precision = self[(0, i)].prec().max(self[(0, j)].prec());
         let mut acc = Float::with_val(precision, 0);
-        for row in 0..self.rows {
+        for row in 0..self.nrows() {
             acc += &self[(row, i)] * &self[(row, j)];
         }
         acc
@@ -250,18 +471,49 @@ impl RugMat {
     pub fn column_norm2(&self, j: usize) -> Float {
         let precision = self[(0, j)].prec();
         let mut acc = Float::with_val(precision, 0);
-        for row in 0..self.rows {
+        for row in 0..self.nrows() {
             let x = &self[(row, j)];
             acc += x * x;
         }
         acc
     }
 
+    /// Squared 2-norms of all columns, as `column_norm2` for each j
+    pub fn column_norms2(&self) -> Vec<Float> {
+        let precision = self.as_slice()[0].prec();
+        self.as_slice()
+            .par_chunks(self.nrows())
+            .map(|col| {
+                let mut acc = Float::with_val(precision, 0);
+                for x in col {
+                    acc += x * x;
+                }
+                acc
+            })
+            .collect()
+    }
+
+    /// Squared 2-norms of all rows
+    pub fn row_norms2(&self) -> Vec<Float> {
+        let precision = self.as_slice()[0].prec();
+        (0..self.nrows())
+            .into_par_iter()
+            .map(|i| {
+                let mut acc = Float::with_val(precision, 0);
+                for j in 0..self.ncols() {
+                    let x = &self[(i, j)];
+                    acc += x * x;
+                }
+                acc
+            })
+            .collect()
+    }
+
     /// Infinity norm (max absolute row sum), optimized for column-major layout with Rayon
     pub fn norm_inf(&self) -> Float {
-        let precision = self.data[0].prec();
-        let rows = self.rows;
-        let cols = self.cols;
+        let precision = self.as_slice()[0].prec();
+        let rows = self.nrows();
+        let cols = self.ncols();
 
         // Parallel over columns, accumulate per-row values
         let row_sums = (0..cols)
@@ -295,12 +547,6 @@ impl RugMat {
     }
 }
 
-use crate::float_serializer::{read_float, write_float};
-use rayon::prelude::*;
-use rug::float::Round;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
-
 impl RugMat {
     /// Compute 2-norm of a vector
     pub fn norm2_vec(v: &[Float]) -> Float {
@@ -321,261 +567,117 @@ impl RugMat {
     ) -> Vec<Float> {
         match alg {
             PseudoInverseAlgorithm::GradientDescent { alpha } => {
-                let precision = b[0].prec();
-                let mut x = vec![Float::with_val(precision, 0); self.cols];
-
-                for _ in 0..iters {
-                    let Ax = self.matmul_vec(&x);
-                    let mut r = vec![Float::with_val(precision, 0); self.rows];
-                    for i in 0..self.rows {
-                        r[i] = Ax[i].clone() - &b[i];
-                    }
-                    let At_r = t!(self).mul(&r);
-                    for j in 0..self.cols {
-                        x[j] -= &alpha * &At_r[j];
-                    }
-                }
-                x
+                solvers::gradient_descent(self, b, &alpha, iters)
             }
             PseudoInverseAlgorithm::LSQR => self.lsqr(b, iters),
             PseudoInverseAlgorithm::ConjugateGradient => self.conjugate_gradient(b, iters),
         }
     }
 
+    /// CG on the normal equations AᵗA x = Aᵗb, see [`solvers::conjugate_gradient`]
     pub fn conjugate_gradient(&self, b: &[Float], max_iters: usize) -> Vec<Float> {
-        let precision = b[0].prec();
-
-        // Normal equations: AᵗA x = Aᵗb
-        let At_b = t!(self).mul(b);
-        let mut x = vec![Float::with_val(precision, 0); self.cols];
-
-        let Ax = self.matmul_vec(&x);
-        let At_Ax = t!(self).mul(&Ax);
-
-        let mut r: Vec<Float> = At_b
-            .iter()
-            .zip(&At_Ax)
-            .map(|(b, ax)| (b - ax).complete(b.prec()))
-            .collect();
-        let mut p = r.clone();
-        let mut rs_old = r
-            .iter()
-            .map(|v| (v * v).complete(v.prec()))
-            .reduce(|a, b| (a + b))
-            .unwrap();
-
-        let initial_norm = rs_old.clone().sqrt();
-        let epsilon = Float::with_val(precision, 1e-30);
-        let mut restart_regularized = false;
-
-        for iter in 0..max_iters {
-            let Ap = {
-                let Av = self.matmul_vec(&p);
-                t!(self).mul(&Av)
-            };
-            let denom = p
-                .iter()
-                .zip(&Ap)
-                .map(|(a, b)| (a * b).complete(a.prec()))
-                .reduce(|a, b| a + b)
-                .unwrap();
-
-            if denom == 0 {
-                // Likely rank deficiency
-                if !restart_regularized {
-                    eprintln!(
-                        "[CG] Detected possible rank deficiency at iter {} — restarting with Tikhonov regularization",
-                        iter
-                    );
-                    return self.cg_regularized(b, max_iters, Float::with_val(precision, 1e-10));
-                } else {
-                    break;
-                }
-            }
-
-            let alpha = (&rs_old / &denom).complete(rs_old.prec());
-
-            for i in 0..x.len() {
-                x[i] += &alpha * &p[i];
-                r[i] -= &alpha * &Ap[i];
-            }
-
-            let rs_new = r
-                .iter()
-                .map(|v| (v * v).complete(v.prec()))
-                .reduce(|a, b| a + b)
-                .unwrap();
-            if rs_new < (&epsilon * &initial_norm).complete(epsilon.prec()) {
-                break;
-            }
-
-            let beta = (&rs_new / &rs_old).complete(rs_new.prec());
-            for i in 0..p.len() {
-                p[i] = (&r[i] + (&beta * &p[i]).complete(beta.prec()));
-            }
-            rs_old = rs_new;
-        }
-        x
+        solvers::conjugate_gradient(self, b, max_iters)
     }
 
     /// Regularized CG: Solve (AᵗA + λI)x = Aᵗb
     pub fn cg_regularized(&self, b: &[Float], max_iters: usize, lambda: Float) -> Vec<Float> {
-        let precision = b[0].prec();
-
-        let At_b = t!(self).mul(b);
-        let mut x = vec![Float::with_val(precision, 0); self.cols];
-
-        let Ax = self.matmul_vec(&x);
-        let At_Ax = t!(self).mul(&Ax);
-
-        let mut r: Vec<Float> = At_b
-            .iter()
-            .zip(&At_Ax)
-            .map(|(b, ax)| (b - ax).complete(b.prec()))
-            .collect();
-        for (ri, xi) in r.iter_mut().zip(&x) {
-            *ri -= &lambda * xi;
-        }
-
-        let mut p = r.clone();
-        let mut rs_old = r
-            .iter()
-            .map(|v| (v * v).complete(v.prec()))
-            .reduce(|a, b| a + b)
-            .unwrap();
-
-        for _ in 0..max_iters {
-            let Ap = {
-                let Av = self.matmul_vec(&p);
-                let AtAv = t!(self).mul(&Av);
-                AtAv.into_iter()
-                    .zip(&p)
-                    .map(|(val, pi)| val + &lambda * pi)
-                    .collect::<Vec<_>>()
-            };
-            let denom = p
-                .iter()
-                .zip(&Ap)
-                .map(|(a, b)| (a * b).complete(a.prec()))
-                .reduce(|a, b| a + b)
-                .unwrap();
-            let alpha = (&rs_old / &denom).complete(rs_old.prec());
-
-            for i in 0..x.len() {
-                x[i] += &alpha * &p[i];
-                r[i] -= &alpha * &Ap[i];
-            }
-
-            let rs_new = r
-                .iter()
-                .map(|v| (v * v).complete(v.prec()))
-                .reduce(|a, b| a + b)
-                .unwrap();
-            if rs_new < Float::with_val(precision, 1e-30) {
-                break;
-            }
-            let beta = (&rs_new / &rs_old).complete(rs_new.prec());
-            for i in 0..p.len() {
-                p[i] = &r[i] + (&beta * &p[i]).complete(beta.prec());
-            }
-            rs_old = rs_new;
-        }
-        x
+        solvers::cg_regularized(self, b, max_iters, lambda)
     }
 
     /// LSQR algorithm to solve A x ≈ b
     pub fn lsqr(&self, b: &[Float], max_iters: usize) -> Vec<Float> {
-        let precision = b[0].prec();
-        let mut x = vec![Float::with_val(precision, 0); self.cols];
-        let mut u = b.to_vec();
-        let beta = Self::norm2_vec(&u);
-        for ui in &mut u {
-            *ui /= &beta;
-        }
+        solvers::lsqr(self, b, max_iters)
+    }
 
-        let mut v = t!(self).mul(&u);
-        let alpha = Self::norm2_vec(&v);
-        for vi in &mut v {
-            *vi /= &alpha;
+    /// Solve for every column of an m×k block of right-hand sides in
+    /// parallel, collecting the n-vectors into an n×k matrix
+    fn solve_columns(
+        &self,
+        b: &RugMat,
+        f: impl Fn(&[Float]) -> Vec<Float> + Send + Sync,
+    ) -> RugMat {
+        assert_eq!(
+            b.nrows(),
+            self.nrows(),
+            "Right-hand sides have the wrong row count"
+        );
+        if b.is_empty() {
+            // With no equations every column's minimum-norm solution is zero
+            let precision = self.as_slice().first().map_or(64, |a| a.prec());
+            return RugMat::new(self.ncols(), b.ncols(), precision);
         }
+        let cols: Vec<Vec<Float>> = b.as_slice().par_chunks(b.nrows()).map(f).collect();
+        RugMat::from_col_major(
+            self.ncols(),
+            b.ncols(),
+            cols.into_iter().flatten().collect(),
+        )
+    }
 
-        let mut w = v.clone();
-        let mut phibar = beta.clone();
-        let mut rhobar = alpha.clone();
+    /// [`RugMat::pseudo_inverse_solve`] for each column of B
+    pub fn pseudo_inverse_solve_mat(
+        &self,
+        b: &RugMat,
+        iters: usize,
+        alg: PseudoInverseAlgorithm,
+    ) -> RugMat {
+        self.solve_columns(b, |col| self.pseudo_inverse_solve(col, iters, alg.clone()))
+    }
 
-        for _ in 0..max_iters {
-            let mut u_new = self.matmul_vec(&v);
-            for (u_newi, ui) in u_new.iter_mut().zip(&u) {
-                *u_newi -= alpha.clone() * ui;
-            }
-            let beta = Self::norm2_vec(&u_new);
-            for ui in &mut u_new {
-                *ui /= &beta;
-            }
-            u = u_new;
+    /// [`RugMat::conjugate_gradient`] for each column of B; every column runs
+    /// its own Krylov sequence against the shared operator
+    pub fn conjugate_gradient_mat(&self, b: &RugMat, max_iters: usize) -> RugMat {
+        self.solve_columns(b, |col| self.conjugate_gradient(col, max_iters))
+    }
 
-            let mut v_new = t!(self).mul(&u);
-            for (v_newi, vi) in v_new.iter_mut().zip(&v) {
-                *v_newi -= beta.clone() * vi;
-            }
-            let alpha = Self::norm2_vec(&v_new);
-            for vi in &mut v_new {
-                *vi /= &alpha;
-            }
-            v = v_new;
-
-            let rho = (rhobar.clone().square() + beta.clone().square()).sqrt();
-            let c = rhobar.clone() / &rho;
-            let s = beta / &rho;
-            let theta = s.clone() * &alpha;
-            rhobar = -c.clone() * alpha;
-            let phi = c * phibar.clone();
-            phibar = s * phibar;
-
-            for j in 0..x.len() {
-                x[j] += &phi * &w[j];
-            }
-            for j in 0..w.len() {
-                w[j] = &v[j] - (&theta * &w[j]).complete(theta.prec());
-            }
-        }
+    /// [`RugMat::lsqr`] for each column of B
+    pub fn lsqr_mat(&self, b: &RugMat, max_iters: usize) -> RugMat {
+        self.solve_columns(b, |col| self.lsqr(col, max_iters))
+    }
 
-        x
+    /// CG on the normal equations without forming AᵗA, see [`solvers::cgls`]
+    pub fn cgls(&self, b: &[Float], max_iters: usize, tol: f64) -> Vec<Float> {
+        solvers::cgls(self, b, max_iters, tol)
+    }
+
+    /// Restarted GMRES for a square, possibly nonsymmetric A, see [`solvers::gmres`]
+    pub fn gmres(&self, b: &[Float], restart: usize, max_iters: usize, tol: f64) -> Vec<Float> {
+        solvers::gmres(self, b, restart, max_iters, tol)
     }
 
-    /// Estimate the largest singular value (spectral norm) using power iteration
+    /// Estimate the largest singular value (spectral norm) using power iteration on AᵗA
     pub fn spectral_norm_estimate(&self, max_iters: usize, tol: f64) -> Float {
-        let precision = self.data[0].prec();
-        let mut x = vec![Float::with_val(precision, 1); self.cols];
-        let mut lambda = Float::with_val(precision, 0);
+        let precision = self.as_slice()[0].prec();
+        let mut x = vec![Float::with_val(precision, 1); self.ncols()];
+        let norm_x = Self::norm2_vec(&x);
+        for xi in &mut x {
+            *xi /= &norm_x;
+        }
+        let mut sigma = Float::with_val(precision, 0);
 
         for _ in 0..max_iters {
             let y = self.matmul_vec(&x);
-            let norm_y = Self::norm2_vec(&y);
-            for i in 0..self.cols {
-                x[i] = y[i].clone() / &norm_y;
+            let sigma_new = Self::norm2_vec(&y);
+            let z = t!(self).mul(&y);
+            let norm_z = Self::norm2_vec(&z);
+            if norm_z.is_zero() {
+                return sigma_new;
             }
-            let y2 = self.matmul_vec(&x);
-            let lambda_new = y
-                .iter()
-                .zip(&y2)
-                .map(|(a, b)| (a * b).complete(a.prec()))
-                .reduce(|a, b| a + b)
-                .unwrap();
-            if (&lambda_new - &lambda).complete(lambda_new.prec()).abs()
-                < Float::with_val(precision, tol)
-            {
-                return lambda_new;
+            for i in 0..self.ncols() {
+                x[i] = z[i].clone() / &norm_z;
             }
-            lambda.assign(lambda_new);
+            if (&sigma_new - &sigma).complete(precision).abs() < Float::with_val(precision, tol) {
+                return sigma_new;
+            }
+            sigma = sigma_new;
         }
-        lambda
+        sigma
     }
 
     /// Estimate the smallest singular value using inverse power iteration with gradient solve
     pub fn smallest_singular_value_estimate(&self, max_iters: usize, tol: f64) -> Float {
-        let precision = self.data[0].prec();
-        let mut x = vec![Float::with_val(precision, 1); self.rows];
+        let precision = self.as_slice()[0].prec();
+        let mut x = vec![Float::with_val(precision, 1); self.nrows()];
         let mut lambda = Float::with_val(precision, 0);
 
         for _ in 0..max_iters {
@@ -587,13 +689,13 @@ impl RugMat {
                 },
             );
             let norm_y = Self::norm2_vec(&y);
-            for i in 0..self.cols {
+            for i in 0..self.ncols() {
                 x[i] = y[i].clone() / &norm_y;
             }
-            let Ax = self.matmul_vec(&x);
+            let ax = self.matmul_vec(&x);
             let lambda_new = x
                 .iter()
-                .zip(&Ax)
+                .zip(&ax)
                 .map(|(a, b)| (a * b).complete(a.prec()))
                 .reduce(|a, b| a + b)
                 .unwrap();
@@ -607,9 +709,10 @@ impl RugMat {
         lambda
     }
 
-    /// Estimate the condition number based on spectral and inverse estimates
+    /// Estimate the condition number based on spectral and inverse estimates.
+    /// See `cond1_estimate` for a cheaper estimate that handles singularity.
     pub fn cond_estimate(&self, max_iters: usize, tol: f64) -> Float {
-        let precision = self.data[0].prec();
+        let precision = self.as_slice()[0].prec();
         let sigma_max = self.spectral_norm_estimate(max_iters, tol);
         let sigma_min = self.smallest_singular_value_estimate(max_iters, tol);
         // TODO: Panic might not be the best way to handle this as singular matrices can totally occur
@@ -627,159 +730,35 @@ impl RugMat {
     /// by the condition number κ = cond(A), we may lose up to log2(κ) bits of
     /// effective precision. To achieve `bits` of final result accuracy, we need:
     ///
-    ///     required_bits = bits + ceil(log2(cond))
+    /// ```text
+    /// required_bits = bits + ceil(log2(cond))
+    /// ```
     ///
     /// This function returns that estimate.
     pub fn required_precision_for_cond(bits: usize, cond_estimate: &Float) -> usize {
         let extra = cond_estimate.clone().log2().ceil().to_integer().unwrap();
         bits + extra.to_usize_wrapping()
     }
-}
-
-impl RugMat {
-    /*
-
-    /// LU decomposition using Doolittle’s method (no pivoting)
-    /// Returns (L, U) such that A = L * U
-    pub fn lu_decompose(&self) -> (RugMat, RugMat) {
-        assert_eq!(self.rows, self.cols, "Matrix must be square");
-        let n = self.rows;
-        let precision = self.data[0][0].precision();
-
-        let mut l = RugMat::new(n, n, precision);
-        let mut u = RugMat::new(n, n, precision);
-
-        for i in 0..n {
-            // U upper triangle
-            for k in i..n {
-                let mut sum = Float::with_val(precision, 0);
-                for j in 0..i {
-                    sum += &l.data[i][j] * &u.data[j][k];
-                }
-                u.data[i][k] = &self.data[i][k] - sum;
-            }
-
-            // L lower triangle
-            for k in i..n {
-                if i == k {
-                    l.data[i][i] = Float::with_val(precision, 1);
-                } else {
-                    let mut sum = Float::with_val(precision, 0);
-                    for j in 0..i {
-                        sum += &l.data[k][j] * &u.data[j][i];
-                    }
-                    l.data[k][i] = (&self.data[k][i] - sum) / &u.data[i][i];
-                }
-            }
-        }
-
-        (l, u)
-    }
-
-    /// LU decomposition with partial pivoting.
-    /// Returns (L, U, P) where P is a permutation vector: P[i] is the row index swapped into position i.
-    pub fn lu_decompose_pivot(&self) -> (RugMat, RugMat, Vec<usize>) {
-        assert_eq!(self.rows, self.cols, "Matrix must be square");
-        let n = self.rows;
-        let precision = self.data[0][0].precision();
-
-        let mut a = self.data.clone();
-        let mut p: Vec<usize> = (0..n).collect();
-
-        for i in 0..n {
-            // Partial pivoting: find row with max |a[j][i]|
-            let mut max_row = i;
-            let mut max_val = a[i][i].abs();
-
-            for j in (i + 1)..n {
-                let abs = a[j][i].abs();
-                if abs > max_val {
-                    max_val = abs.clone();
-                    max_row = j;
-                }
-            }
-
-            // Swap rows in `a` and permutation
-            a.swap(i, max_row);
-            p.swap(i, max_row);
-        }
-
-        let mut l = RugMat::new(n, n, precision);
-        let mut u = RugMat::new(n, n, precision);
-
-        for i in 0..n {
-            for j in 0..n {
-                if i <= j {
-                    // Upper triangular matrix
-                    let mut sum = Float::with_val(precision, 0);
-                    for k in 0..i {
-                        sum += &l.data[i][k] * &u.data[k][j];
-                    }
-                    u.data[i][j] = &a[i][j] - sum;
-                } else {
-                    // Lower triangular matrix
-                    let mut sum = Float::with_val(precision, 0);
-                    for k in 0..j {
-                        sum += &l.data[i][k] * &u.data[k][j];
-                    }
-                    l.data[i][j] = (&a[i][j] - sum) / &u.data[j][j];
-                }
-
-                if i == j {
-                    l.data[i][j] = Float::with_val(precision, 1);
-                }
-            }
-        }
-
-        (l, u, p)
-    }
-
-    /// Apply row permutation `p` to a vector (or right-hand side of Ax = b)
-    pub fn apply_permutation(vec: &[Float], p: &[usize]) -> Vec<Float> {
-        p.iter().map(|&i| vec[i].clone()).collect()
-    }
-
-    /// Solves Ly = b for y, where L is lower triangular.
-    pub fn forward_substitution(&self, b: &[Float]) -> Vec<Float> {
-        let n = self.rows;
-        let precision = self.data[0][0].precision();
-        let mut y = vec![Float::with_val(precision, 0); n];
-
-        for i in 0..n {
-            let mut sum = Float::with_val(precision, 0);
-            for j in 0..i {
-                sum += &self.data[i][j] * &y[j];
-            }
-            y[i] = (&b[i] - sum) / &self.data[i][i];
-        }
-        y
-    }
-
-    /// Solves Ux = y for x, where U is upper triangular.
-    pub fn backward_substitution(&self, y: &[Float]) -> Vec<Float> {
-        let n = self.rows;
-        let precision = self.data[0][0].precision();
-        let mut x = vec![Float::with_val(precision, 0); n];
 
-        for i in (0..n).rev() {
-            let mut sum = Float::with_val(precision, 0);
-            for j in (i + 1)..n {
-                sum += &self.data[i][j] * &x[j];
-            }
-            x[i] = (&y[i] - sum) / &self.data[i][i];
+    /// Condition number of a simple eigenvalue λ from its right (Ax = λx) and
+    /// left (yᵗA = λyᵗ) eigenvectors: κ(λ) = ‖x‖‖y‖ / |yᵗx|.
+    ///
+    /// κ = 1 for normal matrices; large values mean λ moves by up to κ·‖E‖
+    /// under a perturbation E. Returns +∞ when x and y are orthogonal
+    /// (a defective eigenvalue).
+    pub fn eigenvalue_condition(right: &[Float], left: &[Float]) -> Float {
+        assert_eq!(right.len(), left.len());
+        let overlap = dot(left, right).abs();
+        let norms = Self::norm2_vec(right) * Self::norm2_vec(left);
+        if overlap.is_zero() {
+            return Float::with_val(norms.prec(), rug::float::Special::Infinity);
         }
-        x
-    }
-
-    /// Solves Ax = b using LU decomposition with partial pivoting.
-    pub fn solve(&self, b: &[Float]) -> Vec<Float> {
-        let (l, u, p) = self.lu_decompose_pivot();
-        let pb = RugMat::apply_permutation(b, &p);
-        let y = l.forward_substitution(&pb);
-        let x = u.backward_substitution(&y);
-        x
+        norms / overlap
     }
+}
 
+impl RugMat {
+    /*
 
     /// Estimate condition number in infinity norm: ||A|| * ||A⁻¹|| (estimated)
     pub fn condition_number(&self) -> Float {
@@ -879,118 +858,6 @@ impl RugMat {
         eigvals.into_iter().map(|λ| λ.abs()).collect()
     }
 
-    /// Returns bidiagonal matrix B from A ≈ U * B * Vᵗ using Golub-Kahan
-    pub fn bidiagonalize(&self) -> RugMat {
-        let mut a = self.clone();
-        let (m, n) = (self.rows, self.cols);
-        let precision = self.data[0][0].precision();
-
-        for i in 0..n.min(m) {
-            // Householder from left (columns)
-            let x: Vec<Float> = (i..m).map(|row| a.data[row][i].clone()).collect();
-            let v = householder_vector(&x, precision);
-
-            for col in i..n {
-                let mut dot = Float::with_val(precision, 0);
-                for row in i..m {
-                    dot += &v[row - i] * &a.data[row][col];
-                }
-                for row in i..m {
-                    a.data[row][col] -= &v[row - i] * &dot * 2.clone();
-                }
-            }
-
-            if i < n - 2 {
-                // Householder from right (rows)
-                let x: Vec<Float> = (i + 1..n).map(|col| a.data[i][col].clone()).collect();
-                let v = householder_vector(&x, precision);
-
-                for row in i..m {
-                    let mut dot = Float::with_val(precision, 0);
-                    for col in i + 1..n {
-                        dot += &v[col - i - 1] * &a.data[row][col];
-                    }
-                    for col in i + 1..n {
-                        a.data[row][col] -= &v[col - i - 1] * &dot * 2.clone();
-                    }
-                }
-            }
-        }
-
-        a // now bidiagonal
-    }
-
-    fn householder_vector(x: &[Float], precision: u32) -> Vec<Float> {
-        let mut v = x.to_vec();
-        let norm_x = norm2(x, precision);
-        let sign = if x[0].significant_bits() == 0
-            || x[0].significant_bits() > 0 && x[0].is_sign_negative()
-        {
-            -Float::with_val(precision, 1)
-        } else {
-            Float::with_val(precision, 1)
-        };
-
-        v[0] += &sign * &norm_x;
-        let norm_v = norm2(&v, precision);
-        for val in &mut v {
-            *val /= &norm_v;
-        }
-
-        v
-    }
-
-    pub fn svd(&self, max_iters: usize) -> Vec<Float> {
-        let precision = self.data[0][0].precision();
-        let mut b = self.bidiagonalize();
-        let mut bt = b.transpose();
-        let mut btb = bt.matmul(&b);
-
-        for _ in 0..max_iters {
-            let (q, r) = btb.qr_decompose();
-            btb = r.matmul(&q);
-        }
-
-        (0..btb.rows)
-            .map(|i| btb.data[i][i].clone().sqrt())
-            .collect()
-    }
-
-    pub fn full_svd(&self, max_iters: usize) -> SVD {
-        let precision = self.data[0][0].precision();
-        let at = self.transpose();
-        let ata = at.matmul(self);
-        let aat = self.matmul(&at);
-
-        // Step 1: eigendecomposition
-        let eigvals_v = ata.eigenvalues_via_qr(max_iters);
-        let mut svals: Vec<Float> = eigvals_v.iter().map(|λ| λ.clone().sqrt()).collect();
-
-        // Step 2: right singular vectors (columns of V)
-        let mut v_cols = Vec::new();
-        for λ in &eigvals_v {
-            let v = power_method(&ata, λ.clone(), max_iters);
-            v_cols.push(v);
-        }
-
-        let vt = RugMat::from_columns(&v_cols);
-
-        // Step 3: left singular vectors
-        let mut u_cols = Vec::new();
-        for (i, s) in svals.iter().enumerate() {
-            if s.significant_bits() == 0 {
-                continue;
-            } // avoid division by 0
-            let av = self.matmul_vec(&vt.get_column(i));
-            let ui: Vec<Float> = av.into_iter().map(|x| x / s).collect();
-            u_cols.push(ui);
-        }
-
-        let u = RugMat::from_columns(&u_cols);
-
-        SVD { u, s: svals, vt }
-    }
-
     pub fn get_column(&self, j: usize) -> Vec<Float> {
         (0..self.rows).map(|i| self.data[i][j].clone()).collect()
     }
@@ -1001,11 +868,7 @@ impl RugMat {
         let data = (0..rows)
             .map(|i| (0..cols_len).map(|j| cols[j][i].clone()).collect())
             .collect();
-        RugMat {
-            data,
-            rows,
-            cols: cols_len,
-        }
+        RugMat::from_col_major(rows, cols_len, data)
     }
 
     pub fn matmul_vec(&self, v: &[Float]) -> Vec<Float> {
@@ -1191,7 +1054,7 @@ impl RugMat {
             }
         }
 
-        RugMat { data, rows, cols }
+        RugMat::from_col_major(rows, cols, data)
     }
 
     pub fn from_faer_f32(mat: &faer::Mat<f32>, precision: u32) -> Self {
@@ -1206,54 +1069,123 @@ impl RugMat {
             }
         }
 
-        RugMat { data, rows, cols }
+        RugMat::from_col_major(rows, cols, data)
     }
 
     */
+    /// Trace (nuclear) norm approximated by the sum of at most
+    /// `max_singulars` leading singular values, see
+    /// [`RugMat::top_k_singular_values`]
     pub fn trace_norm_approx(&self, max_iters: usize, tol: f64, max_singulars: usize) -> Float {
-        let precision = self.data[0].prec();
-        let mut A = self.clone();
+        let precision = self.as_slice()[0].prec();
         let mut total = Float::with_val(precision, 0);
+        for sigma in self.top_k_singular_values(max_singulars, max_iters, tol) {
+            total += sigma;
+        }
+        total
+    }
+
+    /// The k largest singular values, descending, by power iteration on AᵗA
+    /// with deflation A ← A - σ u vᵗ after each one.
+    ///
+    /// Stops early once a value falls below `tol`; `tol` is also the
+    /// relative change at which one power iteration counts as converged.
+    /// Each value costs up to max_iters products with A and Aᵗ, so for
+    /// clustered spectra prefer `svd_partial`.
+    pub fn top_k_singular_values(&self, k: usize, max_iters: usize, tol: f64) -> Vec<Float> {
+        self.top_k_singular_triplets(k, max_iters, tol).s
+    }
+
+    /// [`RugMat::top_k_singular_values`] together with the singular
+    /// vectors: U is m×r and Vᵗ is r×n for the r <= k values found.
+    pub fn top_k_singular_triplets(&self, k: usize, max_iters: usize, tol: f64) -> SVD {
+        let precision = self.as_slice()[0].prec();
+        let (m, n) = (self.nrows(), self.ncols());
+        let tol = Float::with_val(precision, tol);
+        let mut a = self.clone();
+        let mut s = Vec::new();
+        let mut us: Vec<Vec<Float>> = Vec::new();
+        let mut vs: Vec<Vec<Float>> = Vec::new();
+
+        // Keep the iterate out of the deflated directions, which rounding
+        // would otherwise slowly reintroduce
+        let project_out = |x: &mut Vec<Float>, vs: &[Vec<Float>]| {
+            for v in vs {
+                let c = dot(v, x);
+                for (xi, vi) in x.iter_mut().zip(v) {
+                    *xi -= (&c * vi).complete(precision);
+                }
+            }
+        };
 
-        for _ in 0..max_singulars {
-            let sigma = A.spectral_norm_estimate(max_iters, tol);
-            if sigma < Float::with_val(precision, tol) {
+        for _ in 0..k.min(m.min(n)) {
+            let mut x: Vec<Float> = (1..=n).map(|i| Float::with_val(precision, i)).collect();
+            project_out(&mut x, &vs);
+            let norm = Self::norm2_vec(&x);
+            if norm.is_zero() {
                 break;
             }
-            total += &sigma;
+            for xi in &mut x {
+                *xi /= &norm;
+            }
 
-            // Deflate the dominant singular value component
-            // (Not full deflation, approximate)
-            let mut x = vec![Float::with_val(precision, 1); A.cols];
+            let mut sigma = Float::with_val(precision, 0);
             for _ in 0..max_iters {
-                let y = A.matmul_vec(&x);
-                let y2 = Transpose::new(&A).mul(&y);
-                let norm = RugMat::norm2_vec(&y2);
-                if norm < Float::with_val(precision, tol) {
+                let y = a.matmul_vec(&x);
+                let sigma_new = Self::norm2_vec(&y);
+                let mut z = a.matmul_transpose_vec(&y);
+                project_out(&mut z, &vs);
+                let norm_z = Self::norm2_vec(&z);
+                if norm_z.is_zero() {
                     break;
                 }
-                for i in 0..x.len() {
-                    x[i] = y2[i].clone() / &norm;
+                for (xi, zi) in x.iter_mut().zip(&z) {
+                    *xi = zi.clone() / &norm_z;
+                }
+                let change = (&sigma_new - &sigma).complete(precision).abs();
+                sigma = sigma_new;
+                if change <= (&tol * &sigma).complete(precision) {
+                    break;
                 }
             }
 
-            let y = A.matmul_vec(&x);
-            for i in 0..A.rows {
-                for j in 0..A.cols {
-                    let mut temp = (&y[i] * &x[j]).complete(y[i].prec());
-                    temp *= &sigma;
-                    A[(i, j)] -= temp;
+            let mut av = a.matmul_vec(&x);
+            let sigma = Self::norm2_vec(&av);
+            if sigma < tol {
+                break;
+            }
+            for j in 0..n {
+                for i in 0..m {
+                    a[(i, j)] -= (&av[i] * &x[j]).complete(precision);
                 }
             }
+            for ui in &mut av {
+                *ui /= &sigma;
+            }
+            s.push(sigma);
+            us.push(av);
+            vs.push(x);
+        }
+
+        let r = s.len();
+        let mut vt = RugMat::new(r, n, precision);
+        for (i, v) in vs.iter().enumerate() {
+            for (j, vj) in v.iter().enumerate() {
+                vt[(i, j)] = vj.clone();
+            }
+        }
+        SVD {
+            u: RugMat::from_col_major(m, r, us.into_iter().flatten().collect()),
+            s,
+            vt,
         }
-        total
     }
 
     pub fn max_entry_norm(&self) -> Float {
-        let precision = self.data[0].prec();
+        let precision = self.as_slice()[0].prec();
         let mut max_val = Float::with_val(precision, 0);
-        for j in 0..self.cols {
-            for i in 0..self.rows {
+        for j in 0..self.ncols() {
+            for i in 0..self.nrows() {
                 let val = self[(i, j)].clone().abs();
                 if val > max_val {
                     max_val = val;
@@ -1266,12 +1198,12 @@ impl RugMat {
     pub fn lp_norm(&self, p: f64, epsilon: Option<f64>) -> Float {
         assert!(p > 0.0 && p <= 2.0, "Lp norm only defined for p in (0, 2]");
 
-        let precision = self.data[0].prec();
+        let precision = self.as_slice()[0].prec();
         let mut acc = Float::with_val(precision * 2, 0);
         let eps = epsilon.unwrap_or(0.0);
 
-        for j in 0..self.cols {
-            for i in 0..self.rows {
+        for j in 0..self.ncols() {
+            for i in 0..self.nrows() {
                 let mut val = self[(i, j)].clone().abs();
                 if eps > 0.0 && val < Float::with_val(precision, eps) {
                     continue;
@@ -1286,8 +1218,8 @@ impl RugMat {
 
     pub fn l0_norm(&self) -> usize {
         let mut count = 0;
-        for j in 0..self.cols {
-            for i in 0..self.rows {
+        for j in 0..self.ncols() {
+            for i in 0..self.nrows() {
                 if !self[(i, j)].is_zero() {
                     count += 1;
                 }
@@ -1297,10 +1229,10 @@ impl RugMat {
     }
 
     pub fn frobenius_norm(&self) -> Float {
-        let precision = self.data[0].prec();
+        let precision = self.as_slice()[0].prec();
         let mut acc = Float::with_val(precision * 2, 0);
-        for j in 0..self.cols {
-            for i in 0..self.rows {
+        for j in 0..self.ncols() {
+            for i in 0..self.nrows() {
                 acc += self[(i, j)].clone().square();
             }
         }
@@ -1309,11 +1241,11 @@ impl RugMat {
 
     /// 1-norm (maximum absolute column sum)
     pub fn norm1(&self) -> Float {
-        let precision = self.data[0].prec();
-        let mut col_sums = vec![Float::with_val(precision, 0); self.cols];
+        let precision = self.as_slice()[0].prec();
+        let mut col_sums = vec![Float::with_val(precision, 0); self.ncols()];
 
-        for j in 0..self.cols {
-            for i in 0..self.rows {
+        for j in 0..self.ncols() {
+            for i in 0..self.nrows() {
                 col_sums[j] += self[(i, j)].clone().abs();
             }
         }
@@ -1333,6 +1265,169 @@ impl RugMat {
         mat
     }
 
+    /// Assemble a block-diagonal matrix from square or rectangular blocks.
+    ///
+    /// Block entries are cloned with their own precision; the zero padding
+    /// uses the highest precision among the blocks.
+    pub fn block_diag(blocks: &[&RugMat]) -> Self {
+        let rows = blocks.iter().map(|b| b.nrows()).sum();
+        let cols = blocks.iter().map(|b| b.ncols()).sum();
+        let precision = blocks
+            .iter()
+            .filter_map(|b| b.as_slice().first().map(|x| x.prec()))
+            .max()
+            .expect("block_diag needs at least one non-empty block");
+
+        let mut mat = RugMat::new(rows, cols, precision);
+        let (mut row_off, mut col_off) = (0, 0);
+        for block in blocks {
+            mat.set_block(row_off, col_off, block);
+            row_off += block.nrows();
+            col_off += block.ncols();
+        }
+        mat
+    }
+
+    /// Overwrite the submatrix starting at (row_offset, col_offset) with block.
+    ///
+    /// Entries are cloned with their own precision, as in `block_diag`.
+    pub fn set_block(&mut self, row_offset: usize, col_offset: usize, block: &RugMat) {
+        self.for_each_block_column(row_offset, col_offset, block, |dst, src| {
+            dst.clone_from_slice(src)
+        });
+    }
+
+    /// Overwrite the submatrix starting at (row_offset, col_offset) with
+    /// block through `Assign`, so each destination entry keeps its
+    /// allocation and precision and the values are rounded to it. Suited to
+    /// workspace matrices that are overwritten repeatedly.
+    pub fn assign_submatrix(&mut self, row_offset: usize, col_offset: usize, block: &RugMat) {
+        self.for_each_block_column(row_offset, col_offset, block, |dst, src| {
+            for (d, s) in dst.iter_mut().zip(src) {
+                d.assign(s);
+            }
+        });
+    }
+
+    /// Pair each column of block with the rows of self it lands on, in parallel
+    fn for_each_block_column(
+        &mut self,
+        row_offset: usize,
+        col_offset: usize,
+        block: &RugMat,
+        f: impl Fn(&mut [Float], &[Float]) + Send + Sync,
+    ) {
+        assert!(
+            row_offset + block.nrows() <= self.nrows()
+                && col_offset + block.ncols() <= self.ncols(),
+            "Block {}×{} at ({}, {}) does not fit in {}×{}",
+            block.nrows(),
+            block.ncols(),
+            row_offset,
+            col_offset,
+            self.nrows(),
+            self.ncols()
+        );
+        if block.nrows() == 0 {
+            return;
+        }
+        let rows = self.nrows();
+        self.as_mut_slice()[col_offset * rows..(col_offset + block.ncols()) * rows]
+            .par_chunks_mut(rows)
+            .zip(block.as_slice().par_chunks(block.nrows()))
+            .for_each(|(dst, src)| f(&mut dst[row_offset..row_offset + block.nrows()], src));
+    }
+
+    /// Drop column j; contiguous in column-major storage, so a single drain
+    #[allow(deprecated)]
+    pub fn remove_column(&mut self, j: usize) {
+        assert!(j < self.ncols(), "Column {} out of range", j);
+        self.data.drain(j * self.nrows()..(j + 1) * self.nrows());
+        self.cols -= 1;
+    }
+
+    /// Drop row i, compacting the columns in a single pass
+    #[allow(deprecated)]
+    pub fn remove_row(&mut self, i: usize) {
+        assert!(i < self.nrows(), "Row {} out of range", i);
+        let rows = self.nrows();
+        let mut k = 0;
+        self.data.retain(|_| {
+            let keep = k % rows != i;
+            k += 1;
+            keep
+        });
+        self.rows -= 1;
+    }
+
+    /// Insert col so that it becomes column j
+    #[allow(deprecated)]
+    pub fn insert_column(&mut self, j: usize, col: &[Float]) {
+        assert!(j <= self.ncols(), "Column {} out of range", j);
+        assert_eq!(col.len(), self.nrows());
+        let at = j * self.nrows();
+        self.data.splice(at..at, col.iter().cloned());
+        self.cols += 1;
+    }
+
+    /// Set every entry to value, keeping the existing allocations
+    pub fn fill(&mut self, value: &Float) {
+        self.as_mut_slice()
+            .par_iter_mut()
+            .for_each(|x| x.assign(value));
+    }
+
+    /// Set every entry to zero in place
+    pub fn set_zero(&mut self) {
+        self.as_mut_slice().par_iter_mut().for_each(|x| x.assign(0));
+    }
+
+    /// Overwrite a square matrix with the identity in place
+    pub fn set_identity(&mut self) {
+        assert!(
+            self.is_square(),
+            "Identity requires a square matrix, got {}",
+            self.shape()
+        );
+        self.set_zero();
+        for i in 0..self.nrows() {
+            self[(i, i)].assign(1);
+        }
+    }
+
+    /// Overwrite self with the entries of a matrix of the same shape.
+    ///
+    /// Values are assigned into the existing Floats, which keep their
+    /// allocations and precision; entries of other are rounded to it.
+    pub fn copy_from(&mut self, other: &RugMat) {
+        assert_eq!(
+            (self.nrows(), self.ncols()),
+            (other.nrows(), other.ncols()),
+            "Shape mismatch in copy_from"
+        );
+        self.as_mut_slice()
+            .par_iter_mut()
+            .zip(other.as_slice().par_iter())
+            .for_each(|(d, s)| d.assign(s));
+    }
+
+    /// Move the matrix out, leaving an empty 0×0 matrix in its place; no
+    /// entry is copied
+    pub fn take(&mut self) -> RugMat {
+        std::mem::take(self)
+    }
+
+    /// Exchange contents with other in O(1), for ping-pong buffers
+    pub fn swap(&mut self, other: &mut RugMat) {
+        std::mem::swap(self, other);
+    }
+
+    /// The column-major entries, consuming the matrix without copying
+    #[allow(deprecated)]
+    pub fn into_data(self) -> Vec<Float> {
+        self.data
+    }
+
     pub fn diagonal_from_f64(diag: &[f64], precision: u32) -> Self {
         let mut mat = RugMat::new(diag.len(), diag.len(), precision);
         for (i, &v) in diag.iter().enumerate() {
@@ -1341,17 +1436,120 @@ impl RugMat {
         mat
     }
 
+    /// Build from rows, panicking where [`RugMat::try_from_vecvec`] errs
     pub fn from_vecvec(vecvec: Vec<Vec<Float>>) -> Self {
+        Self::try_from_vecvec(vecvec).unwrap_or_else(|e| panic!("from_vecvec: {}", e))
+    }
+
+    /// Build from rows, checking that the input is non-empty, rectangular
+    /// and of a single precision
+    pub fn try_from_vecvec(vecvec: Vec<Vec<Float>>) -> Result<Self, ShapeError> {
         let rows = vecvec.len();
-        let cols = vecvec[0].len();
+        let cols = vecvec.first().map_or(0, Vec::len);
+        if cols == 0 {
+            return Err(ShapeError::Empty);
+        }
         let precision = vecvec[0][0].prec();
+        for (i, row) in vecvec.iter().enumerate() {
+            if row.len() != cols {
+                return Err(ShapeError::RaggedRow {
+                    row: i,
+                    expected: cols,
+                    found: row.len(),
+                });
+            }
+            if let Some(j) = row.iter().position(|v| v.prec() != precision) {
+                return Err(ShapeError::MixedPrecision {
+                    row: i,
+                    col: j,
+                    expected: precision,
+                    found: row[j].prec(),
+                });
+            }
+        }
+
         let mut mat = RugMat::new(rows, cols, precision);
         for (i, row) in vecvec.into_iter().enumerate() {
             for (j, val) in row.into_iter().enumerate() {
                 mat[(i, j)] = val;
             }
         }
-        mat
+        Ok(mat)
+    }
+
+    /// Check whether |a_ij - a_ji| <= tol for all off-diagonal pairs
+    pub fn is_symmetric(&self, tol: f64) -> bool {
+        if !self.is_square() {
+            return false;
+        }
+        if self.is_empty() {
+            return true;
+        }
+        let mut diff = Float::new(self.as_slice()[0].prec());
+        for j in 0..self.ncols() {
+            for i in (j + 1)..self.nrows() {
+                diff.assign(&self[(i, j)] - &self[(j, i)]);
+                diff.abs_mut();
+                if diff > tol {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Check whether all entries strictly below the diagonal are exactly zero
+    pub fn is_upper_triangular(&self) -> bool {
+        (0..self.ncols()).all(|j| ((j + 1)..self.nrows()).all(|i| self[(i, j)].is_zero()))
+    }
+
+    /// Check whether all entries strictly above the diagonal are exactly zero
+    pub fn is_lower_triangular(&self) -> bool {
+        (0..self.ncols()).all(|j| (0..j.min(self.nrows())).all(|i| self[(i, j)].is_zero()))
+    }
+
+    /// Replace A with (A + Aᵗ) / 2 in place
+    pub fn symmetrize(&mut self) {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        for j in 0..self.ncols() {
+            for i in (j + 1)..self.nrows() {
+                let mut avg = Float::with_val(self[(i, j)].prec(), &self[(i, j)] + &self[(j, i)]);
+                avg /= 2;
+                self[(j, i)].assign(&avg);
+                self[(i, j)] = avg;
+            }
+        }
+    }
+
+    /// Upper triangle on and above the k-th diagonal (k = 0 is the main
+    /// diagonal, k > 0 above, k < 0 below); other entries are zeroed
+    pub fn triu(&self, k: isize) -> RugMat {
+        let mut out = self.clone();
+        for j in 0..self.ncols() {
+            for i in 0..self.nrows() {
+                if (j as isize) - (i as isize) < k {
+                    out[(i, j)] = Float::with_val(self[(i, j)].prec(), 0);
+                }
+            }
+        }
+        out
+    }
+
+    /// Lower triangle on and below the k-th diagonal; other entries are zeroed
+    pub fn tril(&self, k: isize) -> RugMat {
+        let mut out = self.clone();
+        for j in 0..self.ncols() {
+            for i in 0..self.nrows() {
+                if (j as isize) - (i as isize) > k {
+                    out[(i, j)] = Float::with_val(self[(i, j)].prec(), 0);
+                }
+            }
+        }
+        out
     }
 }
 
@@ -1433,7 +1631,7 @@ fn test_matmul_known() {
     ]);
     let result = a.matmul(&b);
 
-    let expected = vec![
+    let expected = [
         vec![
             Float::with_val(precision, 13), // 1*3 + 2*5
             Float::with_val(precision, 16), // 1*4 + 2*6
@@ -1463,7 +1661,7 @@ fn test_matmul_vec_known() {
     let vec = vec![Float::with_val(precision, 1), Float::with_val(precision, 2)];
     let result = mat.matmul_vec(&vec);
 
-    let expected = vec![
+    let expected = [
         Float::with_val(precision, 2), // 2*1 + 0*2
         Float::with_val(precision, 7), // 1*1 + 3*2
     ];
@@ -1473,3 +1671,343 @@ fn test_matmul_vec_known() {
         );
     }
 }
+
+#[test]
+fn test_eigenvalue_condition() {
+    let precision = 128;
+    // A = [[1, 1], [0, 2]]: λ = 1 has x = (1, 0), y = (1, -1)
+    let x = vec![Float::with_val(precision, 1), Float::with_val(precision, 0)];
+    let y = vec![
+        Float::with_val(precision, 1),
+        Float::with_val(precision, -1),
+    ];
+    let kappa = RugMat::eigenvalue_condition(&x, &y);
+    assert!((kappa.to_f64() - 2f64.sqrt()).abs() < 1e-20);
+
+    let orthogonal = vec![Float::with_val(precision, 0), Float::with_val(precision, 1)];
+    assert!(RugMat::eigenvalue_condition(&x, &orthogonal).is_infinite());
+}
+
+#[test]
+fn test_symmetry_predicates() {
+    let precision = 128;
+    let mut mat = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 2)],
+        vec![Float::with_val(precision, 4), Float::with_val(precision, 3)],
+    ]);
+    assert!(!mat.is_symmetric(1e-20));
+    assert!(!mat.is_upper_triangular());
+    assert!(!mat.is_lower_triangular());
+
+    mat.symmetrize();
+    assert!(mat.is_symmetric(0.0));
+    assert_eq!(mat[(0, 1)].to_f64(), 3.0);
+    assert_eq!(mat[(1, 0)].to_f64(), 3.0);
+
+    let id = RugMat::identity(3, precision);
+    assert!(id.is_upper_triangular());
+    assert!(id.is_lower_triangular());
+
+    assert!(RugMat::new(0, 0, precision).is_symmetric(0.0));
+}
+
+#[test]
+fn test_triu_tril_bands() {
+    let precision = 64;
+    let mut mat = RugMat::new(3, 4, precision);
+    for j in 0..4 {
+        for i in 0..3 {
+            mat[(i, j)] = Float::with_val(precision, 1 + i * 4 + j);
+        }
+    }
+
+    let upper = mat.triu(0);
+    assert!(upper.is_upper_triangular());
+    assert_eq!(upper[(0, 3)], mat[(0, 3)]);
+    assert!(upper[(1, 0)].is_zero());
+
+    let strict_lower = mat.tril(-1);
+    assert!(strict_lower[(1, 1)].is_zero());
+    assert_eq!(strict_lower[(2, 1)], mat[(2, 1)]);
+
+    let super_diag = mat.triu(1).tril(1);
+    for j in 0..4 {
+        for i in 0..3 {
+            assert_eq!(super_diag[(i, j)].is_zero(), j != i + 1);
+        }
+    }
+}
+
+#[test]
+fn test_block_diag_mixed_shapes_and_precisions() {
+    let a = RugMat::identity(2, 64);
+    let b = RugMat::from_vecvec(vec![vec![
+        Float::with_val(256, 7),
+        Float::with_val(256, 8),
+        Float::with_val(256, 9),
+    ]]);
+    let m = RugMat::block_diag(&[&a, &b]);
+
+    assert_eq!((m.nrows(), m.ncols()), (3, 5));
+    assert_eq!(m[(0, 0)].prec(), 64);
+    assert_eq!(m[(2, 4)].prec(), 256);
+    assert_eq!(m[(2, 4)].to_f64(), 9.0);
+    assert!(m[(0, 2)].is_zero() && m[(2, 0)].is_zero());
+    assert_eq!(m[(0, 2)].prec(), 256);
+}
+
+#[test]
+fn test_set_block_assembles_saddle_point() {
+    let precision = 64;
+    // [[A, B], [Bᵗ, 0]] with A = 2I and B = [1, 2]ᵗ
+    let a = RugMat::diagonal_from_f64(&[2.0, 2.0], precision);
+    let b = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 2)],
+    ]);
+    let mut k = RugMat::new(3, 3, precision);
+    k.set_block(0, 0, &a);
+    k.set_block(0, 2, &b);
+    k.set_block(2, 0, &b.transpose());
+    assert!(k.is_symmetric(0.0));
+    assert_eq!(k[(1, 1)], 2);
+    assert_eq!(k[(1, 2)], 2);
+    assert_eq!(k[(2, 2)], 0);
+
+    let mut copy = RugMat::new(3, 3, precision);
+    copy.copy_from(&k);
+    assert_eq!(copy.as_slice(), k.as_slice());
+
+    // Assignment keeps the workspace precision and its limb buffers
+    let mut work = RugMat::new(3, 3, 2 * precision);
+    let limbs = |x: &Float| unsafe { (*x.as_raw()).d.as_ptr() };
+    let before = limbs(&work.as_slice()[4]);
+    work.copy_from(&k);
+    assert_eq!(work.as_slice(), k.as_slice());
+    work.assign_submatrix(1, 1, &a);
+    assert_eq!(work[(2, 2)], 2);
+    assert_eq!(work[(1, 2)], 0);
+    assert_eq!(work[(2, 0)], 1);
+    assert!(work.as_slice().iter().all(|x| x.prec() == 2 * precision));
+    assert_eq!(limbs(&work.as_slice()[4]), before);
+
+    // Ping-pong and take move the entries rather than copying them
+    let mut other = RugMat::identity(3, precision);
+    let moved = limbs(&work.as_slice()[4]);
+    work.swap(&mut other);
+    assert_eq!(limbs(&other.as_slice()[4]), moved);
+    assert_eq!(work.as_slice(), RugMat::identity(3, precision).as_slice());
+    let taken = other.take();
+    assert_eq!((other.nrows(), other.ncols(), other.len()), (0, 0, 0));
+    assert_eq!(limbs(&taken.into_data()[4]), moved);
+}
+
+#[test]
+fn test_column_and_row_norms2() {
+    let precision = 64;
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 3), Float::with_val(precision, 1)],
+        vec![
+            Float::with_val(precision, 4),
+            Float::with_val(precision, -2),
+        ],
+        vec![Float::with_val(precision, 0), Float::with_val(precision, 2)],
+    ]);
+    let cols = a.column_norms2();
+    assert_eq!(cols, [25, 9]);
+    assert_eq!(cols[1], a.column_norm2(1));
+    assert_eq!(a.row_norms2(), [10, 20, 4]);
+}
+
+#[test]
+fn test_fill_zero_identity_in_place() {
+    let precision = 96;
+    let mut a = RugMat::new(2, 2, precision);
+    a.fill(&Float::with_val(precision, 7));
+    assert!(a.as_slice().iter().all(|x| *x == 7));
+
+    a.set_identity();
+    assert_eq!(a.as_slice(), RugMat::identity(2, precision).as_slice());
+    assert!(a.as_slice().iter().all(|x| x.prec() == precision));
+
+    a.set_zero();
+    assert!(a.as_slice().iter().all(|x| x.is_zero()));
+}
+
+#[test]
+fn test_remove_and_insert_rows_columns() {
+    let precision = 64;
+    let a = RugMat::from_vecvec(
+        [[1, 2, 3], [4, 5, 6]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+
+    let mut b = a.clone();
+    b.remove_column(1);
+    assert_eq!((b.nrows(), b.ncols()), (2, 2));
+    assert_eq!(b.as_slice(), [1, 4, 3, 6]);
+
+    b.insert_column(
+        1,
+        &[Float::with_val(precision, 2), Float::with_val(precision, 5)],
+    );
+    assert_eq!(b.as_slice(), a.as_slice());
+
+    b.remove_row(0);
+    assert_eq!((b.nrows(), b.ncols()), (1, 3));
+    assert_eq!(b.as_slice(), [4, 5, 6]);
+}
+
+#[test]
+fn test_top_k_singular_values() {
+    let precision = 128;
+    // Scaled permutation: singular values 5, 3, 1 in scrambled positions
+    let mut a = RugMat::new(4, 3, precision);
+    a[(2, 0)] = Float::with_val(precision, 3);
+    a[(0, 1)] = Float::with_val(precision, -1);
+    a[(3, 2)] = Float::with_val(precision, 5);
+    let s = a.top_k_singular_values(2, 50, 1e-30);
+    assert_eq!(s.len(), 2);
+    assert!((s[0].clone() - 5u32).abs() < 1e-30 && (s[1].clone() - 3u32).abs() < 1e-30);
+    assert!((a.trace_norm_approx(50, 1e-30, 10) - 9u32).abs() < 1e-30);
+
+    // Dense matrix against the full SVD, with A v = σ u for the vectors;
+    // σ₃ ≈ 1.0047 sits close to σ₄, so power iteration stops early there
+    let mut b = RugMat::new(5, 4, precision);
+    for j in 0..4 {
+        for i in 0..5 {
+            b[(i, j)] = Float::with_val(precision, 1) / (i + 2 * j + 1) as u32
+                + if i == j { 1u32 } else { 0 };
+        }
+    }
+    let exact = b.singular_values(1e-35);
+    let svd = b.top_k_singular_triplets(3, 2000, 1e-32);
+    for (i, sigma) in svd.s.iter().enumerate() {
+        assert!((sigma.clone() - &exact[i]).abs() < 1e-15);
+        let v: Vec<Float> = (0..4).map(|j| svd.vt[(i, j)].clone()).collect();
+        let bv = b.matmul_vec(&v);
+        for (r, bvr) in bv.iter().enumerate() {
+            assert!((bvr.clone() - (sigma * &svd.u[(r, i)]).complete(precision)).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn test_block_rhs_solvers_match_single_column() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(
+        [0, 1, 2, 3]
+            .iter()
+            .map(|&t| vec![Float::with_val(precision, 1), Float::with_val(precision, t)])
+            .collect(),
+    );
+    let b = RugMat::from_vecvec(
+        [[1, 0], [2, -1], [4, 3], [5, 2]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let lsqr = a.lsqr_mat(&b, 10);
+    let cg = a.conjugate_gradient_mat(&b, 10);
+    let pinv = a.pseudo_inverse_solve_mat(&b, 10, PseudoInverseAlgorithm::LSQR);
+    assert_eq!((lsqr.nrows(), lsqr.ncols()), (2, 2));
+    for j in 0..2 {
+        let exact = a
+            .qr()
+            .solve_least_squares(&b.as_slice()[j * 4..(j + 1) * 4]);
+        for (i, e) in exact.iter().enumerate() {
+            for x in [&lsqr, &cg, &pinv] {
+                assert!((x[(i, j)].clone() - e).abs() < 1e-25);
+            }
+        }
+    }
+
+    // No right-hand sides, and right-hand sides with no equations
+    let none = a.lsqr_mat(&RugMat::new(4, 0, precision), 10);
+    assert_eq!((none.nrows(), none.ncols()), (2, 0));
+    let wide = RugMat::new(0, 2, precision).lsqr_mat(&RugMat::new(0, 3, precision), 10);
+    assert_eq!((wide.nrows(), wide.ncols()), (2, 3));
+    assert!(wide.as_slice().iter().all(|x| x.is_zero()));
+}
+
+#[test]
+fn test_try_from_vecvec_validation() {
+    let f = |prec: u32, v: i32| Float::with_val(prec, v);
+    let ok =
+        RugMat::try_from_vecvec(vec![vec![f(64, 1), f(64, 2)], vec![f(64, 3), f(64, 4)]]).unwrap();
+    assert_eq!((ok.nrows(), ok.ncols()), (2, 2));
+    assert_eq!(ok[(1, 0)], 3);
+
+    assert_eq!(
+        RugMat::try_from_vecvec(vec![]).unwrap_err(),
+        ShapeError::Empty
+    );
+    assert_eq!(
+        RugMat::try_from_vecvec(vec![vec![]]).unwrap_err(),
+        ShapeError::Empty
+    );
+    assert_eq!(
+        RugMat::try_from_vecvec(vec![vec![f(64, 1), f(64, 2)], vec![f(64, 3)]]).unwrap_err(),
+        ShapeError::RaggedRow {
+            row: 1,
+            expected: 2,
+            found: 1
+        }
+    );
+    let mixed = RugMat::try_from_vecvec(vec![vec![f(64, 1)], vec![f(128, 2)]]).unwrap_err();
+    assert_eq!(
+        mixed.to_string(),
+        "entry (1, 0) has precision 128, expected 64 like the first entry"
+    );
+}
+
+#[test]
+fn test_column_slices_follow_layout() {
+    let precision = 64;
+    let mut a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 2)],
+        vec![Float::with_val(precision, 3), Float::with_val(precision, 4)],
+        vec![Float::with_val(precision, 5), Float::with_val(precision, 6)],
+    ]);
+    assert_eq!(
+        a.col_as_slice(1),
+        [2, 4, 6].map(|v| Float::with_val(precision, v))
+    );
+    for v in a.col_as_mut_slice(0) {
+        *v *= 10u32;
+    }
+    let (data, rows, cols) = a.raw_parts();
+    assert_eq!((rows, cols), (3, 2));
+    assert_eq!(data[2], 50);
+    assert_eq!(data[rows + 2], a[(2, 1)]);
+    let (data, _, _) = a.raw_parts_mut();
+    data[3].assign(-1);
+    assert_eq!(a[(0, 1)], -1);
+}
+
+#[test]
+fn test_accessors_replace_field_access() {
+    let precision = 64;
+    let entries: Vec<Float> = (1..=6).map(|v| Float::with_val(precision, v)).collect();
+    let mut a = RugMat::from_col_major(3, 2, entries);
+    assert_eq!((a.nrows(), a.ncols(), a.len()), (3, 2, 6));
+    assert!(!a.is_empty() && RugMat::default().is_empty());
+    assert_eq!(a[(0, 1)], 4);
+    a.as_mut_slice()[5].assign(0);
+    assert_eq!(a.as_slice()[5], 0);
+}
+
+#[test]
+fn test_shape_predicates() {
+    let precision = 64;
+    let a = RugMat::new(3, 2, precision);
+    assert_eq!(a.shape(), Shape { rows: 3, cols: 2 });
+    assert_eq!(a.shape().to_string(), "3×2");
+    assert!(!a.is_square() && !a.is_vector());
+    assert!(RugMat::identity(2, precision).is_square());
+    assert!(RugMat::new(1, 4, precision).is_vector());
+    assert!(RugMat::new(4, 1, precision).is_vector());
+    assert!(Shape { rows: 0, cols: 3 }.is_empty());
+}
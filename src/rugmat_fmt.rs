@@ -0,0 +1,104 @@
+// rugmat_fmt.rs: human-readable formatting for RugMat
+use crate::RugMat;
+use std::fmt;
+
+const DEFAULT_DIGITS: usize = 6;
+const DEFAULT_MAX_ROWS: usize = 10;
+const DEFAULT_MAX_COLS: usize = 8;
+
+/// Indices to print along one axis; `None` marks the elided middle.
+fn visible_indices(len: usize, max: usize) -> Vec<Option<usize>> {
+    if len <= max || max == 0 {
+        return (0..len).map(Some).collect();
+    }
+    let head = max.div_ceil(2);
+    let tail = max - head;
+    let mut idx: Vec<Option<usize>> = (0..head).map(Some).collect();
+    idx.push(None);
+    idx.extend((len - tail..len).map(Some));
+    idx
+}
+
+impl RugMat {
+    /// Render the matrix as an aligned table with `digits` significant digits,
+    /// showing at most `max_rows` × `max_cols` entries (head and tail of each axis).
+    pub fn format_with(&self, digits: usize, max_rows: usize, max_cols: usize) -> String {
+        let rows = visible_indices(self.nrows(), max_rows);
+        let cols = visible_indices(self.ncols(), max_cols);
+
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|i| {
+                cols.iter()
+                    .map(|j| match (i, j) {
+                        (Some(i), Some(j)) => {
+                            format!("{:.*e}", digits.max(1), self[(*i, *j)])
+                        }
+                        (None, _) => "⋮".to_string(),
+                        (Some(_), None) => "…".to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..cols.len())
+            .map(|c| {
+                cells
+                    .iter()
+                    .map(|row| row[c].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let precision = self.as_slice().first().map(|x| x.prec()).unwrap_or(0);
+        let mut out = format!(
+            "RugMat {}×{} ({} bits)\n",
+            self.nrows(),
+            self.ncols(),
+            precision
+        );
+        for row in &cells {
+            out.push('[');
+            for (c, cell) in row.iter().enumerate() {
+                if c > 0 {
+                    out.push_str("  ");
+                }
+                let pad = widths[c] - cell.chars().count();
+                out.extend(std::iter::repeat_n(' ', pad));
+                out.push_str(cell);
+            }
+            out.push_str("]\n");
+        }
+        out
+    }
+}
+
+/// Uses the formatter precision as significant digits, e.g. `{:.12}`.
+impl fmt::Display for RugMat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = f.precision().unwrap_or(DEFAULT_DIGITS);
+        f.write_str(&self.format_with(digits, DEFAULT_MAX_ROWS, DEFAULT_MAX_COLS))
+    }
+}
+
+#[test]
+fn test_format_with_truncates_and_aligns() {
+    let mat = RugMat::identity(20, 128);
+    let text = mat.format_with(3, 4, 4);
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines[0], "RugMat 20×20 (128 bits)");
+    // 2 head rows, the elision row, 2 tail rows
+    assert_eq!(lines.len(), 6);
+    assert!(lines[3].contains('⋮'));
+    assert!(lines[1].starts_with("[1.00e0"));
+    assert!(lines[1].contains('…'));
+    let widths: Vec<usize> = lines[1..].iter().map(|l| l.chars().count()).collect();
+    assert!(widths.iter().all(|&w| w == widths[0]));
+
+    assert_eq!(
+        format!("{:.3}", mat),
+        mat.format_with(3, DEFAULT_MAX_ROWS, DEFAULT_MAX_COLS)
+    );
+}
@@ -1,91 +1,925 @@
 // rugmat-io.rs: file I/O and checksum for RugMat
 use crate::RugMat;
-use crate::float_serializer::{read_float, write_float};
+use crate::float_serializer::{
+    check_precision, compact_float_len, read_float, read_float_compact_from_slice,
+    read_float_from_slice, write_float, write_float_compact,
+};
 use rug::Float;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Read, Write};
 
+// Compatibility policy: every format version ever written keeps its own
+// header decoder and entry decoder below, so old files load forever. A
+// layout change gets a new version byte; writers always emit the newest.
+// Within a version, features a reader must understand to decode the entries
+// set bits in the required-capability flags, and readers refuse unknown bits
+// instead of misreading data. Advisory information belongs in the metadata
+// block, which older readers skip.
 const RUGMAT_MAGIC: &[u8; 6] = b"RUGMAT";
 const RUGMAT_VERSION: u8 = 1;
+/// Metadata block, capability flags and per-chunk checksums
+const RUGMAT_VERSION_CHUNKED: u8 = 2;
+/// Delta files from [`save_delta`], versioned separately from matrix files
+const DELTA_MAGIC: &[u8; 6] = b"RUGDLT";
+const DELTA_VERSION: u8 = 1;
+/// Newest version this build reads and the one it writes
+const RUGMAT_VERSION_LATEST: u8 = RUGMAT_VERSION_CHUNKED;
+
+/// Version 2 capability: one shared precision in the header, compact entries
+const FLAG_UNIFORM_PRECISION: u32 = 1 << 0;
+/// Every capability bit this build can decode
+const KNOWN_FLAGS: u32 = FLAG_UNIFORM_PRECISION;
+
+/// Self-describing information stored alongside a matrix by
+/// [`RugMat::save_to_file_with_meta`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub creator: Option<String>,
+    /// Seconds since the Unix epoch
+    pub timestamp: Option<u64>,
+    pub description: Option<String>,
+    /// Arbitrary key-value pairs; the keys above are reserved
+    pub extra: BTreeMap<String, String>,
+    /// Named scalar results (norms, condition numbers, determinants) stored
+    /// exactly, precision included
+    pub scalars: BTreeMap<String, Float>,
+}
+
+impl Metadata {
+    /// Metadata stamped with the current time
+    pub fn now(creator: &str, description: &str) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .ok();
+        Metadata {
+            creator: Some(creator.to_string()),
+            timestamp,
+            description: Some(description.to_string()),
+            extra: BTreeMap::new(),
+            scalars: BTreeMap::new(),
+        }
+    }
+
+    /// u32 pair count, then each key and value as u32 length + UTF-8 bytes;
+    /// then u32 scalar count, each a name as above and a u64 length +
+    /// `write_float` record. A block ending after the pairs has no scalars.
+    fn encode(&self) -> Vec<u8> {
+        let mut pairs: Vec<(&str, String)> = Vec::new();
+        if let Some(c) = &self.creator {
+            pairs.push(("creator", c.clone()));
+        }
+        if let Some(t) = self.timestamp {
+            pairs.push(("timestamp", t.to_string()));
+        }
+        if let Some(d) = &self.description {
+            pairs.push(("description", d.clone()));
+        }
+        for (k, v) in &self.extra {
+            pairs.push((k, v.clone()));
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&(pairs.len() as u32).to_le_bytes());
+        for (k, v) in pairs {
+            for s in [k, v.as_str()] {
+                out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                out.extend_from_slice(s.as_bytes());
+            }
+        }
+
+        out.extend_from_slice(&(self.scalars.len() as u32).to_le_bytes());
+        let mut record = Vec::new();
+        for (name, value) in &self.scalars {
+            out.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            out.extend_from_slice(name.as_bytes());
+            record.clear();
+            write_float(&mut record, value).expect("writing to a Vec cannot fail");
+            out.extend_from_slice(&(record.len() as u64).to_le_bytes());
+            out.extend_from_slice(&record);
+        }
+        out
+    }
+
+    fn decode(mut bytes: &[u8]) -> std::io::Result<Self> {
+        let mut buf4 = [0u8; 4];
+        let mut read_string = |bytes: &mut &[u8]| -> std::io::Result<String> {
+            bytes.read_exact(&mut buf4)?;
+            let mut s = vec![0u8; u32::from_le_bytes(buf4) as usize];
+            bytes.read_exact(&mut s)?;
+            String::from_utf8(s).map_err(|_| invalid_data("Metadata is not valid UTF-8"))
+        };
+
+        let mut count = [0u8; 4];
+        bytes.read_exact(&mut count)?;
+        let mut meta = Metadata::default();
+        for _ in 0..u32::from_le_bytes(count) {
+            let key = read_string(&mut bytes)?;
+            let value = read_string(&mut bytes)?;
+            match key.as_str() {
+                "creator" => meta.creator = Some(value),
+                "timestamp" => {
+                    let t = value
+                        .parse()
+                        .map_err(|_| invalid_data("Bad metadata timestamp"))?;
+                    meta.timestamp = Some(t);
+                }
+                "description" => meta.description = Some(value),
+                _ => {
+                    meta.extra.insert(key, value);
+                }
+            }
+        }
+
+        if bytes.is_empty() {
+            return Ok(meta);
+        }
+        bytes.read_exact(&mut count)?;
+        for _ in 0..u32::from_le_bytes(count) {
+            let name = read_string(&mut bytes)?;
+            let mut buf8 = [0u8; 8];
+            bytes.read_exact(&mut buf8)?;
+            let len = u64::from_le_bytes(buf8);
+            if len > bytes.len() as u64 {
+                return Err(invalid_data("Scalar length exceeds metadata"));
+            }
+            let (record, rest) = bytes.split_at(len as usize);
+            let (value, used) = read_float_from_slice(record)?;
+            if used != record.len() {
+                return Err(invalid_data("Scalar length mismatch"));
+            }
+            meta.scalars.insert(name, value);
+            bytes = rest;
+        }
+        Ok(meta)
+    }
+}
+
+/// Header of a RugMat file as returned by [`RugMat::peek_file`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileInfo {
+    pub version: u8,
+    pub rows: usize,
+    pub cols: usize,
+    /// Present for files written by [`RugMat::save_to_file_with_meta`]
+    pub metadata: Option<Metadata>,
+}
+
+fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// Entries per independently checksummed chunk in version 2 files
+const CHUNK_ENTRIES: usize = 4096;
+/// Smallest possible per-entry record: length prefix plus Float header
+const MIN_ENTRY_BYTES: u64 = 8 + 4 + 1 + 8 + 8;
+
+/// How version 2 chunks store their entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    /// Each entry is a length prefix and a full `write_float` record
+    PerEntry,
+    /// One precision for the whole file, stored in the header; entries are
+    /// fixed-size sign, exponent and limbs
+    Uniform(u32),
+}
+
+impl Encoding {
+    fn of(m: &RugMat) -> Self {
+        match m.as_slice().first() {
+            Some(first) if m.as_slice().iter().all(|x| x.prec() == first.prec()) => {
+                Encoding::Uniform(first.prec())
+            }
+            _ => Encoding::PerEntry,
+        }
+    }
+
+    fn flags(self) -> u32 {
+        match self {
+            Encoding::PerEntry => 0,
+            Encoding::Uniform(_) => FLAG_UNIFORM_PRECISION,
+        }
+    }
+
+    fn min_entry_bytes(self) -> u64 {
+        match self {
+            Encoding::PerEntry => MIN_ENTRY_BYTES,
+            Encoding::Uniform(prec) => compact_float_len(prec) as u64,
+        }
+    }
+}
+
+struct Header {
+    version: u8,
+    rows: usize,
+    cols: usize,
+    encoding: Encoding,
+    meta: Option<Vec<u8>>,
+}
+
+/// Read magic and version, then dispatch to the decoder for that version;
+/// `file_len` bounds every length read
+fn read_header<R: Read>(reader: &mut R, file_len: u64) -> std::io::Result<Header> {
+    let mut magic = [0u8; 6];
+    reader.read_exact(&mut magic)?;
+    if &magic != RUGMAT_MAGIC {
+        return Err(invalid_data("Bad magic header"));
+    }
+
+    let mut version = [0u8; 1];
+    reader.read_exact(&mut version)?;
+    let header = match version[0] {
+        RUGMAT_VERSION => read_header_v1(reader)?,
+        RUGMAT_VERSION_CHUNKED => read_header_v2(reader, file_len)?,
+        v if v > RUGMAT_VERSION_LATEST => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "RugMat file format version {} needs a newer rugmat; \
+                     rugmat {} reads versions {} to {}",
+                    v,
+                    env!("CARGO_PKG_VERSION"),
+                    RUGMAT_VERSION,
+                    RUGMAT_VERSION_LATEST
+                ),
+            ));
+        }
+        _ => return Err(invalid_data("Unsupported version")),
+    };
+
+    let plausible = (header.rows as u64)
+        .checked_mul(header.cols as u64)
+        .and_then(|n| n.checked_mul(header.encoding.min_entry_bytes()))
+        .is_some_and(|bytes| bytes <= file_len);
+    if !plausible {
+        return Err(invalid_data("Shape exceeds file size"));
+    }
+    Ok(header)
+}
+
+fn read_shape<R: Read>(reader: &mut R) -> std::io::Result<([u8; 8], [u8; 8])> {
+    let (mut rows, mut cols) = ([0u8; 8], [0u8; 8]);
+    reader.read_exact(&mut rows)?;
+    reader.read_exact(&mut cols)?;
+    Ok((rows, cols))
+}
+
+/// Version 1: shape only, unprotected until the trailing checksum
+fn read_header_v1<R: Read>(reader: &mut R) -> std::io::Result<Header> {
+    let (rows, cols) = read_shape(reader)?;
+    Ok(Header {
+        version: RUGMAT_VERSION,
+        rows: u64::from_le_bytes(rows) as usize,
+        cols: u64::from_le_bytes(cols) as usize,
+        encoding: Encoding::PerEntry,
+        meta: None,
+    })
+}
+
+/// Version 2: required-capability flags, shape, the shared precision when
+/// flagged, the metadata block and a checksum over all of them
+fn read_header_v2<R: Read>(reader: &mut R, file_len: u64) -> std::io::Result<Header> {
+    let mut flags_bytes = [0u8; 4];
+    reader.read_exact(&mut flags_bytes)?;
+    let flags = u32::from_le_bytes(flags_bytes);
+    let (rows_bytes, cols_bytes) = read_shape(reader)?;
+    let mut prec_bytes = [0u8; 4];
+    if flags & FLAG_UNIFORM_PRECISION != 0 {
+        reader.read_exact(&mut prec_bytes)?;
+    }
+
+    let mut buf8 = [0u8; 8];
+    reader.read_exact(&mut buf8)?;
+    let meta_len = u64::from_le_bytes(buf8);
+    if meta_len > file_len {
+        return Err(invalid_data("Metadata length exceeds file size"));
+    }
+    let mut block = vec![0u8; meta_len as usize];
+    reader.read_exact(&mut block)?;
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&flags_bytes);
+    hasher.update(&rows_bytes);
+    hasher.update(&cols_bytes);
+    if flags & FLAG_UNIFORM_PRECISION != 0 {
+        hasher.update(&prec_bytes);
+    }
+    hasher.update(&block);
+    let mut checksum = [0u8; 32];
+    reader.read_exact(&mut checksum)?;
+    if hasher.finalize() != blake3::Hash::from(checksum) {
+        return Err(invalid_data("Header checksum mismatch"));
+    }
+
+    // Only trusted after the checksum, so corruption is not reported as a
+    // missing feature
+    let unknown = flags & !KNOWN_FLAGS;
+    if unknown != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "RugMat file requires capabilities {:#x} unknown to rugmat {}; \
+                 a newer rugmat is needed",
+                unknown,
+                env!("CARGO_PKG_VERSION")
+            ),
+        ));
+    }
+    let encoding = if flags & FLAG_UNIFORM_PRECISION != 0 {
+        let prec = u32::from_le_bytes(prec_bytes);
+        check_precision(prec)?;
+        Encoding::Uniform(prec)
+    } else {
+        Encoding::PerEntry
+    };
+    Ok(Header {
+        version: RUGMAT_VERSION_CHUNKED,
+        rows: u64::from_le_bytes(rows_bytes) as usize,
+        cols: u64::from_le_bytes(cols_bytes) as usize,
+        encoding,
+        meta: (meta_len > 0).then_some(block),
+    })
+}
+
+/// Chunk checksums also cover the chunk index so that reordered chunks are
+/// rejected
+fn chunk_hash(index: u64, payload: &[u8]) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&index.to_le_bytes());
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+/// Decode Floats from a verified buffer
+fn read_entries(
+    mut payload: &[u8],
+    count: usize,
+    encoding: Encoding,
+    data: &mut Vec<Float>,
+) -> std::io::Result<()> {
+    if let Encoding::Uniform(prec) = encoding {
+        let len = compact_float_len(prec);
+        if payload.len() != count * len {
+            return Err(invalid_data("Chunk length does not match entry count"));
+        }
+        for entry in payload.chunks_exact(len) {
+            data.push(read_float_compact_from_slice(entry, prec)?);
+        }
+        return Ok(());
+    }
+
+    let mut buf8 = [0u8; 8];
+    for _ in 0..count {
+        payload.read_exact(&mut buf8)?;
+        let len = u64::from_le_bytes(buf8);
+        if len > payload.len() as u64 {
+            return Err(invalid_data("Entry length exceeds chunk"));
+        }
+        let (entry, rest) = payload.split_at(len as usize);
+        let (f, used) = read_float_from_slice(entry)?;
+        if used != entry.len() {
+            return Err(invalid_data("Entry length mismatch"));
+        }
+        data.push(f);
+        payload = rest;
+    }
+    if !payload.is_empty() {
+        return Err(invalid_data("Trailing bytes in chunk"));
+    }
+    Ok(())
+}
 
 impl RugMat {
+    /// blake3 hash of the shape and the exact serialized entries (value and precision)
+    pub fn content_hash(&self) -> blake3::Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&(self.nrows() as u64).to_le_bytes());
+        hasher.update(&(self.ncols() as u64).to_le_bytes());
+        let mut temp_buf = Vec::new();
+        for f in self.as_slice() {
+            temp_buf.clear();
+            write_float(&mut temp_buf, f).expect("writing to a Vec cannot fail");
+            hasher.update(&temp_buf);
+        }
+        hasher.finalize()
+    }
+
     pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
+        self.save(path, None)
+    }
+
+    /// Save with `meta` stored after the shape, covered by the header
+    /// checksum
+    pub fn save_to_file_with_meta(&self, path: &str, meta: &Metadata) -> std::io::Result<()> {
+        self.save(path, Some(meta))
+    }
+
+    /// Writes the newest format, version 2: required-capability flags,
+    /// shape, the shared precision when flagged, metadata block (length 0
+    /// when absent) and a checksum of all of them, then the entries in
+    /// chunks of [`CHUNK_ENTRIES`], each preceded by its byte length and
+    /// checksum.
+    ///
+    /// When every entry has the same precision (the common case) it is
+    /// stored once in the header and entries drop their per-entry
+    /// precision, limb count and length prefix.
+    fn save(&self, path: &str, meta: Option<&Metadata>) -> std::io::Result<()> {
         let file = File::create(path)?;
         let mut writer = BufWriter::new(file);
 
         writer.write_all(RUGMAT_MAGIC)?;
-        writer.write_all(&[RUGMAT_VERSION])?;
-        writer.write_all(&(self.rows as u64).to_le_bytes())?;
-        writer.write_all(&(self.cols as u64).to_le_bytes())?;
+        writer.write_all(&[RUGMAT_VERSION_LATEST])?;
+        let encoding = Encoding::of(self);
+        let mut header = Vec::new();
+        header.extend_from_slice(&encoding.flags().to_le_bytes());
+        header.extend_from_slice(&(self.nrows() as u64).to_le_bytes());
+        header.extend_from_slice(&(self.ncols() as u64).to_le_bytes());
+        if let Encoding::Uniform(prec) = encoding {
+            header.extend_from_slice(&prec.to_le_bytes());
+        }
+        writer.write_all(&header)?;
 
+        let block = meta.map(Metadata::encode).unwrap_or_default();
+        writer.write_all(&(block.len() as u64).to_le_bytes())?;
+        writer.write_all(&block)?;
         let mut hasher = blake3::Hasher::new();
-        let mut temp_buf = Vec::new();
+        hasher.update(&header);
+        hasher.update(&block);
+        writer.write_all(hasher.finalize().as_bytes())?;
 
-        for f in &self.data {
-            temp_buf.clear();
-            write_float(&mut temp_buf, f)?;
-            hasher.update(&temp_buf);
-            writer.write_all(&(temp_buf.len() as u64).to_le_bytes())?;
-            writer.write_all(&temp_buf)?;
+        let mut payload = Vec::new();
+        for (index, chunk) in self.as_slice().chunks(CHUNK_ENTRIES).enumerate() {
+            payload.clear();
+            for f in chunk {
+                if encoding != Encoding::PerEntry {
+                    write_float_compact(&mut payload, f)?;
+                    continue;
+                }
+                let start = payload.len();
+                payload.extend_from_slice(&[0u8; 8]);
+                write_float(&mut payload, f)?;
+                let len = (payload.len() - start - 8) as u64;
+                payload[start..start + 8].copy_from_slice(&len.to_le_bytes());
+            }
+            writer.write_all(&(payload.len() as u64).to_le_bytes())?;
+            writer.write_all(chunk_hash(index as u64, &payload).as_bytes())?;
+            writer.write_all(&payload)?;
         }
+        writer.flush()
+    }
 
-        let checksum = hasher.finalize();
-        writer.write_all(checksum.as_bytes())?;
-        Ok(())
+    /// Read the shape and metadata of a saved matrix without loading its
+    /// entries
+    pub fn peek_file(path: &str) -> std::io::Result<FileInfo> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let header = read_header(&mut BufReader::new(file), file_len)?;
+        Ok(FileInfo {
+            version: header.version,
+            rows: header.rows,
+            cols: header.cols,
+            metadata: header
+                .meta
+                .map(|block| Metadata::decode(&block))
+                .transpose()?,
+        })
     }
 
+    /// Load a matrix saved in any format version up to the newest this
+    /// build knows; newer files fail with an error naming the version.
+    ///
+    /// Version 2 chunks are verified before any of their entries are
+    /// decoded, so a corrupt file fails at the first bad chunk having
+    /// allocated at most one chunk of Floats beyond the good ones.
     pub fn load_from_file(path: &str) -> std::io::Result<Self> {
         let file = File::open(path)?;
-        let mut reader = BufReader::new(file);
+        let file_len = file.metadata()?.len();
+        Ok(Self::load(&mut BufReader::new(file), file_len)?.1)
+    }
 
-        let mut magic = [0u8; 6];
-        reader.read_exact(&mut magic)?;
-        if &magic != RUGMAT_MAGIC {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Bad magic header",
-            ));
+    /// Load a whole file already in memory, returning its header as
+    /// [`RugMat::peek_file`] would alongside the matrix
+    pub(crate) fn load_from_bytes(mut bytes: &[u8]) -> std::io::Result<(FileInfo, Self)> {
+        let file_len = bytes.len() as u64;
+        let (header, m) = Self::load(&mut bytes, file_len)?;
+        if !bytes.is_empty() {
+            return Err(invalid_data("Trailing bytes after the entries"));
         }
+        let info = FileInfo {
+            version: header.version,
+            rows: header.rows,
+            cols: header.cols,
+            metadata: header
+                .meta
+                .map(|block| Metadata::decode(&block))
+                .transpose()?,
+        };
+        Ok((info, m))
+    }
 
-        let mut version = [0u8; 1];
-        reader.read_exact(&mut version)?;
-        if version[0] != RUGMAT_VERSION {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Unsupported version",
-            ));
+    fn load<R: Read>(reader: &mut R, file_len: u64) -> std::io::Result<(Header, Self)> {
+        let header = read_header(reader, file_len)?;
+        let (rows, cols) = (header.rows, header.cols);
+
+        let data = match header.version {
+            RUGMAT_VERSION => load_v1_entries(reader, rows * cols)?,
+            _ => load_v2_entries(reader, &header, file_len)?,
+        };
+
+        Ok((header, RugMat::from_col_major(rows, cols, data)))
+    }
+}
+
+/// Save a single Float exactly (value and precision), as a 1×1 matrix
+/// file; see [`Metadata::scalars`] for keeping scalars with a matrix
+pub fn save_float(path: &str, value: &Float) -> std::io::Result<()> {
+    let m = RugMat::from_col_major(1, 1, vec![value.clone()]);
+    m.save_to_file(path)
+}
+
+/// Load a Float written by [`save_float`]
+pub fn load_float(path: &str) -> std::io::Result<Float> {
+    let m = RugMat::load_from_file(path)?;
+    if (m.nrows(), m.ncols()) != (1, 1) {
+        return Err(invalid_data("Not a scalar file"));
+    }
+    Ok(m.into_data().into_iter().next().unwrap())
+}
+
+/// Serialized records of one column, for exact comparison
+fn column_records(m: &RugMat, j: usize) -> Vec<Vec<u8>> {
+    m.as_slice()[j * m.nrows()..(j + 1) * m.nrows()]
+        .iter()
+        .map(|f| {
+            let mut record = Vec::new();
+            write_float(&mut record, f).expect("writing to a Vec cannot fail");
+            record
+        })
+        .collect()
+}
+
+fn column_hash(records: &[Vec<u8>]) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new();
+    for r in records {
+        hasher.update(r);
+    }
+    hasher.finalize()
+}
+
+/// Save the entries of `current` that differ from `base` (value or
+/// precision), for sequences of slowly changing matrices.
+///
+/// Columns are compared by blake3 hash and only the differing entries of
+/// changed columns are written, each as row index plus `write_float`
+/// record. The file records `base.content_hash()` so [`load_delta`]
+/// refuses any other base.
+pub fn save_delta(base: &RugMat, current: &RugMat, path: &str) -> std::io::Result<()> {
+    if (base.nrows(), base.ncols()) != (current.nrows(), current.ncols()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Delta requires matrices of the same shape",
+        ));
+    }
+    let mut body = Vec::new();
+    body.extend_from_slice(&(current.nrows() as u64).to_le_bytes());
+    body.extend_from_slice(&(current.ncols() as u64).to_le_bytes());
+    body.extend_from_slice(base.content_hash().as_bytes());
+
+    let mut columns = Vec::new();
+    for j in 0..current.ncols() {
+        let old = column_records(base, j);
+        let new = column_records(current, j);
+        if column_hash(&old) == column_hash(&new) {
+            continue;
+        }
+        let changed: Vec<(usize, Vec<u8>)> = new
+            .into_iter()
+            .enumerate()
+            .filter(|(i, r)| *r != old[*i])
+            .collect();
+        columns.push((j, changed));
+    }
+    body.extend_from_slice(&(columns.len() as u64).to_le_bytes());
+    for (j, changed) in columns {
+        body.extend_from_slice(&(j as u64).to_le_bytes());
+        body.extend_from_slice(&(changed.len() as u64).to_le_bytes());
+        for (i, record) in changed {
+            body.extend_from_slice(&(i as u64).to_le_bytes());
+            body.extend_from_slice(&(record.len() as u64).to_le_bytes());
+            body.extend_from_slice(&record);
         }
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(DELTA_MAGIC)?;
+    writer.write_all(&[DELTA_VERSION])?;
+    writer.write_all(&body)?;
+    writer.write_all(blake3::hash(&body).as_bytes())?;
+    writer.flush()
+}
 
+/// Rebuild the matrix saved by [`save_delta`] against the same `base`.
+///
+/// The whole-file checksum is verified before any Float is decoded.
+pub fn load_delta(base: &RugMat, path: &str) -> std::io::Result<RugMat> {
+    let bytes = std::fs::read(path)?;
+    if bytes.len() < 7 + 32 || &bytes[..6] != DELTA_MAGIC {
+        return Err(invalid_data("Bad delta magic header"));
+    }
+    if bytes[6] != DELTA_VERSION {
+        return Err(invalid_data("Unsupported delta version"));
+    }
+    let (mut body, checksum) = bytes[7..].split_at(bytes.len() - 7 - 32);
+    if blake3::hash(body) != blake3::Hash::from(<[u8; 32]>::try_from(checksum).unwrap()) {
+        return Err(invalid_data("Checksum mismatch"));
+    }
+
+    let read_u64 = |body: &mut &[u8]| -> std::io::Result<u64> {
         let mut buf8 = [0u8; 8];
+        body.read_exact(&mut buf8)?;
+        Ok(u64::from_le_bytes(buf8))
+    };
+    let rows = read_u64(&mut body)? as usize;
+    let cols = read_u64(&mut body)? as usize;
+    let mut base_hash = [0u8; 32];
+    body.read_exact(&mut base_hash)?;
+    if (rows, cols) != (base.nrows(), base.ncols())
+        || base.content_hash() != blake3::Hash::from(base_hash)
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Delta was saved against a different base matrix",
+        ));
+    }
+
+    let mut m = base.clone();
+    for _ in 0..read_u64(&mut body)? {
+        let j = read_u64(&mut body)? as usize;
+        for _ in 0..read_u64(&mut body)? {
+            let i = read_u64(&mut body)? as usize;
+            let len = read_u64(&mut body)?;
+            if i >= rows || j >= cols || len > body.len() as u64 {
+                return Err(invalid_data("Delta entry out of range"));
+            }
+            let (record, rest) = body.split_at(len as usize);
+            m[(i, j)] = read_float_from_slice(record)?.0;
+            body = rest;
+        }
+    }
+    Ok(m)
+}
+
+/// Version 2 entries in independently checksummed chunks
+fn load_v2_entries<R: Read>(
+    reader: &mut R,
+    header: &Header,
+    file_len: u64,
+) -> std::io::Result<Vec<Float>> {
+    let count = header.rows * header.cols;
+    let mut data = Vec::with_capacity(count);
+    let mut buf8 = [0u8; 8];
+    let mut checksum = [0u8; 32];
+    let mut payload = Vec::new();
+    for index in 0..count.div_ceil(CHUNK_ENTRIES) {
         reader.read_exact(&mut buf8)?;
-        let rows = u64::from_le_bytes(buf8) as usize;
+        let len = u64::from_le_bytes(buf8);
+        if len > file_len {
+            return Err(invalid_data("Chunk length exceeds file size"));
+        }
+        reader.read_exact(&mut checksum)?;
+        payload.resize(len as usize, 0);
+        reader.read_exact(&mut payload)?;
+        if chunk_hash(index as u64, &payload) != blake3::Hash::from(checksum) {
+            return Err(invalid_data("Chunk checksum mismatch"));
+        }
+        let in_chunk = CHUNK_ENTRIES.min(count - data.len());
+        read_entries(&payload, in_chunk, header.encoding, &mut data)?;
+    }
+    Ok(data)
+}
+
+/// Version 1 entries with a single trailing checksum over all of them
+fn load_v1_entries<R: Read>(reader: &mut R, count: usize) -> std::io::Result<Vec<Float>> {
+    let mut data = Vec::with_capacity(count);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf8 = [0u8; 8];
+    for _ in 0..count {
         reader.read_exact(&mut buf8)?;
-        let cols = u64::from_le_bytes(buf8) as usize;
+        let len = u64::from_le_bytes(buf8) as usize;
+        let mut temp_buf = vec![0u8; len];
+        reader.read_exact(&mut temp_buf)?;
+        hasher.update(&temp_buf);
+        let f = read_float(&mut &temp_buf[..])?;
+        data.push(f);
+    }
 
-        let mut data = Vec::with_capacity(rows * cols);
-        let mut hasher = blake3::Hasher::new();
+    let mut checksum_buf = [0u8; 32];
+    reader.read_exact(&mut checksum_buf)?;
+    let checksum_expected = blake3::Hash::from(checksum_buf);
+    let checksum_actual = hasher.finalize();
+    if checksum_actual != checksum_expected {
+        return Err(invalid_data("Checksum mismatch"));
+    }
+    Ok(data)
+}
 
-        for _ in 0..(rows * cols) {
-            reader.read_exact(&mut buf8)?;
-            let len = u64::from_le_bytes(buf8) as usize;
-            let mut temp_buf = vec![0u8; len];
-            reader.read_exact(&mut temp_buf)?;
-            hasher.update(&temp_buf);
-            let f = read_float(&mut &temp_buf[..])?;
-            data.push(f);
-        }
+#[test]
+fn test_save_with_meta_roundtrip_and_peek() {
+    let precision = 256;
+    let a = RugMat::diagonal_from_f64(&[1.5, -2.25, 1e-300], precision);
+    let mut meta = Metadata::now("rugmat tests", "diagonal with a tiny entry");
+    meta.extra
+        .insert("kernel".to_string(), "Matérn ν=5/2".to_string());
 
-        let mut checksum_buf = [0u8; 32];
-        reader.read_exact(&mut checksum_buf)?;
-        let checksum_expected = blake3::Hash::from(checksum_buf);
-        let checksum_actual = hasher.finalize();
-        if checksum_actual != checksum_expected {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Checksum mismatch",
-            ));
+    let dir = std::env::temp_dir();
+    let with_meta = dir.join("rugmat_meta_test.rmat");
+    let plain = dir.join("rugmat_plain_test.rmat");
+    a.save_to_file_with_meta(with_meta.to_str().unwrap(), &meta)
+        .unwrap();
+    a.save_to_file(plain.to_str().unwrap()).unwrap();
+
+    let info = RugMat::peek_file(with_meta.to_str().unwrap()).unwrap();
+    assert_eq!((info.version, info.rows, info.cols), (2, 3, 3));
+    assert_eq!(info.metadata.as_ref(), Some(&meta));
+    assert!(info.metadata.unwrap().timestamp.is_some());
+    let info = RugMat::peek_file(plain.to_str().unwrap()).unwrap();
+    assert_eq!((info.version, info.metadata), (2, None));
+
+    for path in [&with_meta, &plain] {
+        let b = RugMat::load_from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(a.content_hash(), b.content_hash());
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_load_rejects_corrupt_chunk_and_header() {
+    use std::io::ErrorKind;
+    let precision = 128;
+    // Three chunks, the last one partial
+    let n = 2 * CHUNK_ENTRIES + 5;
+    let mut a = RugMat::new(n, 1, precision);
+    for (i, x) in a.as_mut_slice().iter_mut().enumerate() {
+        *x = Float::with_val(precision, i) / 7u32;
+    }
+    let path = std::env::temp_dir().join("rugmat_chunk_test.rmat");
+    let path = path.to_str().unwrap();
+    a.save_to_file(path).unwrap();
+    assert_eq!(
+        RugMat::load_from_file(path).unwrap().content_hash(),
+        a.content_hash()
+    );
+
+    let bytes = std::fs::read(path).unwrap();
+    let corrupt = |offset: usize| {
+        let mut b = bytes.clone();
+        b[offset] ^= 0x40;
+        std::fs::write(path, &b).unwrap();
+        let err = RugMat::load_from_file(path).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        err.to_string()
+    };
+    // A flipped bit in the second chunk's entries, and in the row count
+    assert_eq!(corrupt(bytes.len() / 2), "Chunk checksum mismatch");
+    assert_eq!(corrupt(6 + 1 + 4 + 7), "Header checksum mismatch");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_uniform_precision_encoding_is_smaller() {
+    let precision = 256;
+    let n = 40;
+    let mut a = RugMat::new(n, n, precision);
+    for (i, x) in a.as_mut_slice().iter_mut().enumerate() {
+        *x = Float::with_val(precision, i + 1).sqrt();
+        if i % 3 == 0 {
+            *x = -x.clone();
         }
+    }
+    let dir = std::env::temp_dir();
+    let uniform = dir.join("rugmat_uniform_test.rmat");
+    let mixed = dir.join("rugmat_mixed_test.rmat");
+    a.save_to_file(uniform.to_str().unwrap()).unwrap();
+    let mut b = a.clone();
+    b[(0, 0)] = Float::with_val(precision + 1, &a[(0, 0)]);
+    b.save_to_file(mixed.to_str().unwrap()).unwrap();
+
+    let size = |p: &std::path::PathBuf| std::fs::metadata(p).unwrap().len() as f64;
+    assert!(size(&uniform) < 0.85 * size(&mixed));
+
+    for (path, m) in [(&uniform, &a), (&mixed, &b)] {
+        let loaded = RugMat::load_from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.content_hash(), m.content_hash());
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_scalars_roundtrip() {
+    let precision = 300;
+    let a = RugMat::diagonal_from_f64(&[3.0, 1e-20], precision);
+    let mut meta = Metadata::now("rugmat tests", "ill-conditioned diagonal");
+    meta.scalars.insert("cond1".to_string(), a.cond1_estimate());
+    meta.scalars
+        .insert("det".to_string(), a.lu_decompose_pivot().det());
+    meta.scalars
+        .insert("third".to_string(), Float::with_val(77, 1) / 3u32);
+
+    let dir = std::env::temp_dir();
+    let matrix = dir.join("rugmat_scalars_test.rmat");
+    a.save_to_file_with_meta(matrix.to_str().unwrap(), &meta)
+        .unwrap();
+    let info = RugMat::peek_file(matrix.to_str().unwrap()).unwrap();
+    let scalars = &info.metadata.as_ref().unwrap().scalars;
+    assert_eq!(info.metadata.as_ref(), Some(&meta));
+    assert_eq!(scalars["third"].prec(), 77);
+    std::fs::remove_file(&matrix).unwrap();
 
-        Ok(RugMat { data, rows, cols })
+    let scalar = dir.join("rugmat_scalar_test.rflt");
+    let nan = Float::with_val(precision, -7).sqrt();
+    save_float(scalar.to_str().unwrap(), &nan).unwrap();
+    assert!(load_float(scalar.to_str().unwrap()).unwrap().is_nan());
+    let pi = Float::with_val(precision, rug::float::Constant::Pi);
+    save_float(scalar.to_str().unwrap(), &pi).unwrap();
+    let loaded = load_float(scalar.to_str().unwrap()).unwrap();
+    assert_eq!((loaded.prec(), &loaded), (precision, &pi));
+    std::fs::remove_file(&scalar).unwrap();
+}
+
+#[test]
+fn test_loader_version_policy() {
+    let path = std::env::temp_dir().join("rugmat_version_test.rmat");
+    let path = path.to_str().unwrap();
+
+    // Version 1 layout, as written by earlier releases
+    let precision = 96;
+    let a = RugMat::diagonal_from_f64(&[2.0, -0.5], precision);
+    let mut bytes = RUGMAT_MAGIC.to_vec();
+    bytes.push(RUGMAT_VERSION);
+    bytes.extend_from_slice(&2u64.to_le_bytes());
+    bytes.extend_from_slice(&2u64.to_le_bytes());
+    let mut hasher = blake3::Hasher::new();
+    for f in a.as_slice() {
+        let mut record = Vec::new();
+        write_float(&mut record, f).unwrap();
+        hasher.update(&record);
+        bytes.extend_from_slice(&(record.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&record);
     }
+    bytes.extend_from_slice(hasher.finalize().as_bytes());
+    std::fs::write(path, &bytes).unwrap();
+    let loaded = RugMat::load_from_file(path).unwrap();
+    assert_eq!(loaded.content_hash(), a.content_hash());
+
+    // A future version and an unknown capability in a valid v2 header
+    let mut future = RUGMAT_MAGIC.to_vec();
+    future.push(RUGMAT_VERSION_LATEST + 1);
+    std::fs::write(path, &future).unwrap();
+    let err = RugMat::load_from_file(path).unwrap_err().to_string();
+    assert!(err.contains("needs a newer rugmat"), "{err}");
+
+    let mut header = (1u32 << 31).to_le_bytes().to_vec();
+    header.extend_from_slice(&[0u8; 16]);
+    let mut flagged = RUGMAT_MAGIC.to_vec();
+    flagged.push(RUGMAT_VERSION_CHUNKED);
+    flagged.extend_from_slice(&header);
+    flagged.extend_from_slice(&0u64.to_le_bytes());
+    flagged.extend_from_slice(blake3::hash(&header).as_bytes());
+    std::fs::write(path, &flagged).unwrap();
+    let err = RugMat::peek_file(path).unwrap_err().to_string();
+    assert!(err.contains("capabilities 0x80000000"), "{err}");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_delta_roundtrip() {
+    let precision = 192;
+    let n = 30;
+    let mut base = RugMat::new(n, n, precision);
+    for (k, x) in base.as_mut_slice().iter_mut().enumerate() {
+        *x = Float::with_val(precision, k + 1).ln();
+    }
+    // A continuation step touching two entries in one column and raising
+    // the precision of another
+    let mut current = base.clone();
+    current[(3, 7)] += 1e-40f64;
+    current[(9, 7)] = -current[(9, 7)].clone();
+    current[(0, 20)] = Float::with_val(precision + 64, &current[(0, 20)]);
+
+    let dir = std::env::temp_dir();
+    let delta = dir.join("rugmat_delta_test.rdlt");
+    let full = dir.join("rugmat_delta_full_test.rmat");
+    save_delta(&base, &current, delta.to_str().unwrap()).unwrap();
+    current.save_to_file(full.to_str().unwrap()).unwrap();
+    let size = |p: &std::path::PathBuf| std::fs::metadata(p).unwrap().len();
+    assert!(100 * size(&delta) < size(&full));
+
+    let loaded = load_delta(&base, delta.to_str().unwrap()).unwrap();
+    assert_eq!(loaded.content_hash(), current.content_hash());
+    let err = load_delta(&current, delta.to_str().unwrap()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    std::fs::remove_file(&delta).unwrap();
+    std::fs::remove_file(&full).unwrap();
 }
@@ -0,0 +1,243 @@
+// scaling.rs: diagonal row/column scaling and equilibration
+use crate::RugMat;
+use rayon::prelude::*;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Result of [`RugMat::equilibrate`]: `scaled = diag(row_scale) · A · diag(col_scale)`
+#[derive(Debug, Clone)]
+pub struct Equilibration {
+    pub scaled: RugMat,
+    pub row_scale: Vec<Float>,
+    pub col_scale: Vec<Float>,
+}
+
+/// Result of [`RugMat::balance`]: `balanced = D⁻¹ · A · D` with `D = diag(scale)`
+#[derive(Debug, Clone)]
+pub struct Balance {
+    pub balanced: RugMat,
+    /// Powers of two, so applying or undoing D is exact
+    pub scale: Vec<Float>,
+}
+
+impl Balance {
+    /// Map an eigenvector y of the balanced matrix back to x = D y for A
+    pub fn undo(&self, y: &[Float]) -> Vec<Float> {
+        y.iter()
+            .zip(&self.scale)
+            .map(|(yi, di)| (yi * di).complete(yi.prec()))
+            .collect()
+    }
+}
+
+impl RugMat {
+    /// A ← diag(d) · A
+    pub fn scale_rows(&mut self, d: &[Float]) {
+        let rows = self.nrows();
+        assert_eq!(d.len(), rows);
+        self.as_mut_slice().par_chunks_mut(rows).for_each(|col| {
+            for (x, di) in col.iter_mut().zip(d) {
+                *x *= di;
+            }
+        });
+    }
+
+    /// A ← A · diag(d)
+    pub fn scale_cols(&mut self, d: &[Float]) {
+        assert_eq!(d.len(), self.ncols());
+        let rows = self.nrows();
+        self.as_mut_slice()
+            .par_chunks_mut(rows)
+            .zip(d.par_iter())
+            .for_each(|(col, dj)| {
+                for x in col {
+                    *x *= dj;
+                }
+            });
+    }
+
+    /// Row and column equilibration in the max norm (as in LAPACK `xGEEQU`).
+    ///
+    /// Rows are scaled so their largest entry has magnitude 1, then columns of
+    /// the row-scaled matrix likewise. All-zero rows or columns get scale 1.
+    /// Solve the scaled system for y and recover x = diag(col_scale) · y.
+    pub fn equilibrate(&self) -> Equilibration {
+        let precision = self.as_slice()[0].prec();
+        let one = Float::with_val(precision, 1);
+        let invert = |m: Float| if m.is_zero() { one.clone() } else { m.recip() };
+
+        let mut row_max = vec![Float::with_val(precision, 0); self.nrows()];
+        for j in 0..self.ncols() {
+            for (i, max) in row_max.iter_mut().enumerate() {
+                let v = self[(i, j)].clone().abs();
+                if v > *max {
+                    *max = v;
+                }
+            }
+        }
+        let row_scale: Vec<Float> = row_max.into_iter().map(invert).collect();
+
+        let mut scaled = self.clone();
+        scaled.scale_rows(&row_scale);
+
+        let col_scale: Vec<Float> = scaled
+            .as_slice()
+            .par_chunks(self.nrows())
+            .map(|col| {
+                let max = col.iter().map(|x| x.clone().abs()).fold(
+                    Float::with_val(precision, 0),
+                    |a, b| if b > a { b } else { a },
+                );
+                invert(max)
+            })
+            .collect();
+        scaled.scale_cols(&col_scale);
+
+        Equilibration {
+            scaled,
+            row_scale,
+            col_scale,
+        }
+    }
+
+    /// Parlett-Reinsch balancing by a diagonal similarity (as in LAPACK
+    /// `xGEBAL` without the permutation step).
+    ///
+    /// Row and column i are repeatedly scaled by powers of two until their
+    /// off-diagonal 1-norms are within a factor of two of each other, which
+    /// shrinks the norm without changing the eigenvalues or introducing any
+    /// rounding error.
+    pub fn balance(&self) -> Balance {
+        assert!(
+            self.is_square(),
+            "Matrix must be square, got {}",
+            self.shape()
+        );
+        let n = self.nrows();
+        let precision = self.as_slice()[0].prec();
+        let mut balanced = self.clone();
+        let mut scale = vec![Float::with_val(precision, 1); n];
+
+        let mut converged = false;
+        while !converged {
+            converged = true;
+            for i in 0..n {
+                let mut c = Float::with_val(precision, 0);
+                let mut r = Float::with_val(precision, 0);
+                for j in (0..n).filter(|&j| j != i) {
+                    c += balanced[(j, i)].clone().abs();
+                    r += balanced[(i, j)].clone().abs();
+                }
+                if c.is_zero() || r.is_zero() {
+                    continue;
+                }
+                let s = (&c + &r).complete(precision);
+                let mut f = Float::with_val(precision, 1);
+                let half_r: Float = (&r / 2u32).complete(precision);
+                while c < half_r {
+                    f *= 2u32;
+                    c *= 4u32;
+                }
+                let double_r: Float = (&r * 2u32).complete(precision);
+                while c >= double_r {
+                    f /= 2u32;
+                    c /= 4u32;
+                }
+                if (c + &r) / &f < s * 0.95f64 {
+                    converged = false;
+                    for j in 0..n {
+                        balanced[(i, j)] /= &f;
+                        balanced[(j, i)] *= &f;
+                    }
+                    scale[i] *= &f;
+                }
+            }
+        }
+
+        Balance { balanced, scale }
+    }
+}
+
+#[test]
+fn test_equilibrate_badly_scaled() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(vec![
+        vec![
+            Float::with_val(precision, 1e20),
+            Float::with_val(precision, 2e20),
+        ],
+        vec![
+            Float::with_val(precision, 3e-10),
+            Float::with_val(precision, 1e-10),
+        ],
+    ]);
+    let eq = a.equilibrate();
+
+    // Here the column pass leaves the row maxima intact, so all are 1
+    for i in 0..2 {
+        let row_max = (0..2)
+            .map(|j| eq.scaled[(i, j)].to_f64().abs())
+            .fold(0.0, f64::max);
+        let col_max = (0..2)
+            .map(|k| eq.scaled[(k, i)].to_f64().abs())
+            .fold(0.0, f64::max);
+        assert!((row_max - 1.0).abs() < 1e-30);
+        assert!((col_max - 1.0).abs() < 1e-30);
+    }
+
+    // Rebuild the scaled matrix by hand from the returned vectors
+    let mut manual = a.clone();
+    manual.scale_rows(&eq.row_scale);
+    manual.scale_cols(&eq.col_scale);
+    for (x, y) in manual.as_slice().iter().zip(eq.scaled.as_slice()) {
+        assert_eq!(x, y);
+    }
+}
+
+#[test]
+fn test_balance_badly_scaled_similarity() {
+    let precision = 128;
+    let m = RugMat::from_vecvec(
+        [[1, 2, 0], [3, 1, 1], [1, 0, 2]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    // A = S M S⁻¹ with S spanning 2⁸⁰ of dynamic range
+    let s: Vec<Float> = [0, 40, -40]
+        .iter()
+        .map(|&e| Float::with_val(precision, 1) << e)
+        .collect();
+    let s_inv: Vec<Float> = s.iter().map(|x| x.clone().recip()).collect();
+    let mut a = m.clone();
+    a.scale_rows(&s);
+    a.scale_cols(&s_inv);
+
+    let bal = a.balance();
+    assert!(bal.balanced.frobenius_norm() < 10);
+    assert!(
+        bal.scale
+            .iter()
+            .all(|d| d.is_integer() || d.clone().recip().is_integer())
+    );
+    // D⁻¹ A D rebuilt from the scale vector is exact
+    let mut manual = a.clone();
+    manual.scale_rows(
+        &bal.scale
+            .iter()
+            .map(|d| d.clone().recip())
+            .collect::<Vec<_>>(),
+    );
+    manual.scale_cols(&bal.scale);
+    assert_eq!(manual.as_slice(), bal.balanced.as_slice());
+
+    let mut values = a.eigenvalues(100, 1e-36);
+    let mut expected = m.eigenvalues(100, 1e-36);
+    let key = |z: &rug::Complex| z.real().to_f64();
+    values.sort_by(|x, y| key(x).partial_cmp(&key(y)).unwrap());
+    expected.sort_by(|x, y| key(x).partial_cmp(&key(y)).unwrap());
+    for (x, y) in values.iter().zip(&expected) {
+        assert!((x.real().clone() - y.real()).abs() < 1e-30);
+        assert!((x.imag().clone() - y.imag()).abs() < 1e-30);
+    }
+}
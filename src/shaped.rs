@@ -0,0 +1,164 @@
+// shaped.rs: opt-in dimension tags that make incompatible products fail to compile
+use crate::RugMat;
+use crate::lu::SolveError;
+use rug::Float;
+use std::any::type_name;
+use std::marker::PhantomData;
+
+/// A `RugMat` whose row and column spaces are named by marker types.
+///
+/// The markers are ordinary (usually empty) user types such as
+/// `struct Samples;` and `struct Features;`. Products only type-check when
+/// the inner markers agree, so mixing up two matrices that happen to have
+/// the same size is a compile error rather than a silently wrong answer.
+/// Sizes are still checked at run time, with the marker names in the
+/// message, in case one tag was attached to differently sized objects.
+///
+/// ```
+/// use rugmat::shaped::{Shaped, ShapedVec};
+/// use rugmat::RugMat;
+///
+/// struct Samples;
+/// struct Features;
+///
+/// let x: Shaped<Samples, Features> = Shaped::new(RugMat::identity(3, 64));
+/// let w: ShapedVec<Features> = ShapedVec::new(vec![rug::Float::with_val(64, 2); 3]);
+/// let y: ShapedVec<Samples> = x.mul_vec(&w);
+/// assert_eq!(y.as_slice()[0], 2);
+/// ```
+///
+/// Applying the transpose to a feature vector is rejected by the compiler:
+///
+/// ```compile_fail
+/// # use rugmat::shaped::{Shaped, ShapedVec};
+/// # use rugmat::RugMat;
+/// # struct Samples;
+/// # struct Features;
+/// let x: Shaped<Samples, Features> = Shaped::new(RugMat::identity(3, 64));
+/// let w: ShapedVec<Features> = ShapedVec::new(vec![rug::Float::with_val(64, 2); 3]);
+/// let _ = x.transpose().mul_vec(&w);
+/// ```
+#[derive(Debug)]
+pub struct Shaped<R, C> {
+    mat: RugMat,
+    _dims: PhantomData<fn() -> (R, C)>,
+}
+
+/// A vector living in the space named by the marker `D`
+#[derive(Debug)]
+pub struct ShapedVec<D> {
+    data: Vec<Float>,
+    _dim: PhantomData<fn() -> D>,
+}
+
+// Manual impls: the markers themselves need not be Clone
+impl<R, C> Clone for Shaped<R, C> {
+    fn clone(&self) -> Self {
+        Shaped::new(self.mat.clone())
+    }
+}
+
+impl<D> Clone for ShapedVec<D> {
+    fn clone(&self) -> Self {
+        ShapedVec::new(self.data.clone())
+    }
+}
+
+fn check_len<D>(what: &str, expected: usize, found: usize) {
+    assert_eq!(
+        expected,
+        found,
+        "{} dimension `{}` has length {} here but {} elsewhere",
+        what,
+        type_name::<D>(),
+        found,
+        expected
+    );
+}
+
+impl<R, C> Shaped<R, C> {
+    /// Attach the tags R (rows) and C (columns) to a matrix
+    pub fn new(mat: RugMat) -> Self {
+        Shaped {
+            mat,
+            _dims: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &RugMat {
+        &self.mat
+    }
+
+    pub fn into_inner(self) -> RugMat {
+        self.mat
+    }
+
+    pub fn transpose(&self) -> Shaped<C, R> {
+        Shaped::new(self.mat.transpose())
+    }
+
+    /// self · other, defined only when other's rows are tagged C
+    pub fn matmul<K>(&self, other: &Shaped<C, K>) -> Shaped<R, K> {
+        check_len::<C>("Inner", self.mat.ncols(), other.mat.nrows());
+        Shaped::new(self.mat.matmul(&other.mat))
+    }
+
+    /// self · x for x tagged C
+    pub fn mul_vec(&self, x: &ShapedVec<C>) -> ShapedVec<R> {
+        check_len::<C>("Column", self.mat.ncols(), x.data.len());
+        ShapedVec::new(self.mat.matmul_vec(&x.data))
+    }
+
+    /// Solve self · x = b directly, see [`RugMat::solve`]
+    pub fn solve(&self, b: &ShapedVec<R>) -> Result<ShapedVec<C>, SolveError> {
+        self.mat.solve(&b.data).map(ShapedVec::new)
+    }
+}
+
+impl<D> ShapedVec<D> {
+    pub fn new(data: Vec<Float>) -> Self {
+        ShapedVec {
+            data,
+            _dim: PhantomData,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[Float] {
+        &self.data
+    }
+
+    pub fn into_inner(self) -> Vec<Float> {
+        self.data
+    }
+
+    /// Inner product with another vector of the same space
+    pub fn dot(&self, other: &ShapedVec<D>) -> Float {
+        check_len::<D>("Vector", self.data.len(), other.data.len());
+        crate::rugmat::dot(&self.data, &other.data)
+    }
+}
+
+#[test]
+fn test_shaped_products_and_solve() {
+    struct Obs;
+    struct Params;
+    let precision = 128;
+    let a: Shaped<Obs, Params> = Shaped::new(RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 2), Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 3)],
+    ]));
+    let x: ShapedVec<Params> = ShapedVec::new(vec![
+        Float::with_val(precision, 1),
+        Float::with_val(precision, -1),
+    ]);
+    let b = a.mul_vec(&x);
+    let solved = a.solve(&b).unwrap();
+    for (p, q) in solved.as_slice().iter().zip(x.as_slice()) {
+        assert!((p.clone() - q).abs() < 1e-35);
+    }
+
+    // AᵗA maps parameters to parameters
+    let gram: Shaped<Params, Params> = a.transpose().matmul(&a);
+    assert_eq!(gram.inner()[(1, 1)], 10);
+    assert_eq!(x.dot(&x), 2);
+}
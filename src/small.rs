@@ -0,0 +1,160 @@
+// small.rs: fixed-size matrices for tiny hot kernels (2×2 rotations, 3×3 geometry)
+use crate::RugMat;
+use rug::Float;
+use rug::ops::CompleteRound;
+use std::ops::{Index, IndexMut};
+
+/// R×C matrix whose Float handles live inline (column-major, like `RugMat`).
+///
+/// The limbs are still heap-allocated by MPFR, but there is no outer Vec,
+/// loops have compile-time bounds the optimizer can unroll, and nothing is
+/// dispatched to Rayon, which only costs time at these sizes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmallRugMat<const R: usize, const C: usize> {
+    pub data: [[Float; R]; C],
+}
+
+impl<const R: usize, const C: usize> SmallRugMat<R, C> {
+    pub fn new(precision: u32) -> Self {
+        Self::from_fn(|_, _| Float::with_val(precision, 0))
+    }
+
+    /// Build from a function of (row, col)
+    pub fn from_fn(mut f: impl FnMut(usize, usize) -> Float) -> Self {
+        SmallRugMat {
+            data: std::array::from_fn(|j| std::array::from_fn(|i| f(i, j))),
+        }
+    }
+
+    pub fn transpose(&self) -> SmallRugMat<C, R> {
+        SmallRugMat::from_fn(|i, j| self[(j, i)].clone())
+    }
+
+    /// self · other
+    pub fn matmul<const K: usize>(&self, other: &SmallRugMat<C, K>) -> SmallRugMat<R, K> {
+        let precision = self.data[0][0].prec();
+        SmallRugMat::from_fn(|i, j| {
+            let mut acc = Float::with_val(precision, 0);
+            for k in 0..C {
+                acc += &self[(i, k)] * &other[(k, j)];
+            }
+            acc
+        })
+    }
+
+    /// self · x
+    pub fn mul_vec(&self, x: &[Float; C]) -> [Float; R] {
+        let precision = self.data[0][0].prec();
+        std::array::from_fn(|i| {
+            let mut acc = Float::with_val(precision, 0);
+            for (k, xk) in x.iter().enumerate() {
+                acc += &self[(i, k)] * xk;
+            }
+            acc
+        })
+    }
+}
+
+impl<const N: usize> SmallRugMat<N, N> {
+    pub fn identity(precision: u32) -> Self {
+        Self::from_fn(|i, j| Float::with_val(precision, (i == j) as u32))
+    }
+}
+
+impl SmallRugMat<2, 2> {
+    /// The rotation [c s; -s c]
+    pub fn givens(c: &Float, s: &Float) -> Self {
+        SmallRugMat {
+            data: [[c.clone(), -s.clone()], [s.clone(), c.clone()]],
+        }
+    }
+
+    pub fn det(&self) -> Float {
+        let precision = self.data[0][0].prec();
+        (&self[(0, 0)] * &self[(1, 1)]).complete(precision)
+            - (&self[(0, 1)] * &self[(1, 0)]).complete(precision)
+    }
+}
+
+impl SmallRugMat<3, 3> {
+    /// Determinant by cofactor expansion along the first row
+    pub fn det(&self) -> Float {
+        let precision = self.data[0][0].prec();
+        let minor = |a: (usize, usize), b: (usize, usize), c: (usize, usize), d: (usize, usize)| {
+            (&self[a] * &self[d]).complete(precision) - (&self[b] * &self[c]).complete(precision)
+        };
+        let mut det = Float::with_val(precision, 0);
+        det += &self[(0, 0)] * minor((1, 1), (1, 2), (2, 1), (2, 2));
+        det -= &self[(0, 1)] * minor((1, 0), (1, 2), (2, 0), (2, 2));
+        det += &self[(0, 2)] * minor((1, 0), (1, 1), (2, 0), (2, 1));
+        det
+    }
+}
+
+impl<const R: usize, const C: usize> Index<(usize, usize)> for SmallRugMat<R, C> {
+    type Output = Float;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Float {
+        &self.data[j][i]
+    }
+}
+
+impl<const R: usize, const C: usize> IndexMut<(usize, usize)> for SmallRugMat<R, C> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Float {
+        &mut self.data[j][i]
+    }
+}
+
+impl<const R: usize, const C: usize> From<&SmallRugMat<R, C>> for RugMat {
+    fn from(small: &SmallRugMat<R, C>) -> Self {
+        RugMat::from_col_major(R, C, small.data.iter().flatten().cloned().collect())
+    }
+}
+
+impl<const R: usize, const C: usize> TryFrom<&RugMat> for SmallRugMat<R, C> {
+    type Error = (usize, usize);
+
+    /// Fails with the actual shape when it is not R×C
+    fn try_from(mat: &RugMat) -> Result<Self, Self::Error> {
+        if (mat.nrows(), mat.ncols()) != (R, C) {
+            return Err((mat.nrows(), mat.ncols()));
+        }
+        Ok(Self::from_fn(|i, j| mat[(i, j)].clone()))
+    }
+}
+
+#[test]
+fn test_small_matches_dynamic() {
+    let precision = 128;
+    let a = SmallRugMat::<2, 3>::from_fn(|i, j| Float::with_val(precision, 3 * i + j + 1));
+    let b = SmallRugMat::<3, 2>::from_fn(|i, j| Float::with_val(precision, i + 2 * j));
+    let dynamic = RugMat::from(&a).matmul(&RugMat::from(&b));
+    let small = SmallRugMat::<2, 2>::try_from(&dynamic).unwrap();
+    assert_eq!(a.matmul(&b), small);
+    assert!(SmallRugMat::<3, 3>::try_from(&dynamic).is_err());
+
+    // [[1,2,3],[4,5,6]]·[[0,2],[1,3],[2,4]] = [[8, 20], [17, 47]]
+    assert_eq!(small.det(), 8 * 47 - 20 * 17);
+    let x = [
+        Float::with_val(precision, 1),
+        Float::with_val(precision, -1),
+    ];
+    assert_eq!(small.mul_vec(&x), [-12, -30]);
+}
+
+#[test]
+fn test_small_rotation_det() {
+    let precision = 128;
+    let theta = Float::with_val(precision, 0.3);
+    let (s, c) = theta.sin_cos(Float::new(precision));
+    let g = SmallRugMat::givens(&c, &s);
+    assert!((g.det() - 1u32).abs() < 1e-35);
+    let gtg = g.transpose().matmul(&g);
+    for i in 0..2 {
+        for j in 0..2 {
+            let expected = if i == j { 1u32 } else { 0 };
+            assert!((gtg[(i, j)].clone() - expected).abs() < 1e-35);
+        }
+    }
+    assert_eq!(SmallRugMat::<3, 3>::identity(precision).det(), 1);
+}
@@ -0,0 +1,1671 @@
+// solvers.rs: Krylov solvers generic over LinearOperator
+use crate::RugMat;
+use crate::operator::{ColumnScaled, LinearOperator};
+use crate::preconditioner::{Preconditioner, RightPreconditioned};
+use crate::rugmat::dot;
+use crate::structured::givens;
+use crate::svd::working_tol;
+use rug::ops::CompleteRound;
+use rug::{Assign, Float};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// One entry of a solver's convergence history
+#[derive(Debug, Clone)]
+pub struct IterationRecord {
+    pub iteration: usize,
+    /// The residual norm the solver monitors (see each `_with_info` function)
+    pub residual: Float,
+    /// Wall-clock time since the solve started
+    pub elapsed: Duration,
+}
+
+/// Solution of an iterative solve together with its convergence diagnostics
+#[derive(Debug, Clone)]
+pub struct SolveOutcome {
+    pub x: Vec<Float>,
+    pub iterations: usize,
+    /// Final value of the residual norm the solver monitors
+    pub residual_norm: Float,
+    pub residual_history: Vec<IterationRecord>,
+    /// Whether the solver's own stopping test was met, as opposed to running
+    /// out of iterations or breaking down
+    pub converged: bool,
+    /// Whether the [`SolverOptions::on_iteration`] callback stopped the solve
+    pub cancelled: bool,
+    /// Precision increases made by [`conjugate_gradient_escalating`]; empty
+    /// for the other solvers
+    pub escalations: Vec<PrecisionEscalation>,
+    /// Iteration at which ‖b - Ax‖ reached the [`SolverOptions::discrepancy`]
+    /// level and stopped the solve, if it did
+    pub discrepancy_stop: Option<usize>,
+    /// Iterations done when CG met a search direction p with
+    /// pᵗ(AᵗA + λI)p = 0 and stopped, if it did
+    pub breakdown: Option<usize>,
+}
+
+/// A restart at higher precision made by [`conjugate_gradient_escalating`]
+#[derive(Debug, Clone)]
+pub struct PrecisionEscalation {
+    /// Iterations done before the restart
+    pub iteration: usize,
+    pub from: u32,
+    pub to: u32,
+    /// Monitored residual on the plateau that triggered it
+    pub residual: Float,
+}
+
+#[deprecated(note = "renamed to SolveOutcome")]
+pub type SolveResult = SolveOutcome;
+
+/// File format for [`SolveOutcome::export_history`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+    Csv,
+    Json,
+}
+
+impl SolveOutcome {
+    /// Assemble from a finished history; the residual norm is the last one
+    /// recorded, or `initial` when no iteration ran
+    fn finish(
+        x: Vec<Float>,
+        residual_history: Vec<IterationRecord>,
+        initial: Float,
+        converged: bool,
+    ) -> Self {
+        let residual_norm = residual_history
+            .last()
+            .map_or(initial, |rec| rec.residual.clone());
+        SolveOutcome {
+            x,
+            iterations: residual_history.len(),
+            residual_norm,
+            residual_history,
+            converged,
+            cancelled: false,
+            escalations: Vec::new(),
+            discrepancy_stop: None,
+            breakdown: None,
+        }
+    }
+
+    fn cancelled(mut self, monitor: &Monitor) -> Self {
+        self.cancelled = monitor.cancelled;
+        self
+    }
+
+    fn discrepancy_stop(mut self, iteration: Option<usize>) -> Self {
+        self.discrepancy_stop = iteration;
+        self
+    }
+
+    fn breakdown(mut self, iteration: Option<usize>) -> Self {
+        self.breakdown = iteration;
+        self
+    }
+
+    /// Write the history as iteration, residual and elapsed-seconds columns.
+    ///
+    /// Residuals are written in scientific notation with 17 significant
+    /// digits, keeping exponents beyond the f64 range intact. JSON has no
+    /// NaN or infinity, so non-finite residuals are written there as null.
+    pub fn export_history(&self, path: &str, format: HistoryFormat) -> std::io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        match format {
+            HistoryFormat::Csv => {
+                writeln!(w, "iteration,residual,elapsed_s")?;
+                for rec in &self.residual_history {
+                    writeln!(
+                        w,
+                        "{},{:.17e},{}",
+                        rec.iteration,
+                        rec.residual,
+                        rec.elapsed.as_secs_f64()
+                    )?;
+                }
+            }
+            HistoryFormat::Json => {
+                writeln!(w, "[")?;
+                for (k, rec) in self.residual_history.iter().enumerate() {
+                    let sep = if k + 1 < self.residual_history.len() {
+                        ","
+                    } else {
+                        ""
+                    };
+                    let residual = if rec.residual.is_finite() {
+                        format!("{:.17e}", rec.residual)
+                    } else {
+                        "null".to_string()
+                    };
+                    writeln!(
+                        w,
+                        "  {{\"iteration\": {}, \"residual\": {}, \"elapsed_s\": {}}}{}",
+                        rec.iteration,
+                        residual,
+                        rec.elapsed.as_secs_f64(),
+                        sep
+                    )?;
+                }
+                writeln!(w, "]")?;
+            }
+        }
+        w.flush()
+    }
+}
+
+/// Stopping rules shared by the iterative solvers.
+///
+/// A solve has converged once its monitored residual (see each
+/// `_with_info` function) is at most max(abs_tol, rel_tol·r₀), where r₀ is
+/// the residual at x = 0, also when a warm start is given. The relative
+/// tolerance defaults to the working precision of the right-hand side, so
+/// the same options stay meaningful at 64 and at 1024 bits.
+///
+/// ```
+/// use rugmat::prelude::*;
+/// use rugmat::solvers::{SolverOptions, lsqr_with_info};
+///
+/// let a = RugMat::diagonal_from_f64(&[1.0, 2.0], 256);
+/// let b = vec![Float::with_val(256, 1); 2];
+/// let options = SolverOptions::new(20)
+///     .rel_tol(Float::with_val(256, 1e-60))
+///     .stagnation(5);
+/// assert!(lsqr_with_info(&a, &b, &options).converged);
+/// ```
+#[derive(Clone)]
+pub struct SolverOptions {
+    max_iters: usize,
+    abs_tol: Option<Float>,
+    rel_tol: Option<Float>,
+    stagnation: Option<usize>,
+    callback: Option<Arc<Mutex<IterationCallback>>>,
+    x0: Option<Vec<Float>>,
+    discrepancy: Option<Float>,
+}
+
+/// Progress hook called with the iteration number and monitored residual
+pub type IterationCallback = dyn FnMut(usize, &Float) -> ControlFlow<()> + Send;
+
+impl fmt::Debug for SolverOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SolverOptions")
+            .field("max_iters", &self.max_iters)
+            .field("abs_tol", &self.abs_tol)
+            .field("rel_tol", &self.rel_tol)
+            .field("stagnation", &self.stagnation)
+            .field("callback", &self.callback.is_some())
+            .field("x0_len", &self.x0.as_ref().map(Vec::len))
+            .field("discrepancy", &self.discrepancy)
+            .finish()
+    }
+}
+
+impl SolverOptions {
+    /// At most `max_iters` iterations, relative tolerance at working precision
+    pub fn new(max_iters: usize) -> Self {
+        SolverOptions {
+            max_iters,
+            abs_tol: None,
+            rel_tol: None,
+            stagnation: None,
+            callback: None,
+            x0: None,
+            discrepancy: None,
+        }
+    }
+
+    pub fn max_iters(mut self, max_iters: usize) -> Self {
+        self.max_iters = max_iters;
+        self
+    }
+
+    /// Stop once the residual is at most `tol`, whatever its starting value
+    pub fn abs_tol(mut self, tol: Float) -> Self {
+        self.abs_tol = Some(tol);
+        self
+    }
+
+    /// Stop once the residual has dropped by the factor `tol`
+    pub fn rel_tol(mut self, tol: Float) -> Self {
+        self.rel_tol = Some(tol);
+        self
+    }
+
+    /// Give up, unconverged, after `window` iterations without a new
+    /// smallest residual
+    pub fn stagnation(mut self, window: usize) -> Self {
+        assert!(window >= 1, "Stagnation window must be positive");
+        self.stagnation = Some(window);
+        self
+    }
+
+    /// Call `f(iteration, residual)` after every iteration, with the same
+    /// residual that goes into the history. Returning `ControlFlow::Break`
+    /// stops the solve cleanly: the outcome holds the current iterate with
+    /// `cancelled` set. Clones of the options share the callback.
+    ///
+    /// ```
+    /// use rugmat::prelude::*;
+    /// use rugmat::solvers::{SolverOptions, conjugate_gradient_with_info};
+    /// use std::ops::ControlFlow;
+    ///
+    /// let a = RugMat::diagonal_from_f64(&[1.0, 2.0, 3.0], 128);
+    /// let b = vec![Float::with_val(128, 1); 3];
+    /// let options = SolverOptions::new(100).on_iteration(|iter, residual| {
+    ///     println!("{}: {:.3e}", iter, residual.to_f64());
+    ///     if iter < 2 { ControlFlow::Continue(()) } else { ControlFlow::Break(()) }
+    /// });
+    /// let outcome = conjugate_gradient_with_info(&a, &b, &options);
+    /// assert!(outcome.cancelled && outcome.iterations == 2);
+    /// ```
+    pub fn on_iteration<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(usize, &Float) -> ControlFlow<()> + Send + 'static,
+    {
+        self.callback = Some(Arc::new(Mutex::new(f)));
+        self
+    }
+
+    /// Start from `x0` instead of zero. Tolerances keep referring to the
+    /// zero start, so a good guess saves iterations without changing the
+    /// accuracy asked for; useful when sweeping a parameter such as λ.
+    pub fn initial_guess(mut self, x0: Vec<Float>) -> Self {
+        self.x0 = Some(x0);
+        self
+    }
+
+    /// Stop once ‖b - Ax‖ has fallen to `level`, Morozov's discrepancy
+    /// principle: for noisy data with ‖e‖ <= δ pass τδ with τ slightly above
+    /// 1, since on an ill-posed problem the iterations after that point only
+    /// fit the noise. Honoured by [`lsqr_with_info`], [`cgls_with_info`] and
+    /// CG on the normal equations, which count the stop as converged and
+    /// report it in [`SolveOutcome::discrepancy_stop`]; ignored elsewhere.
+    pub fn discrepancy(mut self, level: Float) -> Self {
+        assert!(
+            !level.is_sign_negative(),
+            "Noise level must be non-negative"
+        );
+        self.discrepancy = Some(level);
+        self
+    }
+
+    /// Whether a data misfit ‖b - Ax‖ meets the discrepancy level
+    fn discrepancy_met(&self, misfit: &Float) -> bool {
+        self.discrepancy
+            .as_ref()
+            .is_some_and(|level| misfit <= level)
+    }
+
+    /// Starting iterate x₀, rounded to the precision of b, and its residual
+    /// b - Ax₀; without a warm start these are 0 and b at no cost
+    fn start<A: LinearOperator + ?Sized>(&self, a: &A, b: &[Float]) -> (Vec<Float>, Vec<Float>) {
+        let precision = b[0].prec();
+        let Some(x0) = &self.x0 else {
+            return (vec![Float::with_val(precision, 0); a.cols()], b.to_vec());
+        };
+        assert_eq!(x0.len(), a.cols(), "Initial guess has the wrong length");
+        let x: Vec<Float> = x0.iter().map(|v| Float::with_val(precision, v)).collect();
+        let ax = a.apply(&x);
+        let r = b
+            .iter()
+            .zip(&ax)
+            .map(|(bi, axi)| (bi - axi).complete(precision))
+            .collect();
+        (x, r)
+    }
+
+    /// Options for solving a correction system from zero whose starting
+    /// residual is `start` while the original one had `reference`, scaling
+    /// the relative tolerance so the absolute target is unchanged
+    fn for_correction(&self, reference: &Float, start: &Float, precision: u32) -> SolverOptions {
+        let mut options = self.clone();
+        options.x0 = None;
+        if !start.is_zero() {
+            let rel = match &self.rel_tol {
+                Some(tol) => tol.clone(),
+                None => Float::with_val(precision, working_tol(precision)),
+            };
+            options.rel_tol = Some(rel * reference / start);
+        }
+        options
+    }
+
+    /// Residual level counting as converged for a starting residual
+    /// `initial`, with the default relative tolerance taken at `precision`
+    fn target(&self, initial: &Float, precision: u32) -> Float {
+        let relative = match &self.rel_tol {
+            Some(tol) => (initial * tol).complete(precision),
+            None => (initial * working_tol(precision)).complete(precision),
+        };
+        match &self.abs_tol {
+            Some(tol) if *tol > relative => Float::with_val(precision, tol),
+            _ => relative,
+        }
+    }
+
+    fn monitor(&self) -> Monitor<'_> {
+        Monitor {
+            options: self,
+            best: None,
+            since_best: 0,
+            cancelled: false,
+        }
+    }
+}
+
+/// Per-solve state behind the stagnation window and the callback
+struct Monitor<'o> {
+    options: &'o SolverOptions,
+    best: Option<Float>,
+    /// Iterations since the monitored residual last reached a new minimum
+    since_best: usize,
+    cancelled: bool,
+}
+
+impl Monitor<'_> {
+    /// Report the residual after `iteration` steps; true when the solve
+    /// should stop without having converged
+    fn halt(&mut self, iteration: usize, residual: &Float) -> bool {
+        if let Some(callback) = &self.options.callback {
+            let mut callback = callback.lock().unwrap_or_else(|e| e.into_inner());
+            if callback(iteration, residual).is_break() {
+                self.cancelled = true;
+                return true;
+            }
+        }
+        match &self.best {
+            Some(best) if residual >= best => self.since_best += 1,
+            _ => {
+                self.best = Some(residual.clone());
+                self.since_best = 0;
+            }
+        }
+        self.options
+            .stagnation
+            .is_some_and(|w| self.since_best >= w)
+    }
+}
+
+/// CG on the normal equations AᵗA x = Aᵗb
+pub fn conjugate_gradient<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    max_iters: usize,
+) -> Vec<Float> {
+    conjugate_gradient_with_info(a, b, &SolverOptions::new(max_iters)).x
+}
+
+/// [`conjugate_gradient`] recording ‖Aᵗ(b - Ax)‖ at every iteration.
+///
+/// Stops without converging when a search direction is annihilated by A,
+/// reporting it in [`SolveOutcome::breakdown`]; [`cg_regularized_with_info`]
+/// solves a nearby system that avoids this.
+pub fn conjugate_gradient_with_info<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    options: &SolverOptions,
+) -> SolveOutcome {
+    cg_normal(a, b, &Float::with_val(b[0].prec(), 0), options)
+}
+
+/// Regularized CG: Solve (AᵗA + λI)x = Aᵗb. To choose λ from the data
+/// instead, see [`RugMat::regularized_least_squares`].
+pub fn cg_regularized<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    max_iters: usize,
+    lambda: Float,
+) -> Vec<Float> {
+    cg_regularized_with_info(a, b, &lambda, &SolverOptions::new(max_iters)).x
+}
+
+/// [`cg_regularized`] recording ‖Aᵗb - (AᵗA + λI)x‖ at every iteration
+pub fn cg_regularized_with_info<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    lambda: &Float,
+    options: &SolverOptions,
+) -> SolveOutcome {
+    cg_normal(a, b, lambda, options)
+}
+
+/// Stagnation window of [`conjugate_gradient_escalating`] when the options
+/// set none
+const ESCALATION_WINDOW: usize = 10;
+
+/// [`conjugate_gradient_with_info`] that climbs in precision instead of
+/// stalling on a rounding plateau.
+///
+/// Whenever ‖Aᵗ(b - Ax)‖ stops improving for the stagnation window of
+/// `options` (10 iterations when unset), or a search direction breaks
+/// down, A, b and the current iterate are re-rounded to twice the
+/// precision, at most `max_precision`, and CG continues from that iterate.
+/// The convergence target stays the one of the original precision; each
+/// restart is recorded in [`SolveOutcome::escalations`] and `x` comes back
+/// at the final precision.
+pub fn conjugate_gradient_escalating(
+    a: &RugMat,
+    b: &[Float],
+    options: &SolverOptions,
+    max_precision: u32,
+) -> SolveOutcome {
+    let mut precision = b[0].prec();
+    let reference = RugMat::norm2_vec(&a.apply_transpose(b));
+    let target = options.target(&reference, precision);
+    let start = Instant::now();
+    let (mut a, mut b) = (a.clone(), b.to_vec());
+    let mut x0 = options.x0.clone();
+    let mut history: Vec<IterationRecord> = Vec::new();
+    let mut escalations = Vec::new();
+
+    loop {
+        let (offset, elapsed) = (history.len(), start.elapsed());
+        let mut stage = options
+            .clone()
+            .max_iters(options.max_iters - offset)
+            .abs_tol(target.clone())
+            .rel_tol(Float::with_val(precision, 0))
+            .stagnation(options.stagnation.unwrap_or(ESCALATION_WINDOW));
+        stage.x0 = x0.take();
+        if let Some(callback) = &options.callback {
+            // Report iterations counted across all precisions
+            let callback = Arc::clone(callback);
+            stage = stage.on_iteration(move |k, residual| {
+                let mut callback = callback.lock().unwrap_or_else(|e| e.into_inner());
+                callback(k + offset, residual)
+            });
+        }
+
+        let zero = Float::with_val(precision, 0);
+        let outcome = cg_normal(&a, &b, &zero, &stage);
+        history.extend(
+            outcome
+                .residual_history
+                .into_iter()
+                .map(|rec| IterationRecord {
+                    iteration: rec.iteration + offset,
+                    elapsed: rec.elapsed + elapsed,
+                    ..rec
+                }),
+        );
+        let plateau = !outcome.converged && !outcome.cancelled && history.len() < options.max_iters;
+        if !plateau || precision >= max_precision {
+            let mut result =
+                SolveOutcome::finish(outcome.x, history, outcome.residual_norm, outcome.converged);
+            result.cancelled = outcome.cancelled;
+            result.escalations = escalations;
+            result.discrepancy_stop = outcome.discrepancy_stop.map(|k| k + offset);
+            result.breakdown = outcome.breakdown.map(|k| k + offset);
+            return result;
+        }
+
+        let next = (2 * precision).min(max_precision);
+        escalations.push(PrecisionEscalation {
+            iteration: history.len(),
+            from: precision,
+            to: next,
+            residual: outcome.residual_norm,
+        });
+        let data = a
+            .as_slice()
+            .iter()
+            .map(|v| Float::with_val(next, v))
+            .collect();
+        a = RugMat::from_col_major(a.nrows(), a.ncols(), data);
+        b = b.iter().map(|v| Float::with_val(next, v)).collect();
+        x0 = Some(outcome.x);
+        precision = next;
+    }
+}
+
+/// CG on (AᵗA + λI)x = Aᵗb; after a direction p with pᵗ(AᵗA + λI)p = 0,
+/// x is left where it was and the breakdown is recorded in the outcome
+fn cg_normal<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    lambda: &Float,
+    options: &SolverOptions,
+) -> SolveOutcome {
+    let precision = b[0].prec();
+    let start = Instant::now();
+    let mut history = Vec::new();
+    let mut monitor = options.monitor();
+    let (mut x, r0) = options.start(a, b);
+    let mut misfit = options.discrepancy.is_some().then(|| r0.clone());
+    let at_b = a.apply_transpose(b);
+    let reference = RugMat::norm2_vec(&at_b);
+    let mut r = if options.x0.is_some() {
+        let mut r = a.apply_transpose(&r0);
+        for (ri, xi) in r.iter_mut().zip(&x) {
+            *ri -= (lambda * xi).complete(precision);
+        }
+        r
+    } else {
+        at_b
+    };
+    let mut p = r.clone();
+    let mut rs_old = dot(&r, &r);
+    let initial = rs_old.clone().sqrt();
+    let target = options.target(&reference, precision);
+    let mut converged = initial <= target;
+    let mut breakdown = None;
+    let mut discrepancy_stop = None;
+    if misfit
+        .as_ref()
+        .is_some_and(|s| options.discrepancy_met(&RugMat::norm2_vec(s)))
+    {
+        discrepancy_stop = Some(0);
+        converged = true;
+    }
+
+    for iter in 0..options.max_iters {
+        if converged {
+            break;
+        }
+        let q = a.apply(&p);
+        let mut ap = a.apply_transpose(&q);
+        if !lambda.is_zero() {
+            for (api, pi) in ap.iter_mut().zip(&p) {
+                *api += (lambda * pi).complete(precision);
+            }
+        }
+        let denom = dot(&p, &ap);
+        if denom.is_zero() {
+            breakdown = Some(iter);
+            break;
+        }
+        let alpha = (&rs_old / &denom).complete(precision);
+        for (xi, pi) in x.iter_mut().zip(&p) {
+            *xi += (&alpha * pi).complete(precision);
+        }
+        for (ri, api) in r.iter_mut().zip(&ap) {
+            *ri -= (&alpha * api).complete(precision);
+        }
+        if let Some(s) = &mut misfit {
+            for (si, qi) in s.iter_mut().zip(&q) {
+                *si -= (&alpha * qi).complete(precision);
+            }
+        }
+
+        let rs_new = dot(&r, &r);
+        let residual = rs_new.clone().sqrt();
+        history.push(IterationRecord {
+            iteration: iter + 1,
+            residual: residual.clone(),
+            elapsed: start.elapsed(),
+        });
+        converged = residual <= target;
+        if misfit
+            .as_ref()
+            .is_some_and(|s| options.discrepancy_met(&RugMat::norm2_vec(s)))
+        {
+            discrepancy_stop = Some(iter + 1);
+            converged = true;
+        }
+        if monitor.halt(iter + 1, &residual) || converged {
+            break;
+        }
+
+        let beta = (&rs_new / &rs_old).complete(precision);
+        for (pi, ri) in p.iter_mut().zip(&r) {
+            *pi *= &beta;
+            *pi += ri;
+        }
+        rs_old = rs_new;
+    }
+    SolveOutcome::finish(x, history, initial, converged)
+        .cancelled(&monitor)
+        .discrepancy_stop(discrepancy_stop)
+        .breakdown(breakdown)
+}
+/// Gradient descent x ← x - α Aᵗ(Ax - b) on ½‖Ax - b‖² with a fixed step
+pub fn gradient_descent<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    alpha: &Float,
+    max_iters: usize,
+) -> Vec<Float> {
+    gradient_descent_with_info(a, b, alpha, &SolverOptions::new(max_iters)).x
+}
+
+/// [`gradient_descent`] recording the gradient norm ‖Aᵗ(Ax - b)‖ at the
+/// current iterate; no step is taken once it meets the tolerances.
+///
+/// Converges for 0 < α < 2/σ₁², slowly when A is ill conditioned.
+pub fn gradient_descent_with_info<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    alpha: &Float,
+    options: &SolverOptions,
+) -> SolveOutcome {
+    let precision = b[0].prec();
+    let start = Instant::now();
+    let mut history = Vec::new();
+    let mut monitor = options.monitor();
+    let (mut x, _) = options.start(a, b);
+    let reference = RugMat::norm2_vec(&a.apply_transpose(b));
+    let target = options.target(&reference, precision);
+    let mut converged = false;
+
+    for iter in 0..options.max_iters {
+        let mut r = a.apply(&x);
+        for (ri, bi) in r.iter_mut().zip(b) {
+            *ri -= bi;
+        }
+        let grad = a.apply_transpose(&r);
+        let residual = RugMat::norm2_vec(&grad);
+        history.push(IterationRecord {
+            iteration: iter + 1,
+            residual: residual.clone(),
+            elapsed: start.elapsed(),
+        });
+        converged = residual <= target;
+        if monitor.halt(iter + 1, &residual) || converged {
+            break;
+        }
+        for (xj, gj) in x.iter_mut().zip(&grad) {
+            *xj -= (alpha * gj).complete(precision);
+        }
+    }
+    SolveOutcome::finish(x, history, reference, converged).cancelled(&monitor)
+}
+
+/// LSQR (Paige & Saunders) to solve A x ≈ b
+pub fn lsqr<A: LinearOperator + ?Sized>(a: &A, b: &[Float], max_iters: usize) -> Vec<Float> {
+    lsqr_with_info(a, b, &SolverOptions::new(max_iters)).x
+}
+
+/// [`lsqr`] recording the residual estimate ‖b - Ax‖ = φ̄ at every iteration.
+///
+/// Converged once φ̄ meets the tolerances relative to ‖b‖, or the
+/// normal-equation residual estimate ‖Aᵗr‖ = φ̄α|c| meets them relative to
+/// ‖Aᵗb‖, covering consistent and least-squares problems alike.
+pub fn lsqr_with_info<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    options: &SolverOptions,
+) -> SolveOutcome {
+    let precision = b[0].prec();
+    let start = Instant::now();
+    let mut history = Vec::new();
+    let (mut x, mut u) = options.start(a, b);
+    let mut beta = RugMat::norm2_vec(&u);
+    if beta.is_zero() {
+        return SolveOutcome::finish(x, history, beta, true);
+    }
+    if options.discrepancy_met(&beta) {
+        return SolveOutcome::finish(x, history, beta, true).discrepancy_stop(Some(0));
+    }
+    for ui in &mut u {
+        *ui /= &beta;
+    }
+
+    let mut v = a.apply_transpose(&u);
+    let mut alpha = RugMat::norm2_vec(&v);
+    if alpha.is_zero() {
+        // The residual is orthogonal to the range, so x is already optimal
+        return SolveOutcome::finish(x, history, beta, true);
+    }
+    for vi in &mut v {
+        *vi /= &alpha;
+    }
+
+    let mut w = v.clone();
+    let mut phibar = beta.clone();
+    let mut rhobar = alpha.clone();
+    let initial = phibar.clone();
+    let (norm_b, norm_at_b) = if options.x0.is_some() {
+        (
+            RugMat::norm2_vec(b),
+            RugMat::norm2_vec(&a.apply_transpose(b)),
+        )
+    } else {
+        (beta.clone(), (&alpha * &beta).complete(precision))
+    };
+    let residual_target = options.target(&norm_b, precision);
+    let normal_target = options.target(&norm_at_b, precision);
+    let mut monitor = options.monitor();
+    let mut converged = false;
+    let mut discrepancy_stop = None;
+
+    for iter in 0..options.max_iters {
+        // Golub-Kahan step: β u = A v - α u, α v = Aᵗ u - β v
+        let mut u_new = a.apply(&v);
+        for (u_newi, ui) in u_new.iter_mut().zip(&u) {
+            *u_newi -= (&alpha * ui).complete(precision);
+        }
+        beta = RugMat::norm2_vec(&u_new);
+        if !beta.is_zero() {
+            for ui in &mut u_new {
+                *ui /= &beta;
+            }
+        }
+        u = u_new;
+
+        let mut v_new = a.apply_transpose(&u);
+        for (v_newi, vi) in v_new.iter_mut().zip(&v) {
+            *v_newi -= (&beta * vi).complete(precision);
+        }
+        alpha = RugMat::norm2_vec(&v_new);
+        if !alpha.is_zero() {
+            for vi in &mut v_new {
+                *vi /= &alpha;
+            }
+        }
+        v = v_new;
+
+        // Plane rotation eliminating β from the bidiagonal
+        let rho = (rhobar.clone().square() + beta.clone().square()).sqrt();
+        let c = (&rhobar / &rho).complete(precision);
+        let s = (&beta / &rho).complete(precision);
+        let theta = (&s * &alpha).complete(precision);
+        rhobar = -(&c * &alpha).complete(precision);
+        let phi = (&c * &phibar).complete(precision);
+        phibar *= &s;
+
+        let step = phi / &rho;
+        let w_scale = theta / &rho;
+        for j in 0..x.len() {
+            x[j] += (&step * &w[j]).complete(precision);
+            w[j] = &v[j] - (&w_scale * &w[j]).complete(precision);
+        }
+
+        let residual = phibar.clone().abs();
+        history.push(IterationRecord {
+            iteration: iter + 1,
+            residual: residual.clone(),
+            elapsed: start.elapsed(),
+        });
+        let normal_residual = (&phibar * &alpha).complete(precision) * c.abs();
+        converged = residual <= residual_target || normal_residual <= normal_target;
+        if options.discrepancy_met(&residual) {
+            discrepancy_stop = Some(iter + 1);
+            converged = true;
+        }
+        if monitor.halt(iter + 1, &residual) || converged {
+            break;
+        }
+    }
+
+    SolveOutcome::finish(x, history, initial, converged)
+        .cancelled(&monitor)
+        .discrepancy_stop(discrepancy_stop)
+}
+
+/// CGLS: CG on AᵗA x = Aᵗb through products with A and Aᵗ only
+pub fn cgls(a: &RugMat, b: &[Float], max_iters: usize, tol: f64) -> Vec<Float> {
+    let options = SolverOptions::new(max_iters).rel_tol(Float::with_val(b[0].prec(), tol));
+    cgls_with_info(a, b, &options).x
+}
+
+/// [`cgls`] recording the normal-equation residual ‖Aᵗ(b - Ax)‖.
+///
+/// The residual r = b - Ax is updated recursively, so each iteration costs
+/// one product with A and one with Aᵗ. All vectors are allocated once and
+/// overwritten in place, keeping their limbs across iterations.
+pub fn cgls_with_info(a: &RugMat, b: &[Float], options: &SolverOptions) -> SolveOutcome {
+    assert_eq!(b.len(), a.nrows());
+    let precision = b[0].prec();
+    let start = Instant::now();
+    let mut history = Vec::new();
+    let zeros = |len| vec![Float::with_val(precision, 0); len];
+
+    let (mut x, mut r) = options.start(a, b);
+    let mut s = zeros(a.ncols());
+    let mut q = zeros(a.nrows());
+    a.matmul_transpose_vec_into(&r, &mut s);
+    let mut p = s.clone();
+    let mut gamma = dot(&s, &s);
+    let initial = gamma.clone().sqrt();
+    let reference = if options.x0.is_some() {
+        RugMat::norm2_vec(&a.matmul_transpose_vec(b))
+    } else {
+        initial.clone()
+    };
+    let target = options.target(&reference, precision);
+    let mut monitor = options.monitor();
+    let mut step = Float::new(precision);
+    let mut converged = initial <= target;
+    let mut discrepancy_stop = None;
+    if options.discrepancy.is_some() && options.discrepancy_met(&RugMat::norm2_vec(&r)) {
+        discrepancy_stop = Some(0);
+        converged = true;
+    }
+
+    for iter in 0..options.max_iters {
+        if converged {
+            break;
+        }
+        a.matmul_vec_into(&p, &mut q);
+        let qq = dot(&q, &q);
+        if qq.is_zero() {
+            break;
+        }
+        step.assign(&gamma / &qq);
+        for (xi, pi) in x.iter_mut().zip(&p) {
+            *xi += &step * pi;
+        }
+        for (ri, qi) in r.iter_mut().zip(&q) {
+            *ri -= &step * qi;
+        }
+
+        a.matmul_transpose_vec_into(&r, &mut s);
+        let gamma_new = dot(&s, &s);
+        let residual = gamma_new.clone().sqrt();
+        history.push(IterationRecord {
+            iteration: iter + 1,
+            residual: residual.clone(),
+            elapsed: start.elapsed(),
+        });
+        converged = residual <= target;
+        if options.discrepancy.is_some() && options.discrepancy_met(&RugMat::norm2_vec(&r)) {
+            discrepancy_stop = Some(iter + 1);
+            converged = true;
+        }
+        if monitor.halt(iter + 1, &residual) || converged {
+            break;
+        }
+        step.assign(&gamma_new / &gamma);
+        for (pi, si) in p.iter_mut().zip(&s) {
+            *pi *= &step;
+            *pi += si;
+        }
+        gamma = gamma_new;
+    }
+
+    SolveOutcome::finish(x, history, initial, converged)
+        .cancelled(&monitor)
+        .discrepancy_stop(discrepancy_stop)
+}
+
+/// Restarted GMRES(m) for a general square system A x = b
+pub fn gmres<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    restart: usize,
+    max_iters: usize,
+    tol: f64,
+) -> Vec<Float> {
+    let options = SolverOptions::new(max_iters).rel_tol(Float::with_val(b[0].prec(), tol));
+    gmres_with_info(a, b, restart, &options).x
+}
+
+/// [`gmres`] recording the residual norm ‖b - Ax‖ at every inner step.
+///
+/// Each cycle builds an Arnoldi basis of at most `restart` vectors
+/// (Gram-Schmidt repeated once, as in [`crate::krylov::arnoldi`]) and
+/// reduces the Hessenberg least-squares problem with Givens rotations, so
+/// the residual norm is known without forming x. Unlike CG on the normal
+/// equations, the condition number is not squared. `max_iters` counts
+/// inner steps over all cycles, and convergence is judged on the true
+/// residual at the end of a cycle.
+pub fn gmres_with_info<A: LinearOperator + ?Sized>(
+    a: &A,
+    b: &[Float],
+    restart: usize,
+    options: &SolverOptions,
+) -> SolveOutcome {
+    let n = a.rows();
+    assert_eq!(a.cols(), n, "GMRES requires a square operator");
+    assert_eq!(b.len(), n);
+    assert!(restart >= 1, "Restart length must be positive");
+    let precision = b[0].prec();
+    let start = Instant::now();
+    let mut history = Vec::new();
+    let (mut x, mut r) = options.start(a, b);
+    let mut residual_norm = RugMat::norm2_vec(&r);
+    let reference = if options.x0.is_some() {
+        RugMat::norm2_vec(b)
+    } else {
+        residual_norm.clone()
+    };
+    let target = options.target(&reference, precision);
+    let max_iters = options.max_iters;
+    let mut monitor = options.monitor();
+    let mut halted = false;
+
+    while history.len() < max_iters && residual_norm > target && !halted {
+        let beta = residual_norm.clone();
+        let mut basis: Vec<Vec<Float>> = vec![
+            r.iter()
+                .map(|ri| (ri / &beta).complete(precision))
+                .collect(),
+        ];
+        // Column j of the rotated Hessenberg, rows 0..=j, and the rotations
+        let mut h: Vec<Vec<Float>> = Vec::new();
+        let mut rotations: Vec<(Float, Float)> = Vec::new();
+        let mut g = vec![beta];
+
+        for j in 0..restart.min(max_iters - history.len()) {
+            let mut w = a.apply(&basis[j]);
+            let mut col = vec![Float::with_val(precision, 0); j + 2];
+            for _ in 0..2 {
+                for (i, vi) in basis.iter().enumerate() {
+                    let c = dot(vi, &w);
+                    for (wl, vl) in w.iter_mut().zip(vi) {
+                        *wl -= (&c * vl).complete(precision);
+                    }
+                    col[i] += c;
+                }
+            }
+            let norm = RugMat::norm2_vec(&w);
+            col[j + 1] = norm.clone();
+
+            for (i, (c, s)) in rotations.iter().enumerate() {
+                let upper =
+                    (c * &col[i]).complete(precision) + (s * &col[i + 1]).complete(precision);
+                let lower =
+                    (c * &col[i + 1]).complete(precision) - (s * &col[i]).complete(precision);
+                col[i] = upper;
+                col[i + 1] = lower;
+            }
+            let (c, s, rho) = givens(&col[j], &col[j + 1]);
+            col[j] = rho;
+            col.truncate(j + 1);
+            let gj = g[j].clone();
+            g.push(-(&s * &gj).complete(precision));
+            g[j] = (&c * &gj).complete(precision);
+            rotations.push((c, s));
+            h.push(col);
+
+            let residual = g[j + 1].clone().abs();
+            history.push(IterationRecord {
+                iteration: history.len() + 1,
+                residual: residual.clone(),
+                elapsed: start.elapsed(),
+            });
+            halted = monitor.halt(history.len(), &residual);
+            if norm.is_zero() || residual <= target || halted {
+                break;
+            }
+            basis.push(w.into_iter().map(|wl| wl / &norm).collect());
+        }
+
+        // Back substitution with the triangular factor, then x += V y
+        let k = h.len();
+        let mut y = g[..k].to_vec();
+        for i in (0..k).rev() {
+            for j in (i + 1)..k {
+                let update = (&h[j][i] * &y[j]).complete(precision);
+                y[i] -= update;
+            }
+            y[i] /= &h[i][i];
+        }
+        for (yj, vj) in y.iter().zip(&basis) {
+            for (xl, vl) in x.iter_mut().zip(vj) {
+                *xl += (yj * vl).complete(precision);
+            }
+        }
+
+        let ax = a.apply(&x);
+        r = b
+            .iter()
+            .zip(&ax)
+            .map(|(bi, axi)| (bi - axi).complete(precision))
+            .collect();
+        residual_norm = RugMat::norm2_vec(&r);
+    }
+
+    // Judge convergence by the true residual, not the rotated estimate
+    SolveOutcome {
+        x,
+        iterations: history.len(),
+        converged: residual_norm <= target,
+        cancelled: monitor.cancelled,
+        residual_norm,
+        residual_history: history,
+        escalations: Vec::new(),
+        discrepancy_stop: None,
+        breakdown: None,
+    }
+}
+
+/// How the solvers precondition A from the right before iterating
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scaling {
+    #[default]
+    None,
+    /// Scale by diag(1/‖aⱼ‖) and unscale the solution afterwards
+    ColumnNorms,
+}
+
+/// [`lsqr`] with optional right preconditioning by column norms
+pub fn lsqr_scaled(a: &RugMat, b: &[Float], max_iters: usize, scaling: Scaling) -> Vec<Float> {
+    match scaling {
+        Scaling::None => lsqr(a, b, max_iters),
+        Scaling::ColumnNorms => {
+            let scaled = ColumnScaled::by_column_norms(a);
+            scaled.unscale(&lsqr(&scaled, b, max_iters))
+        }
+    }
+}
+
+/// [`conjugate_gradient`] with optional right preconditioning by column norms
+pub fn conjugate_gradient_scaled(
+    a: &RugMat,
+    b: &[Float],
+    max_iters: usize,
+    scaling: Scaling,
+) -> Vec<Float> {
+    match scaling {
+        Scaling::None => conjugate_gradient(a, b, max_iters),
+        Scaling::ColumnNorms => {
+            let scaled = ColumnScaled::by_column_norms(a);
+            scaled.unscale(&conjugate_gradient(&scaled, b, max_iters))
+        }
+    }
+}
+
+/// Preconditioned CG on the normal equations AᵗA x = Aᵗb, with M ≈ AᵗA
+/// (for instance [`Jacobi::normal_equations`]).
+///
+/// Records the normal-equation residual ‖Aᵗ(b - Ax)‖ like
+/// [`conjugate_gradient_with_info`].
+pub fn conjugate_gradient_preconditioned<A, P>(
+    a: &A,
+    b: &[Float],
+    options: &SolverOptions,
+    precond: &P,
+) -> SolveOutcome
+where
+    A: LinearOperator + ?Sized,
+    P: Preconditioner + ?Sized,
+{
+    let precision = b[0].prec();
+    let start = Instant::now();
+    let mut history = Vec::new();
+    let mut monitor = options.monitor();
+    let (mut x, r0) = options.start(a, b);
+    let mut r = a.apply_transpose(&r0);
+    let initial = RugMat::norm2_vec(&r);
+    let reference = if options.x0.is_some() {
+        RugMat::norm2_vec(&a.apply_transpose(b))
+    } else {
+        initial.clone()
+    };
+    let target = options.target(&reference, precision);
+    let mut converged = initial <= target;
+    let mut z = precond.apply(&r);
+    let mut p = z.clone();
+    let mut rz = dot(&r, &z);
+
+    for iter in 0..options.max_iters {
+        if converged || rz.is_zero() {
+            break;
+        }
+        let q = a.apply_transpose(&a.apply(&p));
+        let pq = dot(&p, &q);
+        if pq.is_zero() {
+            break;
+        }
+        let alpha = (&rz / &pq).complete(precision);
+        for (xi, pi) in x.iter_mut().zip(&p) {
+            *xi += (&alpha * pi).complete(precision);
+        }
+        for (ri, qi) in r.iter_mut().zip(&q) {
+            *ri -= (&alpha * qi).complete(precision);
+        }
+        let residual = RugMat::norm2_vec(&r);
+        history.push(IterationRecord {
+            iteration: iter + 1,
+            residual: residual.clone(),
+            elapsed: start.elapsed(),
+        });
+        converged = residual <= target;
+        if monitor.halt(iter + 1, &residual) || converged {
+            break;
+        }
+        z = precond.apply(&r);
+        let rz_new = dot(&r, &z);
+        let beta = (&rz_new / &rz).complete(precision);
+        for (pi, zi) in p.iter_mut().zip(&z) {
+            *pi *= &beta;
+            *pi += zi;
+        }
+        rz = rz_new;
+    }
+    SolveOutcome::finish(x, history, initial, converged).cancelled(&monitor)
+}
+
+/// [`lsqr_with_info`] on the right preconditioned problem
+/// min ‖A M⁻¹ y - b‖, returning x = M⁻¹ y
+pub fn lsqr_preconditioned<A, P>(
+    a: &A,
+    b: &[Float],
+    options: &SolverOptions,
+    precond: &P,
+) -> SolveOutcome
+where
+    A: LinearOperator + ?Sized,
+    P: Preconditioner + ?Sized,
+{
+    solve_right_preconditioned(a, b, options, precond, |op, rhs, options| {
+        lsqr_with_info(op, rhs, options)
+    })
+}
+
+/// [`gmres_with_info`] on the right preconditioned system A M⁻¹ y = b,
+/// returning x = M⁻¹ y.
+///
+/// Right preconditioning leaves the residual b - Ax unchanged, so the
+/// tolerances keep their meaning.
+pub fn gmres_preconditioned<A, P>(
+    a: &A,
+    b: &[Float],
+    restart: usize,
+    options: &SolverOptions,
+    precond: &P,
+) -> SolveOutcome
+where
+    A: LinearOperator + ?Sized,
+    P: Preconditioner + ?Sized,
+{
+    solve_right_preconditioned(a, b, options, precond, |op, rhs, options| {
+        gmres_with_info(op, rhs, restart, options)
+    })
+}
+
+/// Run `solve` on A M⁻¹ y = b and return x = M⁻¹ y. A warm start would
+/// need y₀ = M x₀, which a preconditioner cannot form, so the correction
+/// A M⁻¹ y = b - Ax₀ is solved from zero instead and x = x₀ + M⁻¹ y.
+fn solve_right_preconditioned<A, P, S>(
+    a: &A,
+    b: &[Float],
+    options: &SolverOptions,
+    precond: &P,
+    solve: S,
+) -> SolveOutcome
+where
+    A: LinearOperator + ?Sized,
+    P: Preconditioner + ?Sized,
+    S: FnOnce(&RightPreconditioned<'_, A, P>, &[Float], &SolverOptions) -> SolveOutcome,
+{
+    let op = RightPreconditioned { op: a, precond };
+    if options.x0.is_none() {
+        let mut outcome = solve(&op, b, options);
+        outcome.x = op.recover(&outcome.x);
+        return outcome;
+    }
+    let precision = b[0].prec();
+    let (x0, r0) = options.start(a, b);
+    let correction =
+        options.for_correction(&RugMat::norm2_vec(b), &RugMat::norm2_vec(&r0), precision);
+    let mut outcome = solve(&op, &r0, &correction);
+    let dx = op.recover(&outcome.x);
+    outcome.x = x0.into_iter().zip(dx).map(|(xi, di)| xi + di).collect();
+    outcome
+}
+
+#[test]
+fn test_lsqr_overdetermined_least_squares() {
+    let precision = 128;
+    // Fit y = c0 + c1 t through (0, 1), (1, 2), (2, 4): normal equations give
+    // c0 = 5/6, c1 = 3/2
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 0)],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 2)],
+    ]);
+    let b = vec![
+        Float::with_val(precision, 1),
+        Float::with_val(precision, 2),
+        Float::with_val(precision, 4),
+    ];
+    let x = lsqr(&a, &b, 10);
+    assert!((x[0].to_f64() - 5.0 / 6.0).abs() < 1e-25);
+    assert!((x[1].to_f64() - 1.5).abs() < 1e-25);
+}
+
+#[test]
+fn test_column_scaled_lsqr_on_badly_scaled_columns() {
+    let precision = 128;
+    // Same fit as above with the second unknown measured in tiny units
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 0)],
+        vec![
+            Float::with_val(precision, 1),
+            Float::with_val(precision, 1e-12),
+        ],
+        vec![
+            Float::with_val(precision, 1),
+            Float::with_val(precision, 2e-12),
+        ],
+    ]);
+    let b = vec![
+        Float::with_val(precision, 1),
+        Float::with_val(precision, 2),
+        Float::with_val(precision, 4),
+    ];
+    let x = lsqr_scaled(&a, &b, 10, Scaling::ColumnNorms);
+    assert!((x[0].to_f64() - 5.0 / 6.0).abs() < 1e-25);
+    assert!((x[1].to_f64() / 1.5e12 - 1.0).abs() < 1e-25);
+}
+
+#[test]
+fn test_export_history_csv_and_json() {
+    let precision = 128;
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0, 4.0], precision);
+    let b = vec![Float::with_val(precision, 1); 3];
+    let result = lsqr_with_info(&a, &b, &SolverOptions::new(10));
+    assert_eq!(result.iterations, result.residual_history.len());
+    assert!(result.residual_history.last().unwrap().residual < 1e-30);
+
+    let dir = std::env::temp_dir();
+    let csv = dir.join(format!("rugmat_history_test_{}.csv", std::process::id()));
+    let json = dir.join(format!("rugmat_history_test_{}.json", std::process::id()));
+    result
+        .export_history(csv.to_str().unwrap(), HistoryFormat::Csv)
+        .unwrap();
+    result
+        .export_history(json.to_str().unwrap(), HistoryFormat::Json)
+        .unwrap();
+
+    let csv_text = std::fs::read_to_string(&csv).unwrap();
+    let mut lines = csv_text.lines();
+    assert_eq!(lines.next(), Some("iteration,residual,elapsed_s"));
+    assert_eq!(lines.count(), result.iterations);
+    let json_text = std::fs::read_to_string(&json).unwrap();
+    assert!(json_text.trim_start().starts_with('['));
+    assert_eq!(
+        json_text.matches("\"iteration\"").count(),
+        result.iterations
+    );
+    std::fs::remove_file(csv).unwrap();
+
+    // A NaN residual, e.g. after a breakdown, still gives valid JSON
+    let mut broken = result.clone();
+    broken.residual_history[0].residual = Float::with_val(precision, rug::float::Special::Nan);
+    broken
+        .export_history(json.to_str().unwrap(), HistoryFormat::Json)
+        .unwrap();
+    let json_text = std::fs::read_to_string(&json).unwrap();
+    assert!(json_text.contains("\"residual\": null"));
+    assert!(!json_text.contains("NaN"));
+    std::fs::remove_file(json).unwrap();
+}
+
+#[test]
+fn test_gmres_on_nonsymmetric_system() {
+    let precision = 128;
+    // Convection-diffusion style tridiagonal: far from symmetric
+    let n = 12;
+    let mut a = RugMat::new(n, n, precision);
+    for i in 0..n {
+        a[(i, i)] = Float::with_val(precision, 4);
+        if i + 1 < n {
+            a[(i, i + 1)] = Float::with_val(precision, -3);
+            a[(i + 1, i)] = Float::with_val(precision, 1);
+        }
+    }
+    let x_true: Vec<Float> = (0..n)
+        .map(|i| Float::with_val(precision, i as i32 - 5))
+        .collect();
+    let b = a.matmul_vec(&x_true);
+
+    // Full GMRES terminates in at most n steps, restarted GMRES takes longer
+    let tol = Float::with_val(precision, 1e-30);
+    let full = gmres_with_info(&a, &b, n, &SolverOptions::new(n).rel_tol(tol.clone()));
+    let restarted = gmres_with_info(&a, &b, 4, &SolverOptions::new(400).rel_tol(tol));
+    for res in [&full, &restarted] {
+        for (p, q) in res.x.iter().zip(&x_true) {
+            assert!((p.clone() - q).abs() < 1e-25);
+        }
+    }
+    assert!(restarted.iterations > full.iterations);
+    // The recorded residuals never increase
+    for pair in restarted.residual_history.windows(2) {
+        assert!(pair[1].residual <= pair[0].residual);
+    }
+}
+
+#[test]
+fn test_cgls_matches_qr_least_squares() {
+    let precision = 128;
+    let m = 9;
+    let mut a = RugMat::new(m, 3, precision);
+    let mut b = Vec::with_capacity(m);
+    for i in 0..m {
+        let t = Float::with_val(precision, i) / 4u32;
+        a[(i, 0)] = Float::with_val(precision, 1);
+        a[(i, 1)] = t.clone();
+        a[(i, 2)] = t.clone().square();
+        b.push(Float::with_val(precision, (i * 7) % 5));
+    }
+    let options = SolverOptions::new(50).rel_tol(Float::with_val(precision, 1e-30));
+    let res = cgls_with_info(&a, &b, &options);
+    let exact = a.qr().solve_least_squares(&b);
+    for (p, q) in res.x.iter().zip(&exact) {
+        assert!((p.clone() - q).abs() < 1e-25);
+    }
+    // Three unknowns: CG converges in three steps up to rounding
+    assert!(res.iterations <= 6);
+}
+
+#[test]
+fn test_preconditioned_solvers_on_badly_scaled_system() {
+    use crate::preconditioner::{Jacobi, Ssor};
+
+    let precision = 128;
+    // Diagonally dominant, nonsymmetric, rows scaled over eight decades
+    let n = 8;
+    let mut a = RugMat::new(n, n, precision);
+    for i in 0..n {
+        let s = 10f64.powi(i as i32);
+        a[(i, i)] = Float::with_val(precision, 4.0 * s);
+        if i + 1 < n {
+            a[(i, i + 1)] = Float::with_val(precision, -s);
+            a[(i + 1, i)] = Float::with_val(precision, 2.0 * s * 10.0);
+        }
+    }
+    let x_true: Vec<Float> = (0..n)
+        .map(|i| Float::with_val(precision, i as i32 - 3))
+        .collect();
+    let b = a.matmul_vec(&x_true);
+    let close = |x: &[Float]| {
+        x.iter()
+            .zip(&x_true)
+            .all(|(p, q)| (p.clone() - q).abs() < 1e-20)
+    };
+
+    let ssor = Ssor::new(&a, 1.0);
+    let jacobi = Jacobi::new(&a);
+    let options = SolverOptions::new(n);
+    assert!(close(&gmres_preconditioned(&a, &b, n, &options, &ssor).x));
+    assert!(close(&gmres_preconditioned(&a, &b, n, &options, &jacobi).x));
+    let options = options.max_iters(4 * n);
+    assert!(close(&lsqr_preconditioned(&a, &b, &options, &jacobi).x));
+    let normal = Jacobi::normal_equations(&a);
+    assert!(close(
+        &conjugate_gradient_preconditioned(&a, &b, &options, &normal).x
+    ));
+}
+
+#[test]
+fn test_solve_outcome_reports_convergence() {
+    let precision = 128;
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0, 4.0], precision);
+    let b = vec![Float::with_val(precision, 1); 3];
+    let alpha = Float::with_val(precision, 0.1);
+    let options = SolverOptions::new(10);
+    let outcomes = [
+        gradient_descent_with_info(&a, &b, &alpha, &SolverOptions::new(2000)),
+        conjugate_gradient_with_info(&a, &b, &options),
+        lsqr_with_info(&a, &b, &options),
+        cgls_with_info(&a, &b, &options),
+        gmres_with_info(&a, &b, 3, &options),
+    ];
+    for outcome in &outcomes {
+        assert!(outcome.converged);
+        assert_eq!(outcome.iterations, outcome.residual_history.len());
+        assert!(outcome.residual_norm < 1e-30);
+        let expected = [1.0, 0.5, 0.25];
+        for (xi, ei) in outcome.x.iter().zip(expected) {
+            assert!((xi.to_f64() - ei).abs() < 1e-12);
+        }
+    }
+
+    // Too few iterations to reach the tolerance
+    let truncated = gradient_descent_with_info(&a, &b, &alpha, &SolverOptions::new(5));
+    assert!(!truncated.converged);
+    assert_eq!(truncated.iterations, 5);
+}
+
+#[test]
+fn test_solver_options_tolerances_and_stagnation() {
+    let precision = 1024;
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0, 4.0], precision);
+    let b = vec![Float::with_val(precision, 1); 3];
+    let alpha = Float::with_val(precision, 0.1);
+    let run = |options: &SolverOptions| gradient_descent_with_info(&a, &b, &alpha, options);
+
+    // The default relative tolerance follows the 1024-bit working precision
+    let default = run(&SolverOptions::new(100_000));
+    assert!(default.converged);
+    assert!(default.residual_norm < 1e-300);
+
+    // A loose absolute tolerance stops far earlier, and it wins over the
+    // tighter relative one
+    let loose = run(&SolverOptions::new(100_000).abs_tol(Float::with_val(precision, 1e-6)));
+    assert!(loose.converged && loose.residual_norm <= 1e-6);
+    assert!(loose.iterations < default.iterations / 10);
+    let relative = run(&SolverOptions::new(100_000).rel_tol(Float::with_val(precision, 1e-6)));
+    assert!(relative.converged && relative.iterations < default.iterations / 10);
+
+    // A step of 2/σ₁² makes the largest mode oscillate without decaying,
+    // so the gradient levels off once the other modes are below rounding
+    let a = RugMat::diagonal_from_f64(&[1.0, 2.0, 4.0], 64);
+    let b = vec![Float::with_val(64, 1); 3];
+    let alpha = Float::with_val(64, 0.125);
+    let options = SolverOptions::new(100_000).stagnation(10);
+    let stuck = gradient_descent_with_info(&a, &b, &alpha, &options);
+    assert!(!stuck.converged);
+    assert!(stuck.iterations < 1000);
+}
+
+#[test]
+fn test_iteration_callback_reports_and_cancels() {
+    let precision = 128;
+    let n = 12;
+    let mut a = RugMat::new(n, n, precision);
+    for i in 0..n {
+        a[(i, i)] = Float::with_val(precision, 4 + i);
+        if i + 1 < n {
+            a[(i, i + 1)] = Float::with_val(precision, -2);
+            a[(i + 1, i)] = Float::with_val(precision, 1);
+        }
+    }
+    let b = vec![Float::with_val(precision, 1); n];
+
+    // Every reported residual is the one recorded in the history
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let options = SolverOptions::new(50).on_iteration(move |iter, residual| {
+        sink.lock().unwrap().push((iter, residual.clone()));
+        ControlFlow::Continue(())
+    });
+    let outcome = lsqr_with_info(&a, &b, &options);
+    assert!(outcome.converged && !outcome.cancelled);
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), outcome.iterations);
+    for ((iter, residual), rec) in seen.iter().zip(&outcome.residual_history) {
+        assert_eq!((*iter, residual), (rec.iteration, &rec.residual));
+    }
+
+    // Breaking out of GMRES keeps the progress made so far
+    let options = SolverOptions::new(50).on_iteration(|iter, _| {
+        if iter < 3 {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    });
+    let outcome = gmres_with_info(&a, &b, n, &options);
+    assert!(outcome.cancelled && !outcome.converged);
+    assert_eq!(outcome.iterations, 3);
+    assert!(outcome.residual_norm < RugMat::norm2_vec(&b));
+}
+
+#[test]
+fn test_warm_start_saves_iterations() {
+    use crate::preconditioner::Jacobi;
+
+    let precision = 64;
+    let n = 120;
+    let mut a = RugMat::new(n, n, precision);
+    for i in 0..n {
+        a[(i, i)] = Float::with_val(precision, 6 + i % 5);
+        if i + 1 < n {
+            a[(i, i + 1)] = Float::with_val(precision, -1);
+            a[(i + 1, i)] = Float::with_val(precision, -1);
+        }
+    }
+    let b: Vec<Float> = (0..n)
+        .map(|i| Float::with_val(precision, (i * 5) % 7) - 3u32)
+        .collect();
+
+    // Sweeping λ: the solution at λ₁ is a good guess at the nearby λ₂
+    let options = SolverOptions::new(200);
+    let lambda1 = Float::with_val(precision, 0.1);
+    let lambda2 = Float::with_val(precision, 0.11);
+    let first = cg_regularized_with_info(&a, &b, &lambda1, &options);
+    let cold = cg_regularized_with_info(&a, &b, &lambda2, &options);
+    let warm_options = options.clone().initial_guess(first.x.clone());
+    let warm = cg_regularized_with_info(&a, &b, &lambda2, &warm_options);
+    assert!(cold.converged && warm.converged);
+    assert!(warm.iterations < cold.iterations);
+    for (p, q) in warm.x.iter().zip(&cold.x) {
+        assert!((p.clone() - q).abs() < 1e-15);
+    }
+
+    // The other solvers accept the same guess and reach the same answer
+    let exact = conjugate_gradient_with_info(&a, &b, &options).x;
+    let guess: Vec<Float> = exact.iter().map(|v| Float::with_val(53, v)).collect();
+    let warm_options = options.clone().initial_guess(guess);
+    let jacobi = Jacobi::new(&a);
+    let outcomes = [
+        conjugate_gradient_with_info(&a, &b, &warm_options),
+        lsqr_with_info(&a, &b, &warm_options),
+        cgls_with_info(&a, &b, &warm_options),
+        gmres_with_info(&a, &b, n, &warm_options),
+        gmres_preconditioned(&a, &b, n, &warm_options, &jacobi),
+        lsqr_preconditioned(&a, &b, &warm_options, &jacobi),
+    ];
+    let cold_gmres = gmres_with_info(&a, &b, n, &options);
+    for outcome in &outcomes {
+        assert!(outcome.converged);
+        for (p, q) in outcome.x.iter().zip(&exact) {
+            assert!((p.clone() - q).abs() < 1e-15);
+        }
+    }
+    assert!(outcomes[3].iterations < cold_gmres.iterations);
+}
+
+#[test]
+fn test_cg_escalates_precision_past_a_plateau() {
+    let (n, precision) = (8, 64);
+    let mut a = RugMat::new(n, n, precision);
+    for j in 0..n {
+        for i in 0..n {
+            a[(i, j)] = Float::with_val(precision, i + j + 1).recip();
+        }
+    }
+    let b = a.matmul_vec(&vec![Float::with_val(precision, 1); n]);
+    // Out of reach at 64 bits, where κ(AᵗA) ≈ 10²⁰
+    let options = SolverOptions::new(500).rel_tol(Float::with_val(precision, 1e-30));
+
+    let fixed = conjugate_gradient_with_info(&a, &b, &options.clone().stagnation(10));
+    assert!(!fixed.converged);
+    let outcome = conjugate_gradient_escalating(&a, &b, &options, 512);
+    assert!(outcome.converged, "{:?}", outcome.escalations);
+    assert_eq!(outcome.escalations[0].from, precision);
+    assert!(outcome.x[0].prec() > precision);
+    let steps: Vec<usize> = outcome
+        .residual_history
+        .iter()
+        .map(|r| r.iteration)
+        .collect();
+    assert_eq!(steps, (1..=outcome.iterations).collect::<Vec<_>>());
+    // b carries 64-bit rounding, amplified by κ(A) ≈ 10¹⁰
+    for xi in &outcome.x {
+        assert!((xi.clone() - 1u32).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_discrepancy_principle_stops_before_fitting_noise() {
+    use crate::stochastic::gaussian_matrix;
+    use rug::ops::Pow;
+    // Singular values 10^(-j/2) and a solution satisfying the discrete
+    // Picard condition, with white noise of norm δ added to b
+    let (m, n, precision) = (40, 20, 128);
+    let u = gaussian_matrix(m, n, precision, 21).qr().q_thin();
+    let v = gaussian_matrix(n, n, precision, 22).qr().q_thin();
+    let sigma: Vec<Float> = (0..n)
+        .map(|j| Float::with_val(precision, 10).pow(-(j as f64) / 2.0))
+        .collect();
+    let mut us = u;
+    us.scale_cols(&sigma);
+    let a = us.matmul(&v.transpose());
+    let x_true = v.matmul_vec(&sigma);
+    let noise: Vec<Float> = gaussian_matrix(m, 1, precision, 23)
+        .as_slice()
+        .iter()
+        .map(|e| (e * 1e-4f64).complete(precision))
+        .collect();
+    let delta = RugMat::norm2_vec(&noise);
+    let b: Vec<Float> = a
+        .matmul_vec(&x_true)
+        .into_iter()
+        .zip(&noise)
+        .map(|(bi, e)| bi + e)
+        .collect();
+
+    let distance = |p: &[Float], q: &[Float]| {
+        let d: Vec<Float> = p
+            .iter()
+            .zip(q)
+            .map(|(pi, qi)| (pi - qi).complete(precision))
+            .collect();
+        RugMat::norm2_vec(&d).to_f64()
+    };
+    let scale = RugMat::norm2_vec(&x_true).to_f64();
+    let tight = SolverOptions::new(400).rel_tol(Float::with_val(precision, 1e-30));
+    let overfit = lsqr_with_info(&a, &b, &tight);
+    assert!(overfit.discrepancy_stop.is_none());
+    assert!(distance(&overfit.x, &x_true) > 10.0 * scale);
+
+    let level = Float::with_val(precision, 1.01) * &delta;
+    let options = tight.discrepancy(level.clone());
+    let outcomes = [
+        lsqr_with_info(&a, &b, &options),
+        cgls_with_info(&a, &b, &options),
+        conjugate_gradient_with_info(&a, &b, &options),
+    ];
+    for outcome in &outcomes {
+        assert!(outcome.converged);
+        assert_eq!(outcome.discrepancy_stop, Some(outcome.iterations));
+        assert!(outcome.iterations < overfit.iterations);
+        let misfit = distance(&a.matmul_vec(&outcome.x), &b);
+        assert!(misfit <= level.to_f64() * (1.0 + 1e-20));
+        let error = distance(&outcome.x, &x_true);
+        assert!(error < 0.2 * scale, "error {} for ‖x‖ = {}", error, scale);
+    }
+    // LSQR and CGLS build the same Krylov iterates
+    assert_eq!(outcomes[0].iterations, outcomes[1].iterations);
+}
+
+#[test]
+fn test_cg_reports_breakdown() {
+    // Aᵗ maps b to a nonzero gradient whose direction A annihilates
+    struct Annihilating;
+    impl LinearOperator for Annihilating {
+        fn rows(&self) -> usize {
+            2
+        }
+        fn cols(&self) -> usize {
+            2
+        }
+        fn apply(&self, x: &[Float]) -> Vec<Float> {
+            vec![Float::with_val(x[0].prec(), 0); 2]
+        }
+        fn apply_transpose(&self, x: &[Float]) -> Vec<Float> {
+            x.to_vec()
+        }
+    }
+    let precision = 128;
+    let b = vec![Float::with_val(precision, 1); 2];
+    let outcome = conjugate_gradient_with_info(&Annihilating, &b, &SolverOptions::new(10));
+    assert_eq!(outcome.breakdown, Some(0));
+    assert!(!outcome.converged);
+    assert_eq!(outcome.iterations, 0);
+    assert!(outcome.x.iter().all(|xi| xi.is_zero()));
+
+    let healthy =
+        conjugate_gradient_with_info(&RugMat::identity(2, precision), &b, &SolverOptions::new(10));
+    assert!(healthy.converged && healthy.breakdown.is_none());
+}
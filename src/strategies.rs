@@ -0,0 +1,159 @@
+// strategies.rs: proptest generators for RugMats and checks of algebraic identities
+//! Enabled by the `proptest` feature. The strategies produce matrices of
+//! random shape, precision and conditioning; the `check_*` functions assert
+//! identities with tolerances scaled to the working precision, and return
+//! [`TestCaseError`] so they compose with `?` inside `proptest!` blocks.
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use rugmat::strategies::{check_transpose_of_product, multipliable_pair};
+//!
+//! proptest!(ProptestConfig::with_cases(8), |((a, b) in multipliable_pair(4))| {
+//!     check_transpose_of_product(&a, &b)?;
+//! });
+//! ```
+use crate::RugMat;
+use crate::svd::working_tol;
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use rug::Float;
+use rug::ops::Pow;
+
+/// Precisions drawn by [`precision`], from the 53 bits of an f64 upwards
+pub const PRECISIONS: [u32; 4] = [53, 64, 128, 256];
+
+pub fn precision() -> impl Strategy<Value = u32> {
+    prop::sample::select(PRECISIONS.to_vec())
+}
+
+/// rows×cols matrix with entries uniform in [-1, 1], drawn as f64
+pub fn matrix_with(rows: usize, cols: usize, precision: u32) -> impl Strategy<Value = RugMat> {
+    prop::collection::vec(-1.0f64..=1.0, rows * cols).prop_map(move |entries| {
+        let data = entries
+            .into_iter()
+            .map(|v| Float::with_val(precision, v))
+            .collect();
+        RugMat::from_col_major(rows, cols, data)
+    })
+}
+
+/// Matrix of random shape up to max_dim×max_dim and random precision
+pub fn matrix(max_dim: usize) -> impl Strategy<Value = RugMat> {
+    (1..=max_dim, 1..=max_dim, precision()).prop_flat_map(|(m, n, p)| matrix_with(m, n, p))
+}
+
+/// (A, B) at a common precision with A's columns matching B's rows
+pub fn multipliable_pair(max_dim: usize) -> impl Strategy<Value = (RugMat, RugMat)> {
+    (1..=max_dim, 1..=max_dim, 1..=max_dim, precision())
+        .prop_flat_map(|(m, k, n, p)| (matrix_with(m, k, p), matrix_with(k, n, p)))
+}
+
+/// n×n matrix U diag(σ) Vᵗ with random orthogonal U, V and σ spread
+/// geometrically from 1 down to 10^-log10_cond, so κ₂ = 10^log10_cond
+pub fn conditioned(n: usize, log10_cond: f64, precision: u32) -> impl Strategy<Value = RugMat> {
+    (matrix_with(n, n, precision), matrix_with(n, n, precision)).prop_map(move |(g, h)| {
+        let mut u = g.qr().q_thin();
+        let v = h.qr().q_thin();
+        let sigma: Vec<Float> = (0..n)
+            .map(|j| {
+                let exponent = -log10_cond * j as f64 / (n - 1).max(1) as f64;
+                Float::with_val(precision, 10).pow(exponent)
+            })
+            .collect();
+        u.scale_cols(&sigma);
+        u.matmul(&v.transpose())
+    })
+}
+
+/// Square matrix up to max_dim with condition number up to 10^max_log10_cond
+pub fn square(max_dim: usize, max_log10_cond: f64) -> impl Strategy<Value = RugMat> {
+    (1..=max_dim, 0.0..=max_log10_cond, precision()).prop_flat_map(|(n, c, p)| conditioned(n, c, p))
+}
+
+fn precision_of(a: &RugMat) -> u32 {
+    a.as_slice()[0].prec()
+}
+
+/// (AB)ᵗ = BᵗAᵗ up to the rounding of the two products
+pub fn check_transpose_of_product(a: &RugMat, b: &RugMat) -> Result<(), TestCaseError> {
+    let lhs = a.matmul(b).transpose();
+    let rhs = b.transpose().matmul(&a.transpose());
+    let k = a.ncols() as f64;
+    let tol = a.frobenius_norm() * b.frobenius_norm() * (2.0 * k * working_tol(precision_of(a)));
+    let err = lhs.compare(&rhs).max_abs;
+    prop_assert!(err <= tol, "‖(AB)ᵗ - BᵗAᵗ‖ = {} exceeds {}", err, tol);
+    Ok(())
+}
+
+/// A·A⁻¹ ≈ I with the residual bounded by n·u·‖A‖‖A⁻¹‖, the accuracy a
+/// backward stable inverse attains
+pub fn check_inverse(a: &RugMat) -> Result<(), TestCaseError> {
+    prop_assert!(a.is_square(), "A must be square, got {}", a.shape());
+    let n = a.nrows();
+    let precision = precision_of(a);
+    let inv = a.lu_decompose_pivot().inverse();
+    let bound = a.frobenius_norm() * inv.frobenius_norm();
+    let tol = bound * (8.0 * n as f64 * working_tol(precision));
+    let err = a
+        .matmul(&inv)
+        .compare(&RugMat::identity(n, precision))
+        .max_abs;
+    prop_assert!(err <= tol, "‖A·A⁻¹ - I‖ = {} exceeds {}", err, tol);
+    Ok(())
+}
+
+/// ‖AB‖ ≤ ‖A‖‖B‖ in the Frobenius and 1-norms, and ‖Aᵗ‖₁ = ‖A‖∞
+pub fn check_norm_inequalities(a: &RugMat, b: &RugMat) -> Result<(), TestCaseError> {
+    let precision = precision_of(a);
+    // Relative rounding allowance; 1 + u is not representable as an f64
+    let rounding = 4.0 * a.ncols() as f64 * working_tol(precision);
+    let slack = Float::with_val(2 * precision, 1) + rounding;
+    let ab = a.matmul(b);
+    let frobenius = a.frobenius_norm() * b.frobenius_norm() * &slack;
+    prop_assert!(ab.frobenius_norm() <= frobenius, "‖AB‖_F > ‖A‖_F‖B‖_F");
+    prop_assert!(
+        ab.norm1() <= a.norm1() * b.norm1() * &slack,
+        "‖AB‖₁ > ‖A‖₁‖B‖₁"
+    );
+    let transposed = a.transpose().norm1();
+    let inf = a.norm_inf();
+    prop_assert!(
+        (transposed.clone() - &inf).abs() <= inf.clone() * rounding,
+        "‖Aᵗ‖₁ = {} but ‖A‖∞ = {}",
+        transposed,
+        inf
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn prop_transpose_of_product((a, b) in multipliable_pair(6)) {
+        check_transpose_of_product(&a, &b)?;
+    }
+
+    #[test]
+    fn prop_inverse(a in square(6, 6.0)) {
+        check_inverse(&a)?;
+    }
+
+    #[test]
+    fn prop_norm_inequalities((a, b) in multipliable_pair(6)) {
+        check_norm_inequalities(&a, &b)?;
+    }
+
+    #[test]
+    fn prop_conditioned_has_requested_condition(
+        (a, c) in (2usize..6, 0.0f64..8.0)
+            .prop_flat_map(|(n, c)| (conditioned(n, c, 128), Just(c)))
+    ) {
+        let s = a.singular_values(1e-30);
+        let largest = s.iter().max_by(|p, q| p.total_cmp(q)).unwrap();
+        let smallest = s.iter().min_by(|p, q| p.total_cmp(q)).unwrap();
+        let cond = (largest.clone() / smallest).log10().to_f64();
+        prop_assert!((cond - c).abs() < 1e-6, "κ = 10^{} instead of 10^{}", cond, c);
+    }
+}
@@ -0,0 +1,291 @@
+// structured.rs: Givens-based kernels for small structured subproblems of Krylov methods
+use crate::RugMat;
+use crate::qr::back_substitute;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Plane rotation (c, s, r) with c·y + s·z = r and c·z - s·y = 0
+pub fn givens(y: &Float, z: &Float) -> (Float, Float, Float) {
+    let precision = y.prec();
+    let r = Float::with_val(precision, y.hypot_ref(z));
+    if r.is_zero() {
+        return (
+            Float::with_val(precision, 1),
+            Float::with_val(precision, 0),
+            r,
+        );
+    }
+    (
+        (y / &r).complete(precision),
+        (z / &r).complete(precision),
+        r,
+    )
+}
+
+/// Columns p < q of a column-major buffer: (x_p, x_q) ← (c x_p - s x_q, s x_p + c x_q)
+pub(crate) fn rotate_columns(
+    data: &mut [Float],
+    rows: usize,
+    p: usize,
+    q: usize,
+    c: &Float,
+    s: &Float,
+) {
+    let (left, right) = data.split_at_mut(q * rows);
+    let col_p = &mut left[p * rows..(p + 1) * rows];
+    let col_q = &mut right[..rows];
+    for (xp, xq) in col_p.iter_mut().zip(col_q.iter_mut()) {
+        let new_p = (c * &*xp).complete(xp.prec()) - (s * &*xq).complete(xq.prec());
+        let new_q = (s * &*xp).complete(xp.prec()) + (c * &*xq).complete(xq.prec());
+        *xp = new_p;
+        *xq = new_q;
+    }
+}
+
+/// Apply the rotation from [`givens`] to the pair (x, y) in place
+fn rotate_pair(x: &mut Float, y: &mut Float, c: &Float, s: &Float) {
+    let precision = x.prec();
+    let new_x = (c * &*x).complete(precision) + (s * &*y).complete(precision);
+    let new_y = (c * &*y).complete(precision) - (s * &*x).complete(precision);
+    *x = new_x;
+    *y = new_y;
+}
+
+/// Least-squares solution of min ‖rhs - H y‖ for a (k+1)×k upper Hessenberg H,
+/// as in GMRES. Returns y and the residual norm.
+pub fn hessenberg_least_squares(h: &RugMat, rhs: &[Float]) -> (Vec<Float>, Float) {
+    let k = h.ncols();
+    assert_eq!(h.nrows(), k + 1, "Expected a (k+1)×k Hessenberg matrix");
+    assert_eq!(rhs.len(), k + 1);
+    let mut r = h.clone();
+    let mut g = rhs.to_vec();
+
+    for j in 0..k {
+        let (c, s, rho) = givens(&r[(j, j)], &r[(j + 1, j)]);
+        r[(j + 1, j)] = Float::with_val(rho.prec(), 0);
+        r[(j, j)] = rho;
+        for col in j + 1..k {
+            let (top, bottom) =
+                r.as_mut_slice()[col * (k + 1)..(col + 1) * (k + 1)].split_at_mut(j + 1);
+            rotate_pair(&mut top[j], &mut bottom[0], &c, &s);
+        }
+        let (top, bottom) = g.split_at_mut(j + 1);
+        rotate_pair(&mut top[j], &mut bottom[0], &c, &s);
+    }
+
+    let residual = g[k].clone().abs();
+    (back_substitute(&r, &g[..k]), residual)
+}
+
+/// Solve a general tridiagonal system by Givens QR, stable without pivoting
+/// even when the matrix is indefinite. `lower` and `upper` have length n-1.
+pub fn tridiagonal_solve(
+    lower: &[Float],
+    diag: &[Float],
+    upper: &[Float],
+    rhs: &[Float],
+) -> Vec<Float> {
+    let n = diag.len();
+    assert_eq!(rhs.len(), n);
+    assert!(lower.len() + 1 == n && upper.len() + 1 == n);
+    let precision = diag[0].prec();
+
+    // R has bands r0 (diagonal), r1 and r2 (two superdiagonals)
+    let mut r0 = diag.to_vec();
+    let mut r1: Vec<Float> = upper.to_vec();
+    r1.push(Float::with_val(precision, 0));
+    let mut r2 = vec![Float::with_val(precision, 0); n];
+    let mut b = rhs.to_vec();
+    for k in 0..n - 1 {
+        let (c, s, rho) = givens(&r0[k], &lower[k]);
+        r0[k] = rho;
+        // Row k+1 holds (lower_k, diag_{k+1}, upper_{k+1}) before the rotation
+        rotate_pair(&mut r1[k], &mut r0[k + 1], &c, &s);
+        rotate_pair(&mut r2[k], &mut r1[k + 1], &c, &s);
+        let (lo, hi) = b.split_at_mut(k + 1);
+        rotate_pair(&mut lo[k], &mut hi[0], &c, &s);
+    }
+    assert!(r0.iter().all(|x| !x.is_zero()), "Matrix is singular");
+
+    for i in (0..n).rev() {
+        if i + 1 < n {
+            let t = (&r1[i] * &b[i + 1]).complete(precision);
+            b[i] -= t;
+        }
+        if i + 2 < n {
+            let t = (&r2[i] * &b[i + 2]).complete(precision);
+            b[i] -= t;
+        }
+        b[i] /= &r0[i];
+    }
+    b
+}
+
+/// Eigen-decomposition T = Z Λ Zᵗ of a symmetric tridiagonal matrix by
+/// implicit-shift QR with Wilkinson shifts.
+///
+/// `off` is the subdiagonal (length n-1). An off-diagonal entry is dropped
+/// once |eᵢ| <= tol·(|dᵢ| + |dᵢ₊₁|); `max_iters` bounds the total number of
+/// QR sweeps. Returns the eigenvalues in ascending order and Z with the
+/// matching eigenvectors as columns. Panics if some off-diagonal entry is
+/// still above the tolerance after `max_iters` sweeps.
+pub fn symmetric_tridiagonal_eigen(
+    diag: &[Float],
+    off: &[Float],
+    max_iters: usize,
+    tol: f64,
+) -> (Vec<Float>, RugMat) {
+    let n = diag.len();
+    assert_eq!(off.len(), n.saturating_sub(1));
+    let precision = diag.first().map_or(64, |d| d.prec());
+    let tol = Float::with_val(precision, tol);
+    let mut d = diag.to_vec();
+    let mut e = off.to_vec();
+    let mut z = RugMat::identity(n, precision);
+    if n == 0 {
+        return (d, z);
+    }
+
+    let mut sweeps = 0;
+    loop {
+        for i in 0..n - 1 {
+            let scale = Float::with_val(precision, d[i].abs_ref())
+                + Float::with_val(precision, d[i + 1].abs_ref());
+            if Float::with_val(precision, e[i].abs_ref()) <= tol.clone() * scale {
+                e[i] = Float::with_val(precision, 0);
+            }
+        }
+        let mut q = n - 1;
+        while q > 0 && e[q - 1].is_zero() {
+            q -= 1;
+        }
+        if q == 0 {
+            break;
+        }
+        assert!(
+            sweeps < max_iters,
+            "Tridiagonal QR did not converge within {} sweeps",
+            max_iters
+        );
+        sweeps += 1;
+        let mut p = q - 1;
+        while p > 0 && !e[p - 1].is_zero() {
+            p -= 1;
+        }
+
+        // Wilkinson shift: eigenvalue of the trailing 2×2 closer to d_q
+        let delta: Float = (&d[q - 1] - &d[q]).complete(precision) / 2u32;
+        let root = Float::with_val(precision, delta.hypot_ref(&e[q - 1]));
+        let denom = if delta.is_sign_negative() {
+            delta - root
+        } else {
+            delta + root
+        };
+        let mu = if denom.is_zero() {
+            d[q].clone()
+        } else {
+            d[q].clone() - e[q - 1].clone().square() / denom
+        };
+
+        let mut x = (&d[p] - &mu).complete(precision);
+        let mut bulge = e[p].clone();
+        for k in p..q {
+            let (c, s, r) = givens(&x, &bulge);
+            if k > p {
+                e[k - 1] = r;
+            }
+            let cs = (&c * &s).complete(precision);
+            let (c2, s2) = (c.clone().square(), s.clone().square());
+            let two_csb: Float = (&cs * &e[k]).complete(precision) * 2u32;
+            let a = d[k].clone();
+            let b = d[k + 1].clone();
+            d[k] = (&c2 * &a).complete(precision) + &two_csb + (&s2 * &b).complete(precision);
+            d[k + 1] = (&s2 * &a).complete(precision) - &two_csb + (&c2 * &b).complete(precision);
+            e[k] = cs * (b - a) + (c2 - s2) * &e[k];
+            rotate_columns(z.as_mut_slice(), n, k, k + 1, &c, &-s.clone());
+            if k + 1 < q {
+                bulge = (&s * &e[k + 1]).complete(precision);
+                e[k + 1] *= &c;
+                x = e[k].clone();
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| d[a].partial_cmp(&d[b]).unwrap());
+    let mut vectors = RugMat::new(n, n, precision);
+    for (k, &j) in order.iter().enumerate() {
+        vectors.as_mut_slice()[k * n..(k + 1) * n]
+            .clone_from_slice(&z.as_slice()[j * n..(j + 1) * n]);
+    }
+    (order.into_iter().map(|j| d[j].clone()).collect(), vectors)
+}
+
+#[test]
+fn test_hessenberg_least_squares_matches_qr() {
+    let precision = 128;
+    let h = RugMat::from_vecvec(
+        [[2, 1, 3], [1, -1, 2], [0, 4, 1], [0, 0, 2]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let rhs: Vec<Float> = [1, 0, 0, 0]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let (y, residual) = hessenberg_least_squares(&h, &rhs);
+    let y_ref = h.qr().solve_least_squares(&rhs);
+    for (a, b) in y.iter().zip(&y_ref) {
+        assert!((a.clone() - b).abs() < 1e-30);
+    }
+    let r: Vec<Float> = h
+        .matmul_vec(&y)
+        .iter()
+        .zip(&rhs)
+        .map(|(p, q)| (p - q).complete(precision))
+        .collect();
+    assert!((RugMat::norm2_vec(&r) - residual).abs() < 1e-30);
+}
+
+#[test]
+fn test_tridiagonal_solve_and_eigen() {
+    let precision = 128;
+    let n = 6;
+    let f = |v: i32| Float::with_val(precision, v);
+    // 1-D Laplacian tridiag(-1, 2, -1)
+    let diag = vec![f(2); n];
+    let off = vec![f(-1); n - 1];
+
+    let x_true: Vec<Float> = (0..n as i32).map(|i| f(i - 2)).collect();
+    let mut rhs = Vec::with_capacity(n);
+    for i in 0..n {
+        let mut v = (&diag[i] * &x_true[i]).complete(precision);
+        if i > 0 {
+            v -= &x_true[i - 1];
+        }
+        if i + 1 < n {
+            v -= &x_true[i + 1];
+        }
+        rhs.push(v);
+    }
+    let x = tridiagonal_solve(&off, &diag, &off, &rhs);
+    for (a, b) in x.iter().zip(&x_true) {
+        assert!((a.clone() - b).abs() < 1e-30);
+    }
+
+    // Eigenvalues 2 - 2cos(kπ/(n+1)), k = 1..n
+    let (values, vectors) = symmetric_tridiagonal_eigen(&diag, &off, 200, 1e-36);
+    let pi = Float::with_val(precision, rug::float::Constant::Pi);
+    for (k, lambda) in values.iter().enumerate() {
+        let angle = (&pi * (k as u32 + 1)).complete(precision) / (n as u32 + 1);
+        let expected: Float = 2 - angle.cos() * 2u32;
+        assert!((lambda.clone() - expected).abs() < 1e-30);
+    }
+    let id = RugMat::identity(n, precision);
+    assert!(vectors.transpose().matmul(&vectors).compare(&id).max_abs < 1e-30);
+
+    let (values, vectors) = symmetric_tridiagonal_eigen(&[], &[], 200, 1e-36);
+    assert!(values.is_empty());
+    assert_eq!((vectors.nrows(), vectors.ncols()), (0, 0));
+}
@@ -0,0 +1,1169 @@
+// svd.rs: singular value decompositions for RugMat
+use crate::RugMat;
+use crate::qr::{apply_reflector, householder_in_place};
+use crate::rugmat::{SVD, dot};
+use crate::stochastic::gaussian_matrix;
+use crate::structured::{givens, rotate_columns};
+use rayon::prelude::*;
+use rug::Float;
+use rug::float::Special;
+use rug::ops::CompleteRound;
+
+/// Sweep limit used when a Jacobi SVD is needed internally
+pub(crate) const JACOBI_SWEEPS: usize = 60;
+
+/// Relative orthogonality tolerance matching the working precision
+pub(crate) fn working_tol(precision: u32) -> f64 {
+    2f64.powi(1 - precision as i32).max(f64::MIN_POSITIVE)
+}
+
+/// QR sweeps allowed per singular value when the caller gives no limit
+const QR_SWEEPS_PER_VALUE: usize = 30;
+
+/// Minimum-norm least-squares solution together with the rank decision
+#[derive(Debug, Clone)]
+pub struct MinNormSolution {
+    pub x: Vec<Float>,
+    /// Number of singular values kept
+    pub rank: usize,
+    /// Absolute cutoff tol·σ₀ below which singular values were treated as zero
+    pub threshold: Float,
+}
+
+/// Algorithm used by [`RugMat::svd_with_method`]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvdMethod {
+    /// One-sided (Hestenes) Jacobi. Slower, but tiny singular values are
+    /// computed to high relative accuracy.
+    Jacobi,
+    /// Householder bidiagonalization followed by implicit-shift QR on the
+    /// bidiagonal. The standard choice; errors are relative to ‖A‖.
+    GolubKahan,
+}
+
+impl RugMat {
+    /// Compute the thin SVD A = U Σ Vᵗ with the selected algorithm
+    pub fn svd_with_method(&self, method: SvdMethod, max_iters: usize, tol: f64) -> SVD {
+        match method {
+            SvdMethod::Jacobi => self.svd_jacobi(max_iters, tol),
+            SvdMethod::GolubKahan => self.svd(max_iters, tol),
+        }
+    }
+
+    /// Golub-Kahan bidiagonalization A = U B Vᵗ by Householder reflections.
+    ///
+    /// For m >= n, B is n×n upper bidiagonal, U is m×n with orthonormal
+    /// columns and V is n×n orthogonal. For m < n the transpose is reduced,
+    /// so B is m×m lower bidiagonal, U is m×m and V is n×m. U and V are
+    /// only formed when accumulate is set.
+    pub fn bidiagonalize(&self, accumulate: bool) -> (Option<RugMat>, RugMat, Option<RugMat>) {
+        if self.nrows() < self.ncols() {
+            let (u, b, v) = self.transpose().bidiagonalize(accumulate);
+            return (v, b.transpose(), u);
+        }
+        let bd = Bidiagonal::new(self);
+        if accumulate {
+            (Some(bd.u_thin()), bd.b(), Some(bd.v()))
+        } else {
+            (None, bd.b(), None)
+        }
+    }
+
+    /// Singular values only, in descending order.
+    ///
+    /// Same bidiagonalization and QR iteration as [`RugMat::svd`] with the
+    /// same meaning of tol, but U and V are never accumulated, which saves
+    /// most of the work. Panics if the QR iteration does not converge.
+    pub fn singular_values(&self, tol: f64) -> Vec<Float> {
+        if self.nrows() < self.ncols() {
+            return self.transpose().singular_values(tol);
+        }
+        let mut bd = Bidiagonal::new(self);
+        let max_iters = QR_SWEEPS_PER_VALUE * self.ncols();
+        let converged = bd.qr_iterate(None, None, max_iters, tol);
+        assert!(
+            converged,
+            "Bidiagonal QR did not converge within {} sweeps",
+            max_iters
+        );
+        let mut s = bd.d;
+        s.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        s
+    }
+
+    /// The k largest singular triplets by Golub-Kahan-Lanczos
+    /// bidiagonalization with full reorthogonalization.
+    ///
+    /// Only products with A and Aᵗ touch the matrix. After each step the
+    /// small bidiagonal Bⱼ is decomposed; a triplet has converged once
+    /// its residual ‖Aᵗu - σv‖ = βⱼ|pⱼᵢ| is at most tol·σ₁, with pⱼᵢ the last
+    /// entry of the left singular vector of Bⱼ. Returns U (m×k), Σ (k,
+    /// descending) and Vᵗ (k×n).
+    pub fn svd_partial(&self, k: usize, tol: f64) -> SVD {
+        let (m, n) = (self.nrows(), self.ncols());
+        let max_steps = m.min(n);
+        assert!(k >= 1 && k <= max_steps, "Rank must be in 1..=min(m, n)");
+        let precision = self.as_slice()[0].prec();
+        let tol = Float::with_val(precision, tol);
+        let at = self.transpose();
+
+        let orthonormalize = |w: &mut Vec<Float>, basis: &[Vec<Float>]| {
+            for _ in 0..2 {
+                for b in basis {
+                    let c = dot(b, w);
+                    for (wi, bi) in w.iter_mut().zip(b) {
+                        *wi -= (&c * bi).complete(precision);
+                    }
+                }
+            }
+            let norm = RugMat::norm2_vec(w);
+            if !norm.is_zero() {
+                for wi in w.iter_mut() {
+                    *wi /= &norm;
+                }
+            }
+            norm
+        };
+
+        let mut v0: Vec<Float> = (1..=n).map(|i| Float::with_val(precision, i)).collect();
+        orthonormalize(&mut v0, &[]);
+        let mut vs = vec![v0];
+        let mut us: Vec<Vec<Float>> = Vec::new();
+        let mut alpha: Vec<Float> = Vec::new();
+        let mut beta: Vec<Float> = Vec::new();
+
+        loop {
+            let j = us.len();
+            let mut u = self.matmul_vec(&vs[j]);
+            alpha.push(orthonormalize(&mut u, &us));
+            us.push(u);
+            let mut v = at.matmul_vec(&us[j]);
+            let b = orthonormalize(&mut v, &vs);
+            let steps = j + 1;
+
+            if steps >= k {
+                let mut bmat = RugMat::new(steps, steps, precision);
+                for i in 0..steps {
+                    bmat[(i, i)] = alpha[i].clone();
+                    if i + 1 < steps {
+                        bmat[(i, i + 1)] = beta[i].clone();
+                    }
+                }
+                let small = bmat.svd(QR_SWEEPS_PER_VALUE * steps, working_tol(precision));
+                let scale = (&tol * &small.s[0]).complete(precision);
+                let converged = (0..k)
+                    .all(|i| (&b * &small.u[(steps - 1, i)]).complete(precision).abs() <= scale);
+                let invariant = b.is_zero() || alpha[j].is_zero();
+                if converged || invariant || steps == max_steps {
+                    let mut u = RugMat::new(m, k, precision);
+                    let mut vt = RugMat::new(k, n, precision);
+                    for c in 0..k {
+                        for (r, ur) in us.iter().enumerate() {
+                            let p = &small.u[(r, c)];
+                            for i in 0..m {
+                                u[(i, c)] += (p * &ur[i]).complete(precision);
+                            }
+                        }
+                        for (r, vr) in vs.iter().take(steps).enumerate() {
+                            let q = &small.vt[(c, r)];
+                            for i in 0..n {
+                                vt[(c, i)] += (q * &vr[i]).complete(precision);
+                            }
+                        }
+                    }
+                    return SVD {
+                        u,
+                        s: small.s[..k].to_vec(),
+                        vt,
+                    };
+                }
+            }
+            beta.push(b);
+            vs.push(v);
+        }
+    }
+
+    /// Rank-k truncated SVD by randomized range finding (Halko, Martinsson
+    /// and Tropp).
+    ///
+    /// A Gaussian sketch with k + oversampling columns seeded by `seed` is
+    /// multiplied by A, refined by `power_iters` rounds of (AAᵗ)
+    /// re-orthonormalized with QR, and the small projection QᵗA is
+    /// decomposed densely. Each power iteration sharpens the decay of the
+    /// spectrum seen by the sketch; for matrices of numerical rank near k
+    /// the result matches the leading part of [`RugMat::svd`].
+    pub fn randomized_svd(
+        &self,
+        k: usize,
+        oversampling: usize,
+        power_iters: usize,
+        seed: u64,
+    ) -> SVD {
+        let precision = self.as_slice()[0].prec();
+        let l = (k + oversampling).min(self.nrows().min(self.ncols()));
+        assert!(k >= 1 && k <= l, "Rank must be in 1..=min(m, n)");
+        let at = self.transpose();
+
+        let omega = gaussian_matrix(self.ncols(), l, precision, seed);
+        let mut q = self.matmul(&omega).qr().q_thin();
+        for _ in 0..power_iters {
+            let z = at.matmul(&q).qr().q_thin();
+            q = self.matmul(&z).qr().q_thin();
+        }
+
+        let b = q.transpose().matmul(self);
+        let small = b.svd(QR_SWEEPS_PER_VALUE * l, working_tol(precision));
+        let u = q.matmul(&small.u);
+        SVD {
+            u: RugMat::from_col_major(self.nrows(), k, u.as_slice()[..self.nrows() * k].to_vec()),
+            s: small.s[..k].to_vec(),
+            vt: RugMat::from_col_major(
+                k,
+                self.ncols(),
+                (0..self.ncols())
+                    .flat_map(|j| small.vt.as_slice()[j * l..j * l + k].iter().cloned())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Thin SVD A = U Σ Vᵗ by Golub-Kahan bidiagonalization and implicit-shift QR.
+    ///
+    /// A superdiagonal entry is dropped once |eᵢ| <= tol·(|dᵢ| + |dᵢ₊₁|);
+    /// `max_iters` bounds the total number of QR sweeps, and running out of
+    /// them panics rather than returning an unconverged factorization.
+    /// Returns U (m×k), Σ (k, descending) and Vᵗ (k×n) with k = min(m, n).
+    pub fn svd(&self, max_iters: usize, tol: f64) -> SVD {
+        if self.nrows() < self.ncols() {
+            let svd = self.transpose().svd(max_iters, tol);
+            return SVD {
+                u: svd.vt.transpose(),
+                s: svd.s,
+                vt: svd.u.transpose(),
+            };
+        }
+
+        let precision = self.as_slice()[0].prec();
+        let (m, n) = (self.nrows(), self.ncols());
+        let mut bd = Bidiagonal::new(self);
+        let mut u = bd.u_thin();
+        let mut v = bd.v();
+        let converged = bd.qr_iterate(Some(&mut u), Some(&mut v), max_iters, tol);
+        assert!(
+            converged,
+            "Bidiagonal QR did not converge within {} sweeps",
+            max_iters
+        );
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| bd.d[b].partial_cmp(&bd.d[a]).unwrap());
+        let mut u_out = RugMat::new(m, n, precision);
+        let mut vt_out = RugMat::new(n, n, precision);
+        for (k, &j) in order.iter().enumerate() {
+            u_out.as_mut_slice()[k * m..(k + 1) * m]
+                .clone_from_slice(&u.as_slice()[j * m..(j + 1) * m]);
+            for i in 0..n {
+                vt_out[(k, i)] = v[(i, j)].clone();
+            }
+        }
+
+        SVD {
+            u: u_out,
+            s: order.iter().map(|&j| bd.d[j].clone()).collect(),
+            vt: vt_out,
+        }
+    }
+
+    /// One-sided Jacobi SVD.
+    ///
+    /// Columns of a working copy of A are orthogonalized by plane rotations
+    /// until every pair satisfies |uₚᵗu_q| <= tol·‖uₚ‖‖u_q‖. Because the
+    /// stopping test is relative, small singular values keep their relative
+    /// accuracy instead of drowning in ‖A‖·ε. `max_sweeps` bounds the number
+    /// of full passes over all column pairs.
+    ///
+    /// Returns U (m×k), Σ (k, descending) and Vᵗ (k×n) with k = min(m, n).
+    pub fn svd_jacobi(&self, max_sweeps: usize, tol: f64) -> SVD {
+        if self.nrows() < self.ncols() {
+            let svd = self.transpose().svd_jacobi(max_sweeps, tol);
+            return SVD {
+                u: svd.vt.transpose(),
+                s: svd.s,
+                vt: svd.u.transpose(),
+            };
+        }
+
+        let precision = self.as_slice()[0].prec();
+        let (m, n) = (self.nrows(), self.ncols());
+        let mut u = self.clone();
+        let mut v = RugMat::identity(n, precision);
+        let tol = Float::with_val(precision, tol);
+
+        for _ in 0..max_sweeps {
+            let mut rotated = false;
+            for p in 0..n {
+                for q in (p + 1)..n {
+                    let alpha = u.column_norm2(p);
+                    let beta = u.column_norm2(q);
+                    let gamma = u.dot_columns(p, q);
+                    let bound = (&alpha * &beta).complete(precision).sqrt() * &tol;
+                    if gamma.clone().abs() <= bound {
+                        continue;
+                    }
+                    rotated = true;
+
+                    // Rotation annihilating the (p, q) entry of UᵗU
+                    let zeta: Float = ((&beta - &alpha).complete(precision) / gamma) / 2u32;
+                    let root = (zeta.clone().square() + 1u32).sqrt();
+                    let t = if zeta.is_sign_negative() {
+                        -(Float::with_val(precision, 1) / (root - &zeta))
+                    } else {
+                        Float::with_val(precision, 1) / (root + &zeta)
+                    };
+                    let c = (t.clone().square() + 1u32).sqrt().recip();
+                    let s = (&c * &t).complete(precision);
+
+                    rotate_columns(u.as_mut_slice(), m, p, q, &c, &s);
+                    rotate_columns(v.as_mut_slice(), n, p, q, &c, &s);
+                }
+            }
+            if !rotated {
+                break;
+            }
+        }
+
+        // Singular values are the column norms; normalize to get U
+        let sigma: Vec<Float> = (0..n).map(|j| u.column_norm2(j).sqrt()).collect();
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| sigma[b].partial_cmp(&sigma[a]).unwrap());
+
+        let mut u_out = RugMat::new(m, n, precision);
+        let mut vt_out = RugMat::new(n, n, precision);
+        let mut s_out = Vec::with_capacity(n);
+        for (k, &j) in order.iter().enumerate() {
+            for i in 0..m {
+                if !sigma[j].is_zero() {
+                    u_out[(i, k)] = (&u[(i, j)] / &sigma[j]).complete(precision);
+                }
+            }
+            for i in 0..n {
+                vt_out[(k, i)] = v[(i, j)].clone();
+            }
+            s_out.push(sigma[j].clone());
+        }
+
+        SVD {
+            u: u_out,
+            s: s_out,
+            vt: vt_out,
+        }
+    }
+}
+
+impl RugMat {
+    /// Minimum-norm solution of min ‖Ax - b‖ via the SVD.
+    ///
+    /// Singular values with σᵢ <= tol·σ₀ are treated as zero, so the answer is
+    /// x = Σ_{i<rank} (uᵢᵗb / σᵢ) vᵢ regardless of how A is rank deficient;
+    /// unlike CG, it does not depend on a starting point.
+    pub fn solve_min_norm(&self, b: &[Float], tol: f64) -> MinNormSolution {
+        assert_eq!(b.len(), self.nrows());
+        let precision = self.as_slice()[0].prec();
+        let svd = self.svd_jacobi(JACOBI_SWEEPS, working_tol(precision));
+        let threshold = Float::with_val(precision, &svd.s[0] * tol);
+
+        let utb = svd.u.matmul_transpose_vec(b);
+        let mut x = vec![Float::with_val(precision, 0); self.ncols()];
+        let mut rank = 0;
+        for (k, (sigma, coeff)) in svd.s.iter().zip(&utb).enumerate() {
+            if sigma.is_zero() || *sigma <= threshold {
+                break;
+            }
+            rank += 1;
+            let scale = (coeff / sigma).complete(precision);
+            for (j, xj) in x.iter_mut().enumerate() {
+                *xj += (&scale * &svd.vt[(k, j)]).complete(precision);
+            }
+        }
+
+        MinNormSolution { x, rank, threshold }
+    }
+}
+
+impl RugMat {
+    /// Numerical rank r = #{σᵢ > tol·σ₀} of a thin SVD
+    fn svd_rank(svd: &SVD, tol: f64) -> usize {
+        let threshold = Float::with_val(svd.s[0].prec(), &svd.s[0] * tol);
+        svd.s
+            .iter()
+            .take_while(|sigma| !sigma.is_zero() && **sigma > threshold)
+            .count()
+    }
+
+    /// Orthonormal basis of the null space {x : Ax = 0} as the columns of
+    /// an n×(n-r) matrix, with the numerical rank r decided as in
+    /// [`RugMat::solve_min_norm`].
+    ///
+    /// Taken from the right singular vectors, which stay accurate for
+    /// nearly rank-deficient A where elimination-based kernels do not.
+    /// A wide A is padded with zero rows so that its SVD carries all of V.
+    pub fn null_space(&self, tol: f64) -> RugMat {
+        let (m, n) = (self.nrows(), self.ncols());
+        let precision = self.as_slice()[0].prec();
+        let svd = if m < n {
+            let mut padded = RugMat::new(n, n, precision);
+            padded.set_block(0, 0, self);
+            padded.svd(QR_SWEEPS_PER_VALUE * n, working_tol(precision))
+        } else {
+            self.svd(QR_SWEEPS_PER_VALUE * n, working_tol(precision))
+        };
+        let r = RugMat::svd_rank(&svd, tol);
+        let mut basis = RugMat::new(n, n - r, precision);
+        for c in 0..n - r {
+            for i in 0..n {
+                basis[(i, c)] = svd.vt[(r + c, i)].clone();
+            }
+        }
+        basis
+    }
+
+    /// Orthonormal basis of the column space (range) of A as the columns of
+    /// an m×r matrix: the leading left singular vectors
+    pub fn column_space(&self, tol: f64) -> RugMat {
+        let precision = self.as_slice()[0].prec();
+        let k = self.nrows().min(self.ncols());
+        let svd = self.svd(QR_SWEEPS_PER_VALUE * k, working_tol(precision));
+        let r = RugMat::svd_rank(&svd, tol);
+        RugMat::from_col_major(
+            self.nrows(),
+            r,
+            svd.u.as_slice()[..self.nrows() * r].to_vec(),
+        )
+    }
+}
+
+impl SVD {
+    /// Relative condition number of the i-th singular value, ‖A‖₂ / σᵢ.
+    ///
+    /// By Weyl's inequality every σᵢ moves by at most ‖E‖₂ under a
+    /// perturbation E, so its absolute condition number is 1; relative to its
+    /// own size, σᵢ can only be trusted to about log10(σ₀/σᵢ) fewer digits than
+    /// the largest one. Returns +∞ for a zero singular value.
+    pub fn singular_value_condition(&self, i: usize) -> Float {
+        let sigma_max = &self.s[0];
+        if self.s[i].is_zero() {
+            return Float::with_val(sigma_max.prec(), Special::Infinity);
+        }
+        (sigma_max / &self.s[i]).complete(sigma_max.prec())
+    }
+
+    /// Update the decomposition in place to that of A + a bᵗ, see
+    /// [`SVD::update_low_rank`]
+    pub fn update_rank1(&mut self, a: &[Float], b: &[Float]) {
+        let column = |v: &[Float]| RugMat::from_col_major(v.len(), 1, v.to_vec());
+        self.update_low_rank(&column(a), &column(b));
+    }
+
+    /// Update the decomposition in place to that of A + X Yᵗ for an m×r X
+    /// and an n×r Y (Brand's method).
+    ///
+    /// The parts of X and Y outside span(U) and span(V) are orthonormalized
+    /// by QR, which reduces the update to the SVD of a (k+r)×(k+r) core, so
+    /// the cost is linear in m and n rather than a fresh decomposition. The
+    /// rank stays at k = s.len(): the r smallest singular values of the core
+    /// are dropped, which is exact when rank(A + XYᵗ) <= k and otherwise
+    /// gives the best rank-k approximation of the updated matrix restricted
+    /// to the tracked subspaces.
+    pub fn update_low_rank(&mut self, x: &RugMat, y: &RugMat) {
+        let (k, r) = (self.s.len(), x.ncols());
+        assert_eq!(y.ncols(), r, "X and Y must have the same number of columns");
+        assert_eq!(x.nrows(), self.u.nrows());
+        assert_eq!(y.nrows(), self.vt.ncols());
+        let precision = self.s[0].prec();
+
+        let v = self.vt.transpose();
+        let (mx, px, rx) = split_off_span(&self.u, x);
+        let (my, py, ry) = split_off_span(&v, y);
+
+        // Core [Σ 0; 0 0] + [Mx; Rx] [My; Ry]ᵗ
+        let stack = |m: &RugMat, rr: &RugMat| {
+            let mut c = RugMat::new(k + r, r, precision);
+            for j in 0..r {
+                for i in 0..k {
+                    c[(i, j)] = m[(i, j)].clone();
+                }
+                for i in 0..r {
+                    c[(k + i, j)] = rr[(i, j)].clone();
+                }
+            }
+            c
+        };
+        let mut core = stack(&mx, &rx).matmul(&stack(&my, &ry).transpose());
+        for (i, sigma) in self.s.iter().enumerate() {
+            core[(i, i)] += sigma;
+        }
+        let inner = core.svd_jacobi(JACOBI_SWEEPS, working_tol(precision));
+
+        let extend = |basis: &RugMat, extra: RugMat| {
+            RugMat::from_col_major(
+                basis.nrows(),
+                k + r,
+                basis
+                    .as_slice()
+                    .iter()
+                    .cloned()
+                    .chain(extra.into_data())
+                    .collect(),
+            )
+        };
+        let u = extend(&self.u, px).matmul(&inner.u);
+        let v = extend(&v, py).matmul(&inner.vt.transpose());
+        self.u = RugMat::from_col_major(u.nrows(), k, u.as_slice()[..u.nrows() * k].to_vec());
+        self.vt = RugMat::from_col_major(v.nrows(), k, v.as_slice()[..v.nrows() * k].to_vec())
+            .transpose();
+        self.s = inner.s[..k].to_vec();
+    }
+}
+
+/// Split X against an orthonormal basis Q: X = Q M + P R with P orthonormal
+/// and orthogonal to Q. Gram-Schmidt against Q is repeated once.
+fn split_off_span(q: &RugMat, x: &RugMat) -> (RugMat, RugMat, RugMat) {
+    let qt = q.transpose();
+    let mut m = RugMat::new(q.ncols(), x.ncols(), x.as_slice()[0].prec());
+    let mut z = x.clone();
+    for _ in 0..2 {
+        let c = qt.matmul(&z);
+        let qc = q.matmul(&c);
+        for (zi, qci) in z.as_mut_slice().iter_mut().zip(qc.as_slice()) {
+            *zi -= qci;
+        }
+        for (mi, ci) in m.as_mut_slice().iter_mut().zip(c.as_slice()) {
+            *mi += ci;
+        }
+    }
+    let qr = z.qr();
+    (m, qr.q_thin(), qr.r())
+}
+
+/// Upper bidiagonal form B = U_Bᵗ A V_B of an m×n matrix with m >= n.
+///
+/// `reflectors` keeps the left Householder vectors below the diagonal and
+/// the right ones to the right of the superdiagonal, as in LAPACK `xGEBRD`.
+struct Bidiagonal {
+    d: Vec<Float>,
+    e: Vec<Float>,
+    reflectors: RugMat,
+    tau_left: Vec<Float>,
+    tau_right: Vec<Float>,
+}
+
+impl Bidiagonal {
+    fn new(a: &RugMat) -> Self {
+        let precision = a.as_slice()[0].prec();
+        let (m, n) = (a.nrows(), a.ncols());
+        let mut a = a.clone();
+        let mut tau_left = Vec::with_capacity(n);
+        let mut tau_right = Vec::with_capacity(n.saturating_sub(1));
+
+        for k in 0..n {
+            // Left reflector: zero column k below the diagonal
+            let (head, trailing) = a.as_mut_slice().split_at_mut((k + 1) * m);
+            let col = &mut head[k * m..];
+            let t = householder_in_place(&mut col[k..], precision);
+            if !t.is_zero() {
+                let v = &col[k..];
+                trailing.par_chunks_mut(m).for_each(|target| {
+                    apply_reflector(v, &t, &mut target[k..]);
+                });
+            }
+            tau_left.push(t);
+
+            if k + 1 >= n {
+                continue;
+            }
+            // Right reflector: zero row k right of the superdiagonal
+            let mut x: Vec<Float> = (k + 1..n).map(|j| a[(k, j)].clone()).collect();
+            let t = householder_in_place(&mut x, precision);
+            for (j, xj) in (k + 1..n).zip(&x) {
+                a[(k, j)] = xj.clone();
+            }
+            if !t.is_zero() {
+                // Rows below k: A ← A (I - τ v vᵗ), with w = τ A v
+                let trailing = &mut a.as_mut_slice()[(k + 1) * m..];
+                let mut w: Vec<Float> = trailing[k + 1..m].to_vec();
+                for (vj, col) in x[1..].iter().zip(trailing.chunks(m).skip(1)) {
+                    for (wi, aij) in w.iter_mut().zip(&col[k + 1..]) {
+                        *wi += vj * aij;
+                    }
+                }
+                for wi in &mut w {
+                    *wi *= &t;
+                }
+                let one = Float::with_val(precision, 1);
+                trailing.par_chunks_mut(m).enumerate().for_each(|(j, col)| {
+                    let vj = if j == 0 { &one } else { &x[j] };
+                    for (aij, wi) in col[k + 1..].iter_mut().zip(&w) {
+                        *aij -= (vj * wi).complete(precision);
+                    }
+                });
+            }
+            tau_right.push(t);
+        }
+
+        let d = (0..n).map(|k| a[(k, k)].clone()).collect();
+        let e = (0..n.saturating_sub(1))
+            .map(|k| a[(k, k + 1)].clone())
+            .collect();
+        Bidiagonal {
+            d,
+            e,
+            reflectors: a,
+            tau_left,
+            tau_right,
+        }
+    }
+
+    /// The m×n thin factor U = H₀⋯Hₙ₋₁ [I; 0]
+    fn u_thin(&self) -> RugMat {
+        let (m, n) = (self.reflectors.nrows(), self.reflectors.ncols());
+        let precision = self.reflectors.as_slice()[0].prec();
+        let mut u = RugMat::new(m, n, precision);
+        u.as_mut_slice()
+            .par_chunks_mut(m)
+            .enumerate()
+            .for_each(|(j, col)| {
+                col[j] = Float::with_val(precision, 1);
+                for (k, tau) in self.tau_left.iter().enumerate().rev() {
+                    apply_reflector(
+                        &self.reflectors.as_slice()[k * m + k..(k + 1) * m],
+                        tau,
+                        &mut col[k..],
+                    );
+                }
+            });
+        u
+    }
+
+    /// The n×n factor V = G₀⋯Gₙ₋₂
+    fn v(&self) -> RugMat {
+        let n = self.reflectors.ncols();
+        let precision = self.reflectors.as_slice()[0].prec();
+        let right: Vec<Vec<Float>> = (0..self.tau_right.len())
+            .map(|k| {
+                (k + 1..n)
+                    .map(|j| self.reflectors[(k, j)].clone())
+                    .collect()
+            })
+            .collect();
+        let mut v = RugMat::identity(n, precision);
+        v.as_mut_slice().par_chunks_mut(n).for_each(|col| {
+            for (k, tau) in self.tau_right.iter().enumerate().rev() {
+                apply_reflector(&right[k], tau, &mut col[k + 1..]);
+            }
+        });
+        v
+    }
+
+    /// B as a dense n×n upper bidiagonal matrix
+    fn b(&self) -> RugMat {
+        let n = self.d.len();
+        let mut b = RugMat::new(n, n, self.reflectors.as_slice()[0].prec());
+        for (k, dk) in self.d.iter().enumerate() {
+            b[(k, k)] = dk.clone();
+        }
+        for (k, ek) in self.e.iter().enumerate() {
+            b[(k, k + 1)] = ek.clone();
+        }
+        b
+    }
+
+    /// Diagonalize B by implicit-shift QR, accumulating the left rotations
+    /// into the columns of u and the right ones into v when given. Leaves
+    /// the singular values, made nonnegative but unsorted, in d. Returns
+    /// whether every superdiagonal entry was deflated within max_iters sweeps.
+    #[must_use]
+    fn qr_iterate(
+        &mut self,
+        mut u: Option<&mut RugMat>,
+        mut v: Option<&mut RugMat>,
+        max_iters: usize,
+        tol: f64,
+    ) -> bool {
+        let n = self.d.len();
+        if n == 0 {
+            return true;
+        }
+        let precision = self.d[0].prec();
+        let tol = Float::with_val(precision, tol);
+        let b_norm = self
+            .d
+            .iter()
+            .chain(&self.e)
+            .map(|x| x.clone().abs())
+            .fold(Float::with_val(precision, 0), |a, b| a.max(&b));
+        let tiny = (&tol * &b_norm).complete(precision);
+        let mut rot_u = |i: usize, j: usize, c: &Float, s: &Float| {
+            if let Some(u) = u.as_deref_mut() {
+                let (data, rows, _) = u.raw_parts_mut();
+                rotate_columns(data, rows, i, j, c, s);
+            }
+        };
+        let mut rot_v = |i: usize, j: usize, c: &Float, s: &Float| {
+            if let Some(v) = v.as_deref_mut() {
+                let (data, rows, _) = v.raw_parts_mut();
+                rotate_columns(data, rows, i, j, c, s);
+            }
+        };
+
+        let mut converged = false;
+        for _ in 0..max_iters {
+            for i in 0..n - 1 {
+                let scale = Float::with_val(precision, self.d[i].abs_ref())
+                    + Float::with_val(precision, self.d[i + 1].abs_ref());
+                if Float::with_val(precision, self.e[i].abs_ref()) <= tol.clone() * scale {
+                    self.e[i] = Float::with_val(precision, 0);
+                }
+            }
+
+            // Trailing unreduced block [p, q]
+            let mut q = n - 1;
+            while q > 0 && self.e[q - 1].is_zero() {
+                q -= 1;
+            }
+            if q == 0 {
+                converged = true;
+                break;
+            }
+            let mut p = q - 1;
+            while p > 0 && !self.e[p - 1].is_zero() {
+                p -= 1;
+            }
+
+            // A zero on the diagonal splits the block once chased out
+            if let Some(i) =
+                (p..=q).find(|&i| Float::with_val(precision, self.d[i].abs_ref()) <= tiny)
+            {
+                self.d[i] = Float::with_val(precision, 0);
+                if i < q {
+                    // Row chase: rotate rows (i, j) to move e_i off the row
+                    let mut x = std::mem::replace(&mut self.e[i], Float::with_val(precision, 0));
+                    for j in i + 1..=q {
+                        let (c, s, r) = givens(&self.d[j], &x);
+                        self.d[j] = r;
+                        rot_u(i, j, &c, &s);
+                        if j < q {
+                            x = -(&s * &self.e[j]).complete(precision);
+                            self.e[j] *= &c;
+                        }
+                    }
+                } else {
+                    // Column chase: rotate columns (j, q) to move e_{q-1} up
+                    let mut x =
+                        std::mem::replace(&mut self.e[q - 1], Float::with_val(precision, 0));
+                    for j in (p..q).rev() {
+                        let (c, s, r) = givens(&self.d[j], &x);
+                        self.d[j] = r;
+                        rot_v(j, q, &c, &-s.clone());
+                        if j > p {
+                            x = -(&s * &self.e[j - 1]).complete(precision);
+                            self.e[j - 1] *= &c;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // Wilkinson shift from the trailing 2×2 block of BᵗB
+            let dq1 = &self.d[q - 1];
+            let eq1 = &self.e[q - 1];
+            let mut t11 = dq1.clone().square();
+            if q - 1 > p {
+                t11 += self.e[q - 2].clone().square();
+            }
+            let t12 = (dq1 * eq1).complete(precision);
+            let t22 = self.d[q].clone().square() + eq1.clone().square();
+            let delta: Float = (&t11 - &t22).complete(precision) / 2u32;
+            let root = (delta.clone().square() + t12.clone().square()).sqrt();
+            let denom = if delta.is_sign_negative() {
+                delta - root
+            } else {
+                delta + root
+            };
+            let mu = if denom.is_zero() {
+                t22.clone()
+            } else {
+                t22.clone() - t12.square() / denom
+            };
+
+            // Chase the bulge from p down to q
+            let mut y = self.d[p].clone().square() - &mu;
+            let mut z = (&self.d[p] * &self.e[p]).complete(precision);
+            for k in p..q {
+                // Right rotation on columns (k, k+1)
+                let (c, s, r) = givens(&y, &z);
+                if k > p {
+                    self.e[k - 1] = r;
+                }
+                let dk =
+                    (&c * &self.d[k]).complete(precision) + (&s * &self.e[k]).complete(precision);
+                let ek =
+                    (&c * &self.e[k]).complete(precision) - (&s * &self.d[k]).complete(precision);
+                let bulge = (&s * &self.d[k + 1]).complete(precision);
+                self.d[k + 1] *= &c;
+                rot_v(k, k + 1, &c, &-s.clone());
+
+                // Left rotation on rows (k, k+1)
+                let (c, s, r) = givens(&dk, &bulge);
+                self.d[k] = r;
+                self.e[k] =
+                    (&c * &ek).complete(precision) + (&s * &self.d[k + 1]).complete(precision);
+                self.d[k + 1] =
+                    (&c * &self.d[k + 1]).complete(precision) - (&s * &ek).complete(precision);
+                rot_u(k, k + 1, &c, &-s.clone());
+                if k + 1 < q {
+                    y = self.e[k].clone();
+                    z = (&s * &self.e[k + 1]).complete(precision);
+                    self.e[k + 1] *= &c;
+                }
+            }
+        }
+
+        for (j, dj) in self.d.iter_mut().enumerate() {
+            if dj.is_sign_negative() {
+                *dj = -dj.clone();
+                if let Some(v) = v.as_deref_mut() {
+                    for x in v.col_as_mut_slice(j) {
+                        *x = -x.clone();
+                    }
+                }
+            }
+        }
+        converged
+    }
+}
+
+#[test]
+fn test_svd_jacobi_known() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 3), Float::with_val(precision, 0)],
+        vec![Float::with_val(precision, 4), Float::with_val(precision, 5)],
+    ]);
+    let svd = a.svd_with_method(SvdMethod::Jacobi, 50, 1e-35);
+
+    // AᵗA = [[25, 20], [20, 25]] has eigenvalues 45 and 5
+    assert!((svd.s[0].to_f64() - 45f64.sqrt()).abs() < 1e-12);
+    assert!((svd.s[1].to_f64() - 5f64.sqrt()).abs() < 1e-12);
+
+    // U Σ Vᵗ reproduces A
+    let mut us = svd.u.clone();
+    for j in 0..2 {
+        for i in 0..2 {
+            us[(i, j)] *= &svd.s[j];
+        }
+    }
+    let rebuilt = us.matmul(&svd.vt);
+    for j in 0..2 {
+        for i in 0..2 {
+            let err = (rebuilt[(i, j)].clone() - &a[(i, j)]).abs();
+            assert!(err < Float::with_val(precision, 1e-30));
+        }
+    }
+}
+
+#[test]
+fn test_svd_jacobi_tiny_singular_value_relative_accuracy() {
+    // Rows (1, 1) and d·(1, -1) are orthogonal, so σ = √2 and √2·d exactly,
+    // while the columns are nearly parallel and need a rotation to separate.
+    let precision = 256;
+    let d = Float::with_val(precision, 1e-30);
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 1)],
+        vec![d.clone(), -d.clone()],
+    ]);
+    let svd = a.svd_jacobi(30, 1e-70);
+
+    let sqrt2 = Float::with_val(precision, 2).sqrt();
+    let tiny = Float::with_val(precision, &sqrt2 * &d);
+    let rel_big = ((&svd.s[0] - &sqrt2).complete(precision) / &sqrt2).abs();
+    let rel_tiny = ((&svd.s[1] - &tiny).complete(precision) / &tiny).abs();
+    assert!(rel_big < 1e-60);
+    assert!(rel_tiny < 1e-60, "relative error {}", rel_tiny);
+
+    let kappa = svd.singular_value_condition(1);
+    assert!((kappa * &d - 1u32).abs() < 1e-60);
+    assert_eq!(svd.singular_value_condition(0), 1);
+}
+
+#[test]
+fn test_solve_min_norm_rank_deficient() {
+    let precision = 128;
+    // Rank one: every x with x0 + x1 = 2 fits exactly; (1, 1) has least norm
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 1), Float::with_val(precision, 1)],
+        vec![Float::with_val(precision, 2), Float::with_val(precision, 2)],
+    ]);
+    let b = vec![
+        Float::with_val(precision, 2),
+        Float::with_val(precision, 2),
+        Float::with_val(precision, 4),
+    ];
+    let sol = a.solve_min_norm(&b, 1e-30);
+    assert_eq!(sol.rank, 1);
+    assert!((sol.x[0].to_f64() - 1.0).abs() < 1e-30);
+    assert!((sol.x[1].to_f64() - 1.0).abs() < 1e-30);
+}
+
+#[test]
+fn test_svd_golub_kahan_reconstructs() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(
+        [
+            [2, -1, 0, 3],
+            [1, 4, -2, 0],
+            [0, 1, 5, 1],
+            [3, 0, 1, -2],
+            [1, 1, 1, 1],
+        ]
+        .iter()
+        .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+        .collect(),
+    );
+    for mat in [a.clone(), a.transpose()] {
+        let svd = mat.svd_with_method(SvdMethod::GolubKahan, 100, 1e-36);
+        assert!(svd.s.windows(2).all(|w| w[0] >= w[1]));
+
+        let mut us = svd.u.clone();
+        us.scale_cols(&svd.s);
+        let diff = us.matmul(&svd.vt).compare(&mat);
+        assert!(diff.max_abs < 1e-30);
+        let k = svd.s.len();
+        let id = RugMat::identity(k, precision);
+        assert!(svd.u.transpose().matmul(&svd.u).compare(&id).max_abs < 1e-30);
+        assert!(svd.vt.matmul(&svd.vt.transpose()).compare(&id).max_abs < 1e-30);
+
+        // Agrees with the Jacobi singular values
+        let jacobi = mat.svd_jacobi(50, 1e-36);
+        for (x, y) in svd.s.iter().zip(&jacobi.s) {
+            assert!((x.clone() - y).abs() < 1e-30);
+        }
+    }
+}
+
+#[test]
+fn test_svd_golub_kahan_rank_deficient() {
+    let precision = 128;
+    // Rank one with a zero column: exercises the zero-diagonal chase
+    let a = RugMat::from_vecvec(
+        [[1, 0, 2], [2, 0, 4], [3, 0, 6]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    let svd = a.svd(100, 1e-36);
+    assert!((svd.s[0].clone() - Float::with_val(precision, 70).sqrt()).abs() < 1e-30);
+    assert!(svd.s[1] < 1e-30 && svd.s[2] < 1e-30);
+    let mut us = svd.u.clone();
+    us.scale_cols(&svd.s);
+    assert!(us.matmul(&svd.vt).compare(&a).max_abs < 1e-30);
+}
+
+#[test]
+fn test_singular_values_only() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(vec![
+        vec![Float::with_val(precision, 3), Float::with_val(precision, 0)],
+        vec![Float::with_val(precision, 4), Float::with_val(precision, 5)],
+    ]);
+    let s = a.singular_values(1e-36);
+    let expected = [45, 5].map(|v| Float::with_val(precision, v).sqrt());
+    for (x, y) in s.iter().zip(&expected) {
+        assert!((x.clone() - y).abs() < 1e-33);
+    }
+    // Wide input gives the same values
+    assert_eq!(a.transpose().singular_values(1e-36).len(), 2);
+}
+
+#[test]
+fn test_bidiagonalize_reconstructs() {
+    let precision = 128;
+    let a = RugMat::from_vecvec(
+        [[1, 2, 3], [4, 5, 6], [7, 8, 10], [1, 0, 1]]
+            .iter()
+            .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+            .collect(),
+    );
+    for mat in [a.clone(), a.transpose()] {
+        let (u, b, v) = mat.bidiagonalize(true);
+        let (u, v) = (u.unwrap(), v.unwrap());
+        let k = b.nrows();
+        let upper = mat.nrows() >= mat.ncols();
+        for j in 0..k {
+            for i in 0..k {
+                let on_band = i == j || if upper { j == i + 1 } else { i == j + 1 };
+                assert!(on_band || b[(i, j)].is_zero());
+            }
+        }
+        let rebuilt = u.matmul(&b).matmul(&v.transpose());
+        assert!(rebuilt.compare(&mat).max_abs < 1e-30);
+    }
+    let (u, _, v) = a.bidiagonalize(false);
+    assert!(u.is_none() && v.is_none());
+}
+
+#[test]
+fn test_randomized_svd_low_rank() {
+    let precision = 128;
+    let (m, n) = (10, 8);
+    // Rank 3: Σ σᵢ uᵢ vᵢᵗ with orthogonal-ish integer factors
+    let mut a = RugMat::new(m, n, precision);
+    for (sigma, shift) in [(5u32, 0usize), (3, 1), (1, 2)] {
+        for j in 0..n {
+            for i in 0..m {
+                let ui = ((i + shift) % 3) as i32 - 1;
+                let vj = ((j * (shift + 1)) % 4) as i32 - 2;
+                a[(i, j)] += Float::with_val(precision, ui * vj) * sigma;
+            }
+        }
+    }
+    let exact = a.singular_values(1e-36);
+    let rsvd = a.randomized_svd(3, 2, 1, 17);
+    assert_eq!(
+        (
+            rsvd.u.nrows(),
+            rsvd.u.ncols(),
+            rsvd.vt.nrows(),
+            rsvd.vt.ncols()
+        ),
+        (m, 3, 3, n)
+    );
+    for (s, e) in rsvd.s.iter().zip(&exact) {
+        assert!((s.clone() - e).abs() < 1e-30);
+    }
+    let mut us = rsvd.u.clone();
+    us.scale_cols(&rsvd.s);
+    assert!(us.matmul(&rsvd.vt).compare(&a).max_abs < 1e-30);
+}
+
+#[test]
+fn test_svd_partial_top_triplets() {
+    let precision = 128;
+    let (m, n) = (12, 9);
+    let mut a = RugMat::new(m, n, precision);
+    for j in 0..n {
+        for i in 0..m {
+            let v = ((3 * i + 7 * j) % 11) as f64 - 5.0 + 1.0 / (1.0 + i as f64 + j as f64);
+            a[(i, j)] = Float::with_val(precision, v);
+        }
+    }
+    let exact = a.singular_values(1e-36);
+    let partial = a.svd_partial(3, 1e-32);
+    for (s, e) in partial.s.iter().zip(&exact) {
+        assert!((s.clone() - e).abs() < 1e-28);
+    }
+    // A vᵢ = σᵢ uᵢ
+    let mut us = partial.u.clone();
+    us.scale_cols(&partial.s);
+    let av = a.matmul(&partial.vt.transpose());
+    assert!(av.compare(&us).max_abs < 1e-28);
+}
+
+#[test]
+fn test_null_and_column_space() {
+    let precision = 128;
+    let mat = |rows: &[&[i32]]| {
+        RugMat::from_vecvec(
+            rows.iter()
+                .map(|r| r.iter().map(|&v| Float::with_val(precision, v)).collect())
+                .collect(),
+        )
+    };
+    // Rank 2: third row = first + second, and a 1e-60 perturbation that
+    // a relative tolerance of 1e-30 ignores
+    let mut a = mat(&[&[1, 2, 3, 4], &[2, 0, 1, -1], &[3, 2, 4, 3]]);
+    a[(2, 3)] += Float::with_val(precision, 1e-60);
+
+    let null = a.null_space(1e-30);
+    assert_eq!((null.nrows(), null.ncols()), (4, 2));
+    assert!(a.matmul(&null).max_entry_norm() < 1e-30);
+    let id = RugMat::identity(2, precision);
+    assert!(null.transpose().matmul(&null).compare(&id).max_abs < 1e-30);
+
+    let range = a.column_space(1e-30);
+    assert_eq!((range.nrows(), range.ncols()), (3, 2));
+    // (1, 1, -1) is orthogonal to every column of a
+    let y = [1, 1, -1].map(|v| Float::with_val(precision, v));
+    for v in range.matmul_transpose_vec(&y) {
+        assert!(v.abs() < 1e-30);
+    }
+
+    // Tall full-rank matrix has a trivial null space
+    let tall = mat(&[&[1, 0], &[0, 1], &[1, 1]]);
+    assert_eq!(tall.null_space(1e-30).ncols(), 0);
+}
+
+#[test]
+fn test_svd_low_rank_updates_match_recomputation() {
+    let precision = 128;
+    let f = |v: f64| Float::with_val(precision, v);
+    let mut a = RugMat::new(6, 4, precision);
+    for j in 0..4 {
+        for i in 0..6 {
+            a[(i, j)] = f(((3 * i + 5 * j) % 7) as f64 - 3.0 + 0.25 * (i * j) as f64);
+        }
+    }
+    let reconstruct = |svd: &SVD| {
+        let mut us = svd.u.clone();
+        for (j, sigma) in svd.s.iter().enumerate() {
+            for v in us.col_as_mut_slice(j) {
+                *v *= sigma;
+            }
+        }
+        us.matmul(&svd.vt)
+    };
+
+    // Full thin SVD: a rank-one update is exact
+    let mut svd = a.svd_jacobi(JACOBI_SWEEPS, 1e-35);
+    let u: Vec<Float> = (0..6).map(|i| f(1.0 - 0.5 * i as f64)).collect();
+    let v: Vec<Float> = (0..4).map(|j| f(0.3 * j as f64 + 0.1)).collect();
+    svd.update_rank1(&u, &v);
+    for j in 0..4 {
+        for i in 0..6 {
+            a[(i, j)] += (&u[i] * &v[j]).complete(precision);
+        }
+    }
+    assert!(reconstruct(&svd).compare(&a).max_abs < 1e-30);
+    for (p, q) in svd.s.iter().zip(a.singular_values(1e-35)) {
+        assert!((p.clone() - q).abs() < 1e-30);
+    }
+
+    // Rank-2 matrix tracked at k = 2 stays exact under a rank-2 block
+    // update that keeps the rank at 2
+    let left = RugMat::from_vecvec((0..6).map(|i| vec![f(1.0), f(i as f64)]).collect());
+    let right = RugMat::from_vecvec((0..4).map(|j| vec![f(j as f64 - 1.0), f(2.0)]).collect());
+    let low = left.matmul(&right.transpose());
+    let mut tracked = low.svd_jacobi(JACOBI_SWEEPS, 1e-35);
+    tracked.u = RugMat::from_col_major(6, 2, tracked.u.as_slice()[..12].to_vec());
+    tracked.s.truncate(2);
+    tracked.vt = RugMat::from_vecvec(
+        (0..2)
+            .map(|i| (0..4).map(|j| tracked.vt[(i, j)].clone()).collect())
+            .collect(),
+    );
+    let mut shift = right.clone();
+    for v in shift.as_mut_slice() {
+        *v *= 0.5f64;
+    }
+    tracked.update_low_rank(&left, &shift);
+    let mut expected = low.clone();
+    for (e, d) in expected
+        .as_mut_slice()
+        .iter_mut()
+        .zip(left.matmul(&shift.transpose()).as_slice())
+    {
+        *e += d;
+    }
+    assert!(reconstruct(&tracked).compare(&expected).max_abs < 1e-30);
+}
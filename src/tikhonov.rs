@@ -0,0 +1,477 @@
+// tikhonov.rs: Tikhonov-regularized least squares with automatic λ selection
+// and filter-factor analysis of regularized solutions
+use crate::RugMat;
+use crate::rugmat::dot;
+use crate::svd::{JACOBI_SWEEPS, working_tol};
+use rug::Float;
+use rug::ops::{CompleteRound, Pow};
+
+/// Points of the logarithmic λ grid searched for [`LambdaStrategy::GCV`]
+const GCV_GRID: usize = 60;
+/// Golden-section steps refining the best GCV grid point
+const GCV_REFINE: usize = 40;
+
+/// How [`RugMat::regularized_least_squares`] chooses λ in
+/// min ‖Ax - b‖² + λ‖x‖²
+#[derive(Debug, Clone)]
+pub enum LambdaStrategy {
+    /// Use the given λ, as [`crate::solvers::cg_regularized`] does
+    Fixed(Float),
+    /// Corner of the L-curve (log ‖Ax - b‖, log ‖x‖): the λ of largest
+    /// curvature among `grid` values spaced logarithmically over the squared
+    /// singular values
+    LCurve { grid: usize },
+    /// Minimizer of the generalized cross-validation function
+    /// ‖Ax - b‖² / (m - Σ fᵢ)², with fᵢ = σᵢ²/(σᵢ² + λ) the filter factors
+    GCV,
+}
+
+/// Result of [`RugMat::regularized_least_squares`]
+#[derive(Debug, Clone)]
+pub struct Regularized {
+    pub x: Vec<Float>,
+    /// The λ that was used, chosen or given
+    pub lambda: Float,
+    pub residual_norm: Float,
+    pub solution_norm: Float,
+}
+
+/// Regularization whose filter factors [`RugMat::filter_factors`] reports
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// Tikhonov with parameter λ: fᵢ = σᵢ²/(σᵢ² + λ)
+    Tikhonov(Float),
+    /// Truncated SVD keeping the k largest singular values: fᵢ = 1 for i < k
+    /// and 0 otherwise
+    Truncated(usize),
+}
+
+/// Result of [`RugMat::filter_factors`], indexed by singular value in
+/// descending order except for `resolution`
+#[derive(Debug, Clone)]
+pub struct FilterFactors {
+    pub singular_values: Vec<Float>,
+    /// fᵢ, so that the regularized solution is Σ fᵢ (uᵢᵗb/σᵢ) vᵢ
+    pub factors: Vec<Float>,
+    /// Diagonal of the model resolution matrix V diag(f) Vᵗ, one entry per
+    /// unknown: 1 where xⱼ is fully resolved, near 0 where it comes from the
+    /// regularization instead of the data
+    pub resolution: Vec<Float>,
+}
+
+impl FilterFactors {
+    /// Σ fᵢ, the trace of the resolution matrix and the effective number of
+    /// parameters the data determine
+    pub fn effective_dof(&self) -> Float {
+        let precision = self.factors.first().map_or(64, |f| f.prec());
+        Float::with_val(precision, Float::sum(self.factors.iter()))
+    }
+}
+
+/// Tikhonov filter factor fᵢ = σᵢ²/(σᵢ² + λ) and its denominator, or None
+/// when σᵢ = λ = 0 and the component is dropped
+fn tikhonov_filter(sigma: &Float, lambda: &Float, precision: u32) -> Option<(Float, Float)> {
+    let sq = sigma.clone().square();
+    let denom = (&sq + lambda).complete(precision);
+    if denom.is_zero() {
+        return None;
+    }
+    Some((sq / &denom, denom))
+}
+
+/// The SVD coordinates of a problem, in which every quantity of the
+/// regularized solution is a sum over filter factors
+struct Spectral {
+    s: Vec<Float>,
+    /// uᵢᵗb
+    beta: Vec<Float>,
+    /// ‖b‖² - ‖Uᵗb‖², the part of b no λ can fit
+    outside: Float,
+    rows: usize,
+    precision: u32,
+}
+
+impl Spectral {
+    /// (σᵢβᵢ/(σᵢ² + λ), λβᵢ/(σᵢ² + λ), fᵢ): the solution coefficient, the
+    /// residual component and the filter factor; all zero-safe when σᵢ = 0
+    fn terms(&self, lambda: &Float) -> impl Iterator<Item = (Float, Float, Float)> + '_ {
+        let precision = self.precision;
+        let lambda = lambda.clone();
+        self.s.iter().zip(&self.beta).map(move |(sigma, beta)| {
+            let Some((filter, denom)) = tikhonov_filter(sigma, &lambda, precision) else {
+                let zero = Float::with_val(precision, 0);
+                return (zero.clone(), beta.clone(), zero);
+            };
+            let coeff = (sigma * beta).complete(precision) / &denom;
+            let residual = (&lambda * beta).complete(precision) / &denom;
+            (coeff, residual, filter)
+        })
+    }
+
+    /// (‖Ax - b‖², ‖x‖², Σ fᵢ) at λ
+    fn norms(&self, lambda: &Float) -> (Float, Float, Float) {
+        let zero = Float::with_val(self.precision, 0);
+        let (mut rho, mut eta, mut trace) = (self.outside.clone(), zero.clone(), zero);
+        for (coeff, residual, filter) in self.terms(lambda) {
+            rho += residual.square();
+            eta += coeff.square();
+            trace += filter;
+        }
+        (rho, eta, trace)
+    }
+
+    fn gcv(&self, lambda: &Float) -> Float {
+        let (rho, _, trace) = self.norms(lambda);
+        let dof = Float::with_val(self.precision, self.rows) - trace;
+        rho / dof.square()
+    }
+
+    /// ln λ over [ln(u·σ₀²), ln σ₀²], or over the squared nonzero singular
+    /// values when they span less
+    fn log_range(&self) -> (Float, Float) {
+        let precision = self.precision;
+        let top = self.s[0].clone().square();
+        let floor = (&top * working_tol(precision)).complete(precision);
+        let smallest = self
+            .s
+            .iter()
+            .rev()
+            .find(|sigma| !sigma.is_zero())
+            .map(|sigma| sigma.clone().square())
+            .unwrap_or_else(|| floor.clone());
+        let bottom = if smallest > floor { smallest } else { floor };
+        (bottom.ln(), top.ln())
+    }
+
+    /// λ at fraction k/(n - 1) of the log range
+    fn grid(&self, n: usize) -> Vec<Float> {
+        let (lo, hi) = self.log_range();
+        let span = (&hi - &lo).complete(self.precision);
+        (0..n)
+            .map(|k| {
+                let t = (&span * k as u32).complete(self.precision) / (n - 1).max(1) as u32;
+                (t + &lo).exp()
+            })
+            .collect()
+    }
+
+    /// Signed curvature of the L-curve (½ ln ρ, ½ ln η), ρ = ‖Ax - b‖² and
+    /// η = ‖x‖², at λ; positive where it bends from its steep part into its
+    /// flat part. With dᵢ = σᵢ² + λ and Eₖ = Σ σᵢ²βᵢ²/dᵢᵏ, the derivatives
+    /// are η' = -2E₃, η'' = 6E₄, ρ' = 2λE₃ and ρ'' = 2E₃ - 6λE₄.
+    fn curvature(&self, lambda: &Float) -> Float {
+        let precision = self.precision;
+        let (rho, eta, _) = self.norms(lambda);
+        let zero = Float::with_val(precision, 0);
+        let (mut e3, mut e4) = (zero.clone(), zero);
+        for (sigma, beta) in self.s.iter().zip(&self.beta) {
+            let d = sigma.clone().square() + lambda;
+            if d.is_zero() {
+                continue;
+            }
+            let weight = (sigma * beta).complete(precision).square() / d.clone().square();
+            let weight = weight / &d;
+            e4 += (&weight / &d).complete(precision);
+            e3 += weight;
+        }
+        let deta = (&e3 * -2i32).complete(precision);
+        let ddeta = (&e4 * 6u32).complete(precision);
+        let drho = (&e3 * lambda).complete(precision) * 2u32;
+        let ddrho = e3 * 2u32 - (e4 * lambda) * 6u32;
+
+        // x = ½ ln ρ and y = ½ ln η, differentiated along λ
+        let dx = (&drho / &rho).complete(precision) / 2u32;
+        let dy = (&deta / &eta).complete(precision) / 2u32;
+        let ddx = (ddrho * &rho - drho.square()) / rho.square() / 2u32;
+        let ddy = (ddeta * &eta - deta.square()) / eta.square() / 2u32;
+        let speed = (dx.clone().square() + dy.clone().square()).pow(1.5);
+        (dx * ddy - ddx * dy) / speed
+    }
+
+    /// λ of largest L-curve curvature on a log grid
+    fn l_curve(&self, grid: usize) -> Float {
+        let lambdas = self.grid(grid);
+        let curvatures: Vec<Float> = lambdas.iter().map(|l| self.curvature(l)).collect();
+        let corner = (0..lambdas.len())
+            .max_by(|&i, &j| curvatures[i].total_cmp(&curvatures[j]))
+            .unwrap();
+        lambdas[corner].clone()
+    }
+
+    /// Best point of a log grid, refined by golden-section search in ln λ
+    /// between its neighbours
+    fn minimize_gcv(&self) -> Float {
+        let precision = self.precision;
+        let lambdas = self.grid(GCV_GRID);
+        let values: Vec<Float> = lambdas.iter().map(|l| self.gcv(l)).collect();
+        let best = (0..values.len())
+            .min_by(|&i, &j| values[i].total_cmp(&values[j]))
+            .unwrap();
+        let mut lo = lambdas[best.saturating_sub(1)].clone().ln();
+        let mut hi = lambdas[(best + 1).min(lambdas.len() - 1)].clone().ln();
+
+        let ratio = (Float::with_val(precision, 5).sqrt() - 1u32) / 2u32;
+        let at = |t: &Float| self.gcv(&t.clone().exp());
+        // The point a golden fraction of the way from `from` to `to`
+        let toward = |from: &Float, to: &Float| (to - from).complete(precision) * &ratio + from;
+        let mut c = toward(&hi, &lo);
+        let mut d = toward(&lo, &hi);
+        let (mut fc, mut fd) = (at(&c), at(&d));
+        for _ in 0..GCV_REFINE {
+            if fc < fd {
+                hi = d;
+                d = c;
+                fd = fc;
+                c = toward(&hi, &lo);
+                fc = at(&c);
+            } else {
+                lo = c;
+                c = d;
+                fc = fd;
+                d = toward(&lo, &hi);
+                fd = at(&d);
+            }
+        }
+        let refined = ((lo + hi) / 2u32).exp();
+        if self.gcv(&refined) <= values[best] {
+            refined
+        } else {
+            lambdas[best].clone()
+        }
+    }
+}
+
+impl RugMat {
+    /// Tikhonov-regularized least squares: x minimizing ‖Ax - b‖² + λ‖x‖²,
+    /// the solution of (AᵗA + λI)x = Aᵗb, with λ given or chosen from the
+    /// data.
+    ///
+    /// Computed from one Jacobi SVD as x = Σ σᵢ(uᵢᵗb)/(σᵢ² + λ) vᵢ, so every
+    /// λ tried costs O(n) and the searched λ range follows the working
+    /// precision instead of a fixed constant.
+    pub fn regularized_least_squares(&self, b: &[Float], lambda: LambdaStrategy) -> Regularized {
+        assert_eq!(b.len(), self.nrows(), "b must have one entry per row");
+        let precision = self.as_slice()[0].prec();
+        let svd = self.svd_jacobi(JACOBI_SWEEPS, working_tol(precision));
+        let beta = svd.u.matmul_transpose_vec(b);
+        let outside = dot(b, b) - dot(&beta, &beta);
+        let spectral = Spectral {
+            outside: if outside.is_sign_negative() {
+                Float::with_val(precision, 0)
+            } else {
+                Float::with_val(precision, outside)
+            },
+            s: svd.s,
+            beta,
+            rows: self.nrows(),
+            precision,
+        };
+
+        let lambda = match lambda {
+            LambdaStrategy::Fixed(l) => {
+                assert!(!l.is_sign_negative(), "λ must be non-negative");
+                l
+            }
+            LambdaStrategy::LCurve { grid } => {
+                assert!(grid > 0, "The L-curve needs at least one grid point");
+                spectral.l_curve(grid)
+            }
+            LambdaStrategy::GCV => spectral.minimize_gcv(),
+        };
+
+        let mut x = vec![Float::with_val(precision, 0); self.ncols()];
+        for (k, (coeff, _, _)) in spectral.terms(&lambda).enumerate() {
+            for (j, xj) in x.iter_mut().enumerate() {
+                *xj += (&coeff * &svd.vt[(k, j)]).complete(precision);
+            }
+        }
+        let (rho, eta, _) = spectral.norms(&lambda);
+        Regularized {
+            x,
+            lambda,
+            residual_norm: rho.sqrt(),
+            solution_norm: eta.sqrt(),
+        }
+    }
+
+    /// Filter factors of a Tikhonov or truncated SVD solution and the
+    /// diagonal of its model resolution matrix, from one Jacobi SVD.
+    ///
+    /// They depend on A and the regularization only, not on b, and show
+    /// which singular components and which unknowns the regularized solution
+    /// actually recovers.
+    pub fn filter_factors(&self, filter: Filter) -> FilterFactors {
+        let precision = self.as_slice()[0].prec();
+        let svd = self.svd_jacobi(JACOBI_SWEEPS, working_tol(precision));
+        let factors: Vec<Float> = match filter {
+            Filter::Tikhonov(lambda) => {
+                assert!(!lambda.is_sign_negative(), "λ must be non-negative");
+                svd.s
+                    .iter()
+                    .map(|sigma| {
+                        tikhonov_filter(sigma, &lambda, precision)
+                            .map_or_else(|| Float::with_val(precision, 0), |(f, _)| f)
+                    })
+                    .collect()
+            }
+            Filter::Truncated(k) => {
+                assert!(k <= svd.s.len(), "Cannot keep more than min(m, n) values");
+                (0..svd.s.len())
+                    .map(|i| Float::with_val(precision, (i < k) as u32))
+                    .collect()
+            }
+        };
+
+        let resolution = (0..self.ncols())
+            .map(|j| {
+                let mut acc = Float::with_val(precision, 0);
+                for (i, f) in factors.iter().enumerate() {
+                    let v = &svd.vt[(i, j)];
+                    acc += (v * v).complete(precision) * f;
+                }
+                acc
+            })
+            .collect();
+        FilterFactors {
+            singular_values: svd.s,
+            factors,
+            resolution,
+        }
+    }
+}
+
+#[test]
+fn test_fixed_lambda_matches_cg_regularized() {
+    use crate::solvers::cg_regularized;
+    let precision = 128;
+    let entries = [4.0, 1.0, 0.5, 1.0, 1.0, 3.0, -1.0, 1.0, 0.5, -1.0, 2.0, 1.0];
+    let data = entries
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let a = RugMat::from_col_major(4, 3, data);
+    let b: Vec<Float> = [1.0, -2.0, 0.5, 3.0]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let lambda = Float::with_val(precision, 0.25);
+    let fit = a.regularized_least_squares(&b, LambdaStrategy::Fixed(lambda.clone()));
+    let cg = cg_regularized(&a, &b, 50, lambda.clone());
+    for (p, q) in fit.x.iter().zip(&cg) {
+        assert!((p - q).complete(precision).abs() < 1e-30);
+    }
+    assert_eq!(fit.lambda, lambda);
+
+    let r: Vec<Float> = a
+        .matmul_vec(&fit.x)
+        .iter()
+        .zip(&b)
+        .map(|(p, q)| (p - q).complete(precision))
+        .collect();
+    let err = (RugMat::norm2_vec(&r) - &fit.residual_norm).abs();
+    assert!(err < 1e-30, "residual norm off by {}", err);
+}
+
+#[test]
+fn test_chosen_lambda_regularizes_a_discrete_ill_posed_problem() {
+    use crate::stochastic::gaussian_matrix;
+    // σⱼ = 10^(-j/2) with x's coefficients decaying like σⱼ (the discrete
+    // Picard condition), and white noise of size 1e-4 per entry
+    let (m, n) = (40, 20);
+    for precision in [64, 256] {
+        let u = gaussian_matrix(m, n, precision, 11).qr().q_thin();
+        let v = gaussian_matrix(n, n, precision, 12).qr().q_thin();
+        let sigma: Vec<Float> = (0..n)
+            .map(|j| Float::with_val(precision, 10).pow(-(j as f64) / 2.0))
+            .collect();
+        let mut us = u.clone();
+        us.scale_cols(&sigma);
+        let a = us.matmul(&v.transpose());
+        let x_true = v.matmul_vec(&sigma);
+        let noise = gaussian_matrix(m, 1, precision, 14);
+        let b: Vec<Float> = a
+            .matmul_vec(&x_true)
+            .into_iter()
+            .zip(noise.as_slice())
+            .map(|(bi, e)| bi + (e * 1e-4f64).complete(precision))
+            .collect();
+
+        let error = |x: &[Float]| {
+            let d: Vec<Float> = x
+                .iter()
+                .zip(&x_true)
+                .map(|(p, q)| (p - q).complete(precision))
+                .collect();
+            RugMat::norm2_vec(&d).to_f64()
+        };
+        let scale = RugMat::norm2_vec(&x_true).to_f64();
+        let unregularized =
+            a.regularized_least_squares(&b, LambdaStrategy::Fixed(Float::with_val(precision, 0)));
+        assert!(error(&unregularized.x) > 1e3 * scale);
+
+        for strategy in [LambdaStrategy::GCV, LambdaStrategy::LCurve { grid: 80 }] {
+            let fit = a.regularized_least_squares(&b, strategy.clone());
+            let e = error(&fit.x);
+            assert!(
+                e < 0.2 * scale,
+                "{:?} at {} bits: λ = {}, error {} for ‖x‖ = {}",
+                strategy,
+                precision,
+                fit.lambda,
+                e,
+                scale
+            );
+        }
+    }
+}
+
+#[test]
+fn test_filter_factors_and_resolution() {
+    let precision = 128;
+    let (m, n) = (7, 4);
+    let mut a = RugMat::new(m, n, precision);
+    for j in 0..n {
+        for i in 0..m {
+            a[(i, j)] = Float::with_val(precision, 1) / (i + j + 1) as u32;
+        }
+    }
+
+    // Tikhonov: the resolution matrix is (AᵗA + λI)⁻¹AᵗA
+    let lambda = Float::with_val(precision, 1e-4);
+    let analysis = a.filter_factors(Filter::Tikhonov(lambda.clone()));
+    let ata = a.transpose().matmul(&a);
+    let mut shifted = ata.clone();
+    for j in 0..n {
+        shifted[(j, j)] += &lambda;
+    }
+    let resolution = shifted.solve_mat(&ata).unwrap();
+    for (j, rj) in analysis.resolution.iter().enumerate() {
+        assert!((rj.clone() - &resolution[(j, j)]).abs() < 1e-25);
+    }
+    for (f, sigma) in analysis.factors.iter().zip(&analysis.singular_values) {
+        assert!(*f > 0 && *f < 1);
+        let expected = sigma.clone().square() / (sigma.clone().square() + &lambda);
+        assert!((f - &expected).complete(precision).abs() < 1e-30);
+    }
+    // Hilbert-like singular values decay fast, so λ = 1e-4 filters the tail
+    assert!(analysis.factors[0].clone() - 1u32 > -1e-3);
+    assert!(analysis.factors[n - 1] < 1e-3);
+    let trace = analysis
+        .resolution
+        .iter()
+        .fold(Float::with_val(precision, 0), |t, r| t + r);
+    assert!((trace - analysis.effective_dof()).abs() < 1e-30);
+
+    // Truncated SVD: V_k V_kᵗ is a projection whose trace is k
+    let analysis = a.filter_factors(Filter::Truncated(2));
+    assert_eq!(analysis.factors[1], 1);
+    assert_eq!(analysis.factors[2], 0);
+    assert!((analysis.effective_dof() - 2u32).abs() < 1e-35);
+    for r in &analysis.resolution {
+        assert!(*r >= 0 && r.clone() - 1u32 < 1e-35);
+    }
+    let full = a.filter_factors(Filter::Truncated(n));
+    for r in &full.resolution {
+        assert!((r.clone() - 1u32).abs() < 1e-35);
+    }
+}
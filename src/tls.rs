@@ -0,0 +1,173 @@
+// tls.rs: total least squares for errors-in-variables problems
+use crate::RugMat;
+use crate::svd::{JACOBI_SWEEPS, working_tol};
+use rug::Float;
+use std::fmt;
+
+/// Result of [`RugMat::total_least_squares`]
+#[derive(Debug, Clone)]
+pub struct TotalLeastSquares {
+    pub x: Vec<Float>,
+    /// ‖[ΔA Δb]‖_F = σₙ₊₁([A b]), the smallest correction making the system
+    /// consistent
+    pub correction_norm: Float,
+    /// ‖ΔA‖_F, the part of the correction applied to A
+    pub matrix_correction_norm: Float,
+    /// ‖Δb‖, the part applied to b
+    pub rhs_correction_norm: Float,
+}
+
+/// Returned by [`RugMat::total_least_squares`] when the TLS problem has no
+/// unique solution
+#[derive(Debug, Clone, PartialEq)]
+pub enum TlsError {
+    /// σₙ([A b]) = σₙ₊₁([A b]), so the smallest correction is not unique
+    NotUnique,
+    /// The last right singular vector has no b component, so no correction
+    /// of minimal norm puts b in the range of A + ΔA
+    Nongeneric,
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::NotUnique => {
+                write!(f, "smallest singular value of [A b] is repeated")
+            }
+            TlsError::Nongeneric => {
+                write!(
+                    f,
+                    "nongeneric problem: b is orthogonal to the TLS direction"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+impl RugMat {
+    /// Total least squares: the x for which A x = b holds after the
+    /// correction [ΔA Δb] of smallest Frobenius norm, for noise in A as well
+    /// as in b.
+    ///
+    /// With v the right singular vector of [A b] for its smallest singular
+    /// value σₙ₊₁, x = -v[..n]/vₙ and [ΔA Δb] = -σₙ₊₁ u vᵗ. The Jacobi SVD
+    /// keeps σₙ₊₁ accurate relative to itself, which matters when the
+    /// correction is small.
+    pub fn total_least_squares(&self, b: &[Float]) -> Result<TotalLeastSquares, TlsError> {
+        let (m, n) = (self.nrows(), self.ncols());
+        assert_eq!(b.len(), m, "b must have one entry per row");
+        assert!(m > n, "TLS requires more rows than columns ({}×{})", m, n);
+        let precision = self.as_slice()[0].prec();
+        let mut data = self.as_slice().to_vec();
+        data.extend(b.iter().map(|bi| Float::with_val(precision, bi)));
+        let augmented = RugMat::from_col_major(m, n + 1, data);
+        let svd = augmented.svd_jacobi(JACOBI_SWEEPS, working_tol(precision));
+
+        let tol = (n + 1) as f64 * working_tol(precision);
+        let smallest = &svd.s[n];
+        if n > 0 && (svd.s[n - 1].clone() - smallest) / &svd.s[0] <= tol {
+            return Err(TlsError::NotUnique);
+        }
+        let last = svd.vt[(n, n)].clone();
+        if last.clone().abs() <= tol {
+            return Err(TlsError::Nongeneric);
+        }
+
+        let x = (0..n).map(|j| -(svd.vt[(n, j)].clone() / &last)).collect();
+        let along_a = (0..n).fold(Float::with_val(precision, 0), |acc, j| {
+            acc + svd.vt[(n, j)].clone().square()
+        });
+        Ok(TotalLeastSquares {
+            x,
+            matrix_correction_norm: along_a.sqrt() * smallest,
+            rhs_correction_norm: last.abs() * smallest,
+            correction_norm: smallest.clone(),
+        })
+    }
+
+    /// Just the solution of [`RugMat::total_least_squares`]
+    pub fn solve_tls(&self, b: &[Float]) -> Result<Vec<Float>, TlsError> {
+        self.total_least_squares(b).map(|tls| tls.x)
+    }
+}
+
+#[test]
+fn test_tls_matches_closed_form() {
+    use crate::stochastic::gaussian_matrix;
+    let precision = 160;
+    let (m, n) = (15, 3);
+    let clean = gaussian_matrix(m, n, precision, 31);
+    let x_true: Vec<Float> = [2.0, -1.0, 0.5]
+        .iter()
+        .map(|&v| Float::with_val(precision, v))
+        .collect();
+    let b_clean = clean.matmul_vec(&x_true);
+    // Errors in the variables as well as the observations
+    let a_noise = gaussian_matrix(m, n, precision, 32);
+    let b_noise = gaussian_matrix(m, 1, precision, 33);
+    let mut a = clean.clone();
+    for (aij, e) in a.as_mut_slice().iter_mut().zip(a_noise.as_slice()) {
+        *aij += e.clone() * 1e-3f64;
+    }
+    let b: Vec<Float> = b_clean
+        .iter()
+        .zip(b_noise.as_slice())
+        .map(|(bi, e)| bi.clone() + e.clone() * 1e-3f64)
+        .collect();
+
+    let tls = a.total_least_squares(&b).unwrap();
+    assert_eq!(a.solve_tls(&b).unwrap(), tls.x);
+
+    // x = (AᵗA - σₙ₊₁²I)⁻¹ Aᵗb
+    let sigma2 = tls.correction_norm.clone().square();
+    let mut shifted = a.transpose().matmul(&a);
+    for j in 0..n {
+        shifted[(j, j)] -= &sigma2;
+    }
+    let expected = shifted.solve(&a.matmul_transpose_vec(&b)).unwrap();
+    for (xi, ei) in tls.x.iter().zip(&expected) {
+        assert!((xi.clone() - ei).abs() < 1e-35);
+    }
+    for (xi, ti) in tls.x.iter().zip(&x_true) {
+        assert!((xi.clone() - ti).abs() < 1e-2);
+    }
+
+    // σₙ₊₁² = ‖Ax - b‖² / (1 + ‖x‖²), split as ‖ΔA‖² + ‖Δb‖²
+    let residual: Vec<Float> = a
+        .matmul_vec(&tls.x)
+        .into_iter()
+        .zip(&b)
+        .map(|(p, q)| p - q)
+        .collect();
+    let x_norm2 = RugMat::norm2_vec(&tls.x).square();
+    let ratio = RugMat::norm2_vec(&residual).square() / (x_norm2.clone() + 1u32);
+    assert!(((ratio - &sigma2) / &sigma2).abs() < 1e-35);
+    let split =
+        tls.matrix_correction_norm.clone().square() + tls.rhs_correction_norm.clone().square();
+    assert!(((split - &sigma2) / &sigma2).abs() < 1e-35);
+    let ratio =
+        tls.matrix_correction_norm.clone().square() / tls.rhs_correction_norm.clone().square();
+    assert!(((ratio - x_norm2.clone()) / x_norm2).abs() < 1e-35);
+}
+
+#[test]
+fn test_tls_rejects_degenerate_problems() {
+    let precision = 128;
+    let column = |v: [f64; 3]| v.map(|e| Float::with_val(precision, e)).to_vec();
+
+    // [A b] = I₃: every direction is equally small
+    let mut data = column([1.0, 0.0, 0.0]);
+    data.extend(column([0.0, 1.0, 0.0]));
+    let a = RugMat::from_col_major(3, 2, data);
+    let b = column([0.0, 0.0, 1.0]);
+    assert_eq!(a.total_least_squares(&b).unwrap_err(), TlsError::NotUnique);
+
+    // A zero column of A is the TLS direction, and b has no part in it
+    let mut data = column([1.0, 0.0, 0.0]);
+    data.extend(column([0.0, 0.0, 0.0]));
+    let a = RugMat::from_col_major(3, 2, data);
+    let b = column([1.0, 0.0, 1.0]);
+    assert_eq!(a.total_least_squares(&b).unwrap_err(), TlsError::Nongeneric);
+}
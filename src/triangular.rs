@@ -0,0 +1,120 @@
+// triangular.rs: forward and backward substitution against a triangle of a RugMat
+use crate::RugMat;
+use rayon::prelude::*;
+use rug::Float;
+use rug::ops::CompleteRound;
+
+/// Overwrite x with T⁻¹x for the lower triangle of t, column by column
+fn forward_in_place(t: &RugMat, x: &mut [Float], unit: bool) {
+    let n = t.nrows();
+    for j in 0..n {
+        if !unit {
+            x[j] /= &t[(j, j)];
+        }
+        let (done, rest) = x.split_at_mut(j + 1);
+        let col = &t.as_slice()[j * n + j + 1..(j + 1) * n];
+        for (xi, tij) in rest.iter_mut().zip(col) {
+            *xi -= (tij * &done[j]).complete(xi.prec());
+        }
+    }
+}
+
+/// Overwrite x with T⁻¹x for the upper triangle of t, column by column
+fn backward_in_place(t: &RugMat, x: &mut [Float], unit: bool) {
+    let n = t.nrows();
+    for j in (0..n).rev() {
+        if !unit {
+            x[j] /= &t[(j, j)];
+        }
+        let (rest, done) = x.split_at_mut(j);
+        let col = &t.as_slice()[j * n..j * n + j];
+        for (xi, tij) in rest.iter_mut().zip(col) {
+            *xi -= (tij * &done[0]).complete(xi.prec());
+        }
+    }
+}
+
+impl RugMat {
+    /// Apply a substitution kernel to every column of b in parallel
+    fn triangular_solve(
+        &self,
+        b: &RugMat,
+        unit: bool,
+        kernel: fn(&RugMat, &mut [Float], bool),
+    ) -> RugMat {
+        assert!(
+            self.is_square(),
+            "Triangular solve needs a square matrix, got {}",
+            self.shape()
+        );
+        assert_eq!(
+            b.nrows(),
+            self.nrows(),
+            "Right-hand side has the wrong row count"
+        );
+        let mut x = b.clone();
+        x.as_mut_slice()
+            .par_chunks_mut(b.nrows())
+            .for_each(|col| kernel(self, col, unit));
+        x
+    }
+
+    /// Solve L X = B by forward substitution, reading only the lower
+    /// triangle of self; the columns of B are solved in parallel
+    pub fn solve_lower_triangular(&self, b: &RugMat) -> RugMat {
+        self.triangular_solve(b, false, forward_in_place)
+    }
+
+    /// Solve U X = B by backward substitution, reading only the upper
+    /// triangle of self; the columns of B are solved in parallel
+    pub fn solve_upper_triangular(&self, b: &RugMat) -> RugMat {
+        self.triangular_solve(b, false, backward_in_place)
+    }
+
+    /// As [`RugMat::solve_lower_triangular`] with an implied unit diagonal,
+    /// so the strict lower triangle of a packed LU factor can be used as is
+    pub fn solve_unit_lower_triangular(&self, b: &RugMat) -> RugMat {
+        self.triangular_solve(b, true, forward_in_place)
+    }
+
+    /// As [`RugMat::solve_upper_triangular`] with an implied unit diagonal
+    pub fn solve_unit_upper_triangular(&self, b: &RugMat) -> RugMat {
+        self.triangular_solve(b, true, backward_in_place)
+    }
+}
+
+#[test]
+fn test_triangular_solves_ignore_the_other_triangle() {
+    let precision = 128;
+    let f = |v: i32| Float::with_val(precision, v);
+    // Packed storage: strict lower part is a unit L, upper part is U
+    let packed = RugMat::from_vecvec(vec![
+        vec![f(2), f(1), f(-1)],
+        vec![f(3), f(4), f(2)],
+        vec![f(-2), f(5), f(3)],
+    ]);
+    let mut l = RugMat::identity(3, precision);
+    let mut u = RugMat::new(3, 3, precision);
+    for j in 0..3 {
+        for i in 0..3 {
+            if i > j {
+                l[(i, j)] = packed[(i, j)].clone();
+            } else {
+                u[(i, j)] = packed[(i, j)].clone();
+            }
+        }
+    }
+    let b = RugMat::from_vecvec(vec![vec![f(1), f(0)], vec![f(-2), f(7)], vec![f(4), f(3)]]);
+
+    let y = packed.solve_unit_lower_triangular(&b);
+    assert!(l.matmul(&y).compare(&b).max_abs < 1e-35);
+    let x = packed.solve_upper_triangular(&y);
+    assert!(u.matmul(&x).compare(&y).max_abs < 1e-35);
+
+    // Non-unit lower and unit upper against their explicit triangles
+    let pt = packed.transpose();
+    let lx = pt.solve_lower_triangular(&b);
+    assert!(u.transpose().matmul(&lx).compare(&b).max_abs < 1e-35);
+    let ux = pt.solve_unit_upper_triangular(&b);
+    assert!(l.transpose().matmul(&ux).compare(&b).max_abs < 1e-35);
+}
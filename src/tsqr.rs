@@ -0,0 +1,346 @@
+// tsqr.rs: communication-avoiding tall-skinny QR with tree reduction
+use crate::RugMat;
+use crate::qr::back_substitute;
+use rayon::prelude::*;
+use rug::Float;
+use rug::float::Special;
+
+/// Rows [start, end) of a as a new matrix
+fn row_block(a: &RugMat, start: usize, end: usize) -> RugMat {
+    let precision = a.as_slice()[0].prec();
+    let mut block = RugMat::new(end - start, a.ncols(), precision);
+    block
+        .as_mut_slice()
+        .par_chunks_mut(end - start)
+        .zip(a.as_slice().par_chunks(a.nrows()))
+        .for_each(|(dst, src)| dst.clone_from_slice(&src[start..end]));
+    block
+}
+
+/// R factor of the 2n×n matrix [top; bottom]
+pub(crate) fn merge_r(top: &RugMat, bottom: &RugMat) -> RugMat {
+    let mut stacked = RugMat::new(
+        top.nrows() + bottom.nrows(),
+        top.ncols(),
+        top.as_slice()[0].prec(),
+    );
+    stacked.set_block(0, 0, top);
+    stacked.set_block(top.nrows(), 0, bottom);
+    stacked.qr().r()
+}
+
+impl RugMat {
+    /// R factor of a tall m×n matrix by TSQR.
+    ///
+    /// The rows are split into blocks of at least max(block_rows, n) rows,
+    /// each block is factored in parallel, and the n×n R factors are merged
+    /// pairwise in a binary tree. R agrees with `qr().r()` up to the signs
+    /// of its rows.
+    pub fn tsqr(&self, block_rows: usize) -> RugMat {
+        let (m, n) = (self.nrows(), self.ncols());
+        assert!(m >= n, "TSQR requires rows >= cols (got {}×{})", m, n);
+        let blocks = (m / block_rows.max(n).max(1)).max(1);
+
+        let mut rs: Vec<RugMat> = (0..blocks)
+            .into_par_iter()
+            .map(|b| {
+                row_block(self, b * m / blocks, (b + 1) * m / blocks)
+                    .qr()
+                    .r()
+            })
+            .collect();
+        while rs.len() > 1 {
+            rs = rs
+                .par_chunks(2)
+                .map(|pair| match pair {
+                    [top, bottom] => merge_r(top, bottom),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        rs.pop().unwrap()
+    }
+
+    /// Least-squares solution of min ‖Ax - b‖ for full column rank A via TSQR.
+    ///
+    /// Factors [A b] so that the last column of its R factor carries Qᵗb;
+    /// Q itself is never formed.
+    pub fn tsqr_least_squares(&self, b: &[Float], block_rows: usize) -> Vec<Float> {
+        assert_eq!(b.len(), self.nrows());
+        let n = self.ncols();
+        let mut augmented = self.clone();
+        augmented.insert_column(n, b);
+        let r = augmented.tsqr(block_rows);
+        let c: Vec<Float> = (0..n).map(|i| r[(i, n)].clone()).collect();
+        back_substitute(&r, &c)
+    }
+}
+
+/// Streaming least squares that keeps only the R factor of [A b].
+///
+/// Each pushed chunk is reduced by TSQR and merged into the running
+/// (n+1)×(n+1) factor, so memory stays O(n²) however many rows arrive.
+#[derive(Debug, Clone)]
+pub struct IncrementalLstsq {
+    r: RugMat,
+    rows_seen: usize,
+    block_rows: usize,
+    /// Relative rank tolerance when condition monitoring is on
+    rank_tol: Option<f64>,
+    condition_history: Vec<(usize, Float)>,
+    /// Rank verdict of the last monitored push, None before the first
+    rank_deficient: Option<bool>,
+    /// Row count after the last push that made a full-rank A deficient
+    rank_lost_at: Option<usize>,
+}
+
+impl IncrementalLstsq {
+    /// Empty accumulator for n unknowns; block_rows is passed on to `tsqr`
+    pub fn new(cols: usize, precision: u32, block_rows: usize) -> Self {
+        IncrementalLstsq {
+            r: RugMat::new(cols + 1, cols + 1, precision),
+            rows_seen: 0,
+            block_rows,
+            rank_tol: None,
+            condition_history: Vec::new(),
+            rank_deficient: None,
+            rank_lost_at: None,
+        }
+    }
+
+    /// Track the condition of A as rows arrive.
+    ///
+    /// After every push the estimate max|rᵢᵢ| / min|rᵢᵢ| is recorded, and
+    /// [`IncrementalLstsq::rank_lost_at`] notes when a chunk turns a
+    /// full-rank problem into one with min|rᵢᵢ| <= rank_tol·max|rᵢᵢ|.
+    /// Extra rows never decrease the singular values, but badly scaled rows
+    /// can raise the largest ones far enough to swamp the smallest.
+    pub fn monitor_condition(&mut self, rank_tol: f64) {
+        self.rank_tol = Some(rank_tol);
+    }
+
+    /// Fold the rows of chunk, with right-hand sides rhs_chunk, into R
+    pub fn push_rows(&mut self, chunk: &RugMat, rhs_chunk: &[Float]) {
+        let n = self.r.ncols() - 1;
+        assert_eq!(chunk.ncols(), n, "Chunk has the wrong number of columns");
+        assert_eq!(chunk.nrows(), rhs_chunk.len());
+        if chunk.nrows() == 0 {
+            return;
+        }
+        let mut augmented = chunk.clone();
+        augmented.insert_column(n, rhs_chunk);
+        self.r = if augmented.nrows() > n {
+            merge_r(&self.r, &augmented.tsqr(self.block_rows))
+        } else {
+            merge_r(&self.r, &augmented)
+        };
+        self.rows_seen += chunk.nrows();
+
+        if let Some(rank_tol) = self.rank_tol {
+            let (min, max) = self.diagonal_range();
+            let deficient = min <= max.clone() * rank_tol;
+            if deficient && self.rank_deficient == Some(false) {
+                self.rank_lost_at = Some(self.rows_seen);
+            }
+            self.rank_deficient = Some(deficient);
+            self.condition_history
+                .push((self.rows_seen, self.condition_estimate()));
+        }
+    }
+
+    /// Smallest and largest |rᵢᵢ| of the n×n factor of A
+    fn diagonal_range(&self) -> (Float, Float) {
+        let n = self.r.ncols() - 1;
+        let precision = self.r.as_slice()[0].prec();
+        let mut min = Float::with_val(precision, Special::Infinity);
+        let mut max = Float::with_val(precision, 0);
+        for i in 0..n {
+            let d = self.r[(i, i)].clone().abs();
+            if d < min {
+                min = d.clone();
+            }
+            if d > max {
+                max = d;
+            }
+        }
+        (min, max)
+    }
+
+    /// Condition estimate max|rᵢᵢ| / min|rᵢᵢ| of the rows seen so far, a
+    /// lower bound on κ₂(A) that is usually within a small factor of it;
+    /// +∞ while R is singular
+    pub fn condition_estimate(&self) -> Float {
+        let (min, max) = self.diagonal_range();
+        if min.is_zero() {
+            return Float::with_val(max.prec(), Special::Infinity);
+        }
+        max / min
+    }
+
+    /// Row count and condition estimate after each push since
+    /// [`IncrementalLstsq::monitor_condition`] was called
+    pub fn condition_history(&self) -> &[(usize, Float)] {
+        &self.condition_history
+    }
+
+    /// Whether the last monitored push left A numerically rank deficient;
+    /// false until [`IncrementalLstsq::monitor_condition`] has seen a push
+    pub fn is_rank_deficient(&self) -> bool {
+        self.rank_deficient == Some(true)
+    }
+
+    /// Rows seen when a monitored push last made a full-rank A numerically
+    /// rank deficient; None if that never happened
+    pub fn rank_lost_at(&self) -> Option<usize> {
+        self.rank_lost_at
+    }
+
+    /// Total number of rows pushed so far
+    pub fn rows_seen(&self) -> usize {
+        self.rows_seen
+    }
+
+    /// The current n×n triangular factor R of A
+    pub fn r(&self) -> RugMat {
+        let n = self.r.ncols() - 1;
+        let mut r = RugMat::new(n, n, self.r.as_slice()[0].prec());
+        for j in 0..n {
+            for i in 0..=j {
+                r[(i, j)] = self.r[(i, j)].clone();
+            }
+        }
+        r
+    }
+
+    /// The leading n entries of Qᵗb
+    pub fn qtb(&self) -> Vec<Float> {
+        let n = self.r.ncols() - 1;
+        (0..n).map(|i| self.r[(i, n)].clone()).collect()
+    }
+
+    /// Residual norm ‖Ax - b‖ of the least-squares solution
+    pub fn residual_norm(&self) -> Float {
+        let n = self.r.ncols() - 1;
+        self.r[(n, n)].clone().abs()
+    }
+
+    /// Least-squares solution for the rows seen so far
+    pub fn solve(&self) -> Vec<Float> {
+        back_substitute(&self.r, &self.qtb())
+    }
+}
+
+#[test]
+fn test_tsqr_matches_householder_r() {
+    let precision = 128;
+    let m = 37;
+    let mut a = RugMat::new(m, 3, precision);
+    let mut b = Vec::with_capacity(m);
+    for i in 0..m {
+        let t = Float::with_val(precision, i) / 10u32;
+        a[(i, 0)] = Float::with_val(precision, 1);
+        a[(i, 1)] = t.clone();
+        a[(i, 2)] = t.clone().square();
+        // 2 - t + t²/2 plus a small alternating perturbation
+        let pert = if i % 2 == 0 { 1e-3 } else { -1e-3 };
+        b.push(2 - t.clone() + t.square() / 2u32 + pert);
+    }
+
+    let r_tsqr = a.tsqr(5);
+    let r_ref = a.qr().r();
+    assert!(r_tsqr.is_upper_triangular());
+    for i in 0..3 {
+        for j in i..3 {
+            let diff = r_tsqr[(i, j)].clone().abs() - r_ref[(i, j)].clone().abs();
+            assert!(diff.abs() < 1e-30);
+        }
+    }
+
+    let x = a.tsqr_least_squares(&b, 5);
+    let x_ref = a.qr().solve_least_squares(&b);
+    for (xi, ri) in x.iter().zip(&x_ref) {
+        assert!((xi.clone() - ri).abs() < 1e-30);
+    }
+}
+
+#[test]
+fn test_incremental_lstsq_matches_batch() {
+    use rug::ops::CompleteRound;
+
+    let precision = 128;
+    let m = 23;
+    let mut a = RugMat::new(m, 2, precision);
+    let mut b = Vec::with_capacity(m);
+    for i in 0..m {
+        a[(i, 0)] = Float::with_val(precision, 1);
+        a[(i, 1)] = Float::with_val(precision, i);
+        b.push(Float::with_val(precision, (i * i) % 7));
+    }
+
+    // Uneven chunk sizes, including ones shorter than n + 1
+    let mut inc = IncrementalLstsq::new(2, precision, 4);
+    let mut start = 0;
+    for len in [1, 2, 9, 11] {
+        let chunk = row_block(&a, start, start + len);
+        inc.push_rows(&chunk, &b[start..start + len]);
+        start += len;
+    }
+    assert_eq!(inc.rows_seen(), m);
+
+    let x = inc.solve();
+    let x_ref = a.qr().solve_least_squares(&b);
+    for (xi, ri) in x.iter().zip(&x_ref) {
+        assert!((xi.clone() - ri).abs() < 1e-30);
+    }
+
+    let ax = a.matmul_vec(&x);
+    let residual: Vec<Float> = ax
+        .iter()
+        .zip(&b)
+        .map(|(p, q)| (p - q).complete(precision))
+        .collect();
+    let expected = RugMat::norm2_vec(&residual);
+    assert!((inc.residual_norm() - expected).abs() < 1e-30);
+}
+
+#[test]
+fn test_incremental_lstsq_condition_monitoring() {
+    let precision = 128;
+    let mut inc = IncrementalLstsq::new(2, precision, 4);
+    let rows = |v: &[(f64, f64)]| {
+        RugMat::from_vecvec(
+            v.iter()
+                .map(|&(p, q)| vec![Float::with_val(precision, p), Float::with_val(precision, q)])
+                .collect(),
+        )
+    };
+    let zeros = |k| vec![Float::with_val(precision, 0); k];
+
+    // Without monitoring there is no rank verdict
+    let mut unmonitored = IncrementalLstsq::new(2, precision, 4);
+    unmonitored.push_rows(&rows(&[(1.0, 2.0)]), &zeros(1));
+    assert!(!unmonitored.is_rank_deficient());
+    assert!(unmonitored.condition_history().is_empty());
+
+    // One row cannot determine two unknowns
+    inc.monitor_condition(1e-20);
+    inc.push_rows(&rows(&[(1.0, 2.0)]), &zeros(1));
+    assert!(inc.is_rank_deficient());
+    assert!(inc.condition_estimate().is_infinite());
+    assert_eq!(inc.rank_lost_at(), None);
+
+    inc.push_rows(&rows(&[(0.0, 1.0), (1.0, 0.0)]), &zeros(2));
+    assert!(!inc.is_rank_deficient());
+    let kappa = inc.condition_estimate();
+    assert!(kappa > 1 && kappa < 10);
+
+    // A huge row along (1, 2) swamps the orthogonal direction
+    inc.push_rows(&rows(&[(1e30, 2e30)]), &zeros(1));
+    assert!(inc.is_rank_deficient());
+    assert_eq!(inc.rank_lost_at(), Some(4));
+    assert!(inc.condition_estimate() > 1e20);
+    let history = inc.condition_history();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[2].0, 4);
+}